@@ -2,58 +2,13 @@ use crate::{model, texture};
 use std::io::{BufReader, Cursor};
 use wgpu::util::DeviceExt;
 
-#[cfg(target_arch = "wasm32")]
-fn format_url(file_name: &str) -> reqwest::Url {
-    let window = web_sys::window().unwrap();
-    let location = window.location();
-    let base = reqwest::Url::parse(&format!(
-        "{}/{}/",
-        location.origin().unwrap(),
-        option_env!("RES_PATH").unwrap_or("res"),
-    ))
-    .unwrap();
-    base.join(file_name).unwrap()
-}
-
 pub async fn load_string(file_name: &str) -> anyhow::Result<String> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let txt = reqwest::get(url)
-                .await?
-                .text()
-                .await?;
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let txt = std::fs::read_to_string(path)?;
-        }
-    }
-
-    Ok(txt)
+    Ok(String::from_utf8(load_binary(file_name).await?)?)
 }
 
 pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let data = reqwest::get(url)
-                .await?
-                .bytes()
-                .await?
-                .to_vec();
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let data = std::fs::read(path)?;
-        }
-    }
-
-    Ok(data)
+    utils::assets::mount_out_dir_res(env!("OUT_DIR"));
+    Ok(utils::assets::load(file_name).await?)
 }
 
 pub async fn load_texture(
@@ -2,10 +2,12 @@ use app_surface::AppSurface;
 use bytemuck::{Pod, Zeroable};
 use rand::Rng;
 use utils::{
-    AnyTexture, BufferObj, load_texture, matrix_helper,
+    AnyTexture, BufferObj,
+    load_texture::{self, ColorSpace, TextureLoadOptions},
+    matrix_helper,
     node::{BindGroupData, ComputeNode},
 };
-use wgpu::{BufferAddress, BufferUsages, TextureUsages};
+use wgpu::{BufferUsages, TextureUsages};
 
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable, Default)]
@@ -37,20 +39,23 @@ pub(crate) struct ParticleGen {
     compute_node: ComputeNode,
     /// 粒子生成时的计数器
     counter_buf: BufferObj,
-    /// 用于从 gpu 读取数据的缓冲区
-    staging_buf: wgpu::Buffer,
 }
 
 impl ParticleGen {
     pub async fn new(app: &AppSurface, fovy: f32) -> Self {
         // 加载文字内容纹理
-        let (text_tex, sampler) = load_texture::from_path(
+        // 着色器按原始颜色值判断像素是否有文字，所以按线性数据加载
+        let (text_tex, sampler) = load_texture::from_path_with_options(
             "assets/text.jpg",
             app,
             TextureUsages::TEXTURE_BINDING,
-            false,
+            TextureLoadOptions {
+                color_space: ColorSpace::Linear,
+                ..Default::default()
+            },
         )
-        .await;
+        .await
+        .unwrap();
 
         let (p_matrix, mv_matrix) = matrix_helper::perspective_fullscreen_mvp(
             glam::Vec2 {
@@ -69,10 +74,6 @@ impl ParticleGen {
         // 实际有效粒子数量会远小于像素数量
         let particle_count = text_tex.size.width * text_tex.size.height;
         let mut particle_data: Vec<Particle> = Vec::with_capacity(particle_count as usize);
-        log::info!(
-            "粒子初始内存: {:?}MB",
-            particle_count as usize * core::mem::size_of::<Particle>() / 1024 / 1024
-        );
 
         // 使用 OsRng 替代 ThreadRng, 因为 OsRng 实现了 Send trait, 可以在异步上下文中正常工作
         let mut rng = rand::rngs::OsRng;
@@ -109,15 +110,8 @@ impl ParticleGen {
             BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
             Some("计数器"),
         );
-
-        let staging_buf = app.device.create_buffer(&wgpu::BufferDescriptor {
-            size: core::mem::size_of::<u32>() as BufferAddress,
-            usage: BufferUsages::COPY_DST
-            // MAP_READ 告诉 wpgu 我们要在 cpu 端读取此缓冲区
-            | BufferUsages::MAP_READ,
-            label: None,
-            mapped_at_creation: false,
-        });
+        // 粒子初始占用的显存
+        utils::resource_tracker::log_memory_report();
 
         // 着色器
         let gen_shader = app
@@ -136,7 +130,7 @@ impl ParticleGen {
             ..Default::default()
         };
 
-        let compute_node = ComputeNode::new(&app.device, &bind_group_data, &gen_shader);
+        let compute_node = ComputeNode::new(&app.device, &bind_group_data, &gen_shader).unwrap();
 
         let mut instance = Self {
             count: particle_count,
@@ -146,7 +140,6 @@ impl ParticleGen {
             mvp_buf,
             compute_node,
             counter_buf,
-            staging_buf,
         };
 
         instance.generate_particles(app).await;
@@ -164,45 +157,18 @@ impl ParticleGen {
         // 生成有效粒子
         self.compute_node.compute(&mut encoder);
 
-        // 将 gpu 上的计数器数据复制到 cpu 上的缓冲区
-        encoder.copy_buffer_to_buffer(
-            &self.counter_buf.buffer,
-            0,
-            &self.staging_buf,
-            0,
-            core::mem::size_of::<u32>() as u64,
-        );
-
         app.queue.submit(Some(encoder.finish()));
 
-        // 需要对映射变量设置范围，以便能够解除缓冲区的内存映射
-        let buffer_slice = self.staging_buf.slice(..);
-
-        // 注意：必须在 await future 之前先创建映射，然后再调用 device.poll()。
-        // 否则，应用程序将停止响应。
-        let (tx, rx) = flume::bounded(1);
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            tx.send(result).unwrap();
-        });
-        app.device
-            .poll(wgpu::PollType::wait_indefinitely())
+        // 从 gpu 读回有效粒子的数量
+        let counter = self
+            .counter_buf
+            .read::<u32>(&app.device, &app.queue)
+            .await
             .unwrap();
+        log::info!("从 gpu 读回的粒子数量: {:?}", counter[0]);
+        self.count = counter[0];
 
-        if let Ok(Ok(())) = rx.recv_async().await {
-            let data = buffer_slice.get_mapped_range().unwrap();
-            let result = *bytemuck::from_bytes::<u32>(&data);
-            log::info!("从 gpu 读回的粒子数量: {:?}", result);
-            self.count = result;
-
-            // 须确保在解除缓冲区映射之前已删除所有已映射的视图
-            drop(data);
-            // 解除缓冲区内存映射
-            self.staging_buf.unmap();
-
-            self.gen_final_particle_buf(app);
-        } else {
-            panic!("从 gpu 读取数据失败！");
-        }
+        self.gen_final_particle_buf(app);
     }
 
     fn gen_final_particle_buf(&mut self, app: &AppSurface) {
@@ -231,6 +197,6 @@ impl ParticleGen {
         self.particle_buf.buffer.destroy();
         self.particle_buf = final_particle_buf;
 
-        log::info!("粒子最终占用内存: {:?}MB", size / 1024 / 1024);
+        utils::resource_tracker::log_memory_report();
     }
 }
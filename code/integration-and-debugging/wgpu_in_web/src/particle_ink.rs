@@ -157,7 +157,7 @@ impl ParticleInk {
             .with_vertex_buffer_layouts(vertex_buffer_layouts)
             .with_use_depth_stencil(true)
             .with_color_format(app.config.format);
-        let display_node = builder.build(&app.device);
+        let display_node = builder.build(&app.device).unwrap();
 
         let interact_buf = BufferObj::create_uniform_buffer(
             &app.device,
@@ -177,7 +177,7 @@ impl ParticleInk {
             workgroup_count: ((generator.count as f32 / 64.0).ceil() as u32, 1, 1),
             ..Default::default()
         };
-        let move_node = ComputeNode::new(&app.device, &bind_group_data, &move_shader);
+        let move_node = ComputeNode::new(&app.device, &bind_group_data, &move_shader).unwrap();
 
         let depth_tex = create_depth_tex(app);
 
@@ -201,7 +201,8 @@ impl ParticleInk {
             &debug_shader,
             None,
             1,
-        );
+        )
+        .unwrap();
 
         Self {
             particle_count,
@@ -13,10 +13,34 @@ pub struct WgpuApp {
     particle_ink: ParticleInk,
 }
 
+/// 桌面端从本 crate 目录加载资源；
+/// web 端从站点根目录加载，部署在 learn-wgpu-zh 子路径下时以该子路径为根
+fn mount_assets() {
+    static MOUNT: std::sync::Once = std::sync::Once::new();
+    MOUNT.call_once(|| {
+        std::cfg_select! {
+            target_arch = "wasm32" => {
+                let location = web_sys::window().unwrap().location();
+                let origin = location.origin().unwrap();
+                let root = if location.pathname().unwrap_or_default().starts_with("/learn-wgpu-zh/") {
+                    "/learn-wgpu-zh/"
+                } else {
+                    "/"
+                };
+                utils::assets::mount(utils::assets::HttpSource::new(format!("{origin}{root}")), 0);
+            }
+            _ => {
+                utils::assets::mount(utils::assets::DirSource::new(env!("CARGO_MANIFEST_DIR")), 0);
+            }
+        }
+    });
+}
+
 impl WgpuApp {
     pub async fn new(app: AppSurface) -> Self {
         let size = uvec2(app.config.width, app.config.height);
 
+        mount_assets();
         let gen_node = ParticleGen::new(&app, 45.0_f32.to_radians()).await;
 
         let particle_ink = ParticleInk::new(&app, &gen_node);
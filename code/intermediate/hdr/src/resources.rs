@@ -4,58 +4,13 @@ use wgpu::util::DeviceExt;
 
 use crate::{model, texture};
 
-#[cfg(target_arch = "wasm32")]
-fn format_url(file_name: &str) -> reqwest::Url {
-    let window = web_sys::window().unwrap();
-    let location = window.location();
-    let base = reqwest::Url::parse(&format!(
-        "{}/{}/",
-        location.origin().unwrap(),
-        option_env!("RES_PATH").unwrap_or("res"),
-    ))
-    .unwrap();
-    base.join(file_name).unwrap()
-}
-
 pub async fn load_string(file_name: &str) -> anyhow::Result<String> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let txt = reqwest::get(url)
-                .await?
-                .text()
-                .await?;
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let txt = std::fs::read_to_string(path)?;
-        }
-    }
-
-    Ok(txt)
+    Ok(String::from_utf8(load_binary(file_name).await?)?)
 }
 
 pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let data = reqwest::get(url)
-                .await?
-                .bytes()
-                .await?
-                .to_vec();
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let data = std::fs::read(path)?;
-        }
-    }
-
-    Ok(data)
+    utils::assets::mount_out_dir_res(env!("OUT_DIR"));
+    Ok(utils::assets::load(file_name).await?)
 }
 
 pub async fn load_texture(
@@ -128,73 +83,17 @@ pub async fn load_model(
                 })
                 .collect::<Vec<_>>();
 
-            let indices = &m.mesh.indices;
-            let mut triangles_included = vec![0; vertices.len()];
-
-            // Calculate tangents and bitangets. We're going to
-            // use the triangles, so we need to loop through the
-            // indices in chunks of 3
-            for c in indices.chunks(3) {
-                let v0 = vertices[c[0] as usize];
-                let v1 = vertices[c[1] as usize];
-                let v2 = vertices[c[2] as usize];
-
-                let pos0: glam::Vec3 = v0.position.into();
-                let pos1: glam::Vec3 = v1.position.into();
-                let pos2: glam::Vec3 = v2.position.into();
-
-                let uv0: glam::Vec2 = v0.tex_coords.into();
-                let uv1: glam::Vec2 = v1.tex_coords.into();
-                let uv2: glam::Vec2 = v2.tex_coords.into();
-
-                // Calculate the edges of the triangle
-                let delta_pos1 = pos1 - pos0;
-                let delta_pos2 = pos2 - pos0;
-
-                // This will give us a direction to calculate the
-                // tangent and bitangent
-                let delta_uv1 = uv1 - uv0;
-                let delta_uv2 = uv2 - uv0;
-
-                // Solving the following system of equations will
-                // give us the tangent and bitangent.
-                //     delta_pos1 = delta_uv1.x * T + delta_u.y * B
-                //     delta_pos2 = delta_uv2.x * T + delta_uv2.y * B
-                // Luckily, the place I found this equation provided
-                // the solution!
-                let r = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv1.y * delta_uv2.x);
-                let tangent = (delta_pos1 * delta_uv2.y - delta_pos2 * delta_uv1.y) * r;
-                // We flip the bitangent to enable right-handed normal
-                // maps with wgpu texture coordinate system
-                let bitangent = (delta_pos2 * delta_uv1.x - delta_pos1 * delta_uv2.x) * -r;
-
-                // We'll use the same tangent/bitangent for each vertex in the triangle
-                vertices[c[0] as usize].tangent =
-                    (tangent + glam::Vec3::from(vertices[c[0] as usize].tangent)).into();
-                vertices[c[1] as usize].tangent =
-                    (tangent + glam::Vec3::from(vertices[c[1] as usize].tangent)).into();
-                vertices[c[2] as usize].tangent =
-                    (tangent + glam::Vec3::from(vertices[c[2] as usize].tangent)).into();
-                vertices[c[0] as usize].bitangent =
-                    (bitangent + glam::Vec3::from(vertices[c[0] as usize].bitangent)).into();
-                vertices[c[1] as usize].bitangent =
-                    (bitangent + glam::Vec3::from(vertices[c[1] as usize].bitangent)).into();
-                vertices[c[2] as usize].bitangent =
-                    (bitangent + glam::Vec3::from(vertices[c[2] as usize].bitangent)).into();
-
-                // Used to average the tangents/bitangents
-                triangles_included[c[0] as usize] += 1;
-                triangles_included[c[1] as usize] += 1;
-                triangles_included[c[2] as usize] += 1;
-            }
-
-            // Average the tangents/bitangents
-            for (i, n) in triangles_included.into_iter().enumerate() {
-                let denom = 1.0 / n as f32;
-                let v = &mut vertices[i];
-                v.tangent = (glam::Vec3::from(v.tangent) * denom).into();
-                v.bitangent = (glam::Vec3::from(v.bitangent) * denom).into();
-            }
+            // Calculate tangents with MikkTSpace. Triangles with degenerate
+            // texture coordinates won't produce NaNs
+            utils::mesh::write_tangent_frames(
+                &mut vertices,
+                &m.mesh.indices,
+                |v| (v.position, v.normal, v.tex_coords),
+                |v, tangent, bitangent| {
+                    v.tangent = tangent;
+                    v.bitangent = bitangent;
+                },
+            );
 
             let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some(&format!("{file_name:?} Vertex Buffer")),
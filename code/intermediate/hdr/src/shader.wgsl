@@ -3,9 +3,11 @@
 struct Camera {
     view_pos: vec4<f32>,
     view: mat4x4<f32>,
+    proj: mat4x4<f32>,
     view_proj: mat4x4<f32>,
-    inv_proj: mat4x4<f32>,
     inv_view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view_proj: mat4x4<f32>,
 }
 @group(1) @binding(0)
 var<uniform> camera: Camera;
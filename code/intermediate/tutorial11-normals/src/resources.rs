@@ -3,58 +3,13 @@ use wgpu::util::DeviceExt;
 
 use crate::{model, texture};
 
-#[cfg(target_arch = "wasm32")]
-fn format_url(file_name: &str) -> reqwest::Url {
-    let window = web_sys::window().unwrap();
-    let location = window.location();
-    let base = reqwest::Url::parse(&format!(
-        "{}/{}/",
-        location.origin().unwrap(),
-        option_env!("RES_PATH").unwrap_or("res"),
-    ))
-    .unwrap();
-    base.join(file_name).unwrap()
-}
-
 pub async fn load_string(file_name: &str) -> anyhow::Result<String> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let txt = reqwest::get(url)
-                .await?
-                .text()
-                .await?;
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let txt = std::fs::read_to_string(path)?;
-        }
-    }
-
-    Ok(txt)
+    Ok(String::from_utf8(load_binary(file_name).await?)?)
 }
 
 pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
-    std::cfg_select! {
-        target_arch = "wasm32" => {
-            let url = format_url(file_name);
-            let data = reqwest::get(url)
-                .await?
-                .bytes()
-                .await?
-                .to_vec();
-        }
-        _ => {
-            let path = std::path::Path::new(env!("OUT_DIR"))
-                .join("res")
-                .join(file_name);
-            let data = std::fs::read(path)?;
-        }
-    }
-
-    Ok(data)
+    utils::assets::mount_out_dir_res(env!("OUT_DIR"));
+    Ok(utils::assets::load(file_name).await?)
 }
 
 pub async fn load_texture(
@@ -155,13 +110,20 @@ pub async fn load_model(
                 let delta_uv1 = uv1 - uv0;
                 let delta_uv2 = uv2 - uv0;
 
+                // Triangles whose texture coordinates have no area can't
+                // define a tangent, dividing by zero would give us NaNs
+                let det = delta_uv1.x * delta_uv2.y - delta_uv1.y * delta_uv2.x;
+                if det.abs() < f32::EPSILON {
+                    continue;
+                }
+
                 // Solving the following system of equations will
                 // give us the tangent and bitangent.
                 //     delta_pos1 = delta_uv1.x * T + delta_u.y * B
                 //     delta_pos2 = delta_uv2.x * T + delta_uv2.y * B
                 // Luckily, the place I found this equation provided
                 // the solution!
-                let r = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv1.y * delta_uv2.x);
+                let r = 1.0 / det;
                 let tangent = (delta_pos1 * delta_uv2.y - delta_pos2 * delta_uv1.y) * r;
                 // We flip the bitangent to enable right-handed normal
                 // maps with wgpu texture coordinate system
@@ -187,8 +149,12 @@ pub async fn load_model(
                 triangles_included[c[2] as usize] += 1;
             }
 
-            // Average the tangents/bitangents
+            // Average the tangents/bitangents. Vertices that no
+            // triangle contributed to keep their zero vectors
             for (i, n) in triangles_included.into_iter().enumerate() {
+                if n == 0 {
+                    continue;
+                }
                 let denom = 1.0 / n as f32;
                 let v = &mut vertices[i];
                 v.tangent = (glam::Vec3::from_array(v.tangent) * denom).into();
@@ -612,6 +612,10 @@ impl WgpuAppAction for WgpuApp {
         let Some((output, view)) = self.app.get_current_frame_view(None) else {
             return Ok(());
         };
+        // 只绘制视锥体内的地形区块
+        let frustum = utils::culling::Frustum::from_view_projection(
+            &glam::Mat4::from_cols_array_2d(&self.camera_uniform.view_proj),
+        );
 
         let mut encoder = self
             .app
@@ -667,6 +671,7 @@ impl WgpuAppAction for WgpuApp {
             self.terrain_hack_pipeline.render(
                 &mut render_pass,
                 &self.terrain,
+                &frustum,
                 &self.camera_bind_group,
                 &self.light_bind_group,
             );
@@ -5,6 +5,7 @@ use core::mem::size_of_val;
 use glam::Vec3Swizzles;
 
 use crate::{create_render_pipeline, model};
+use utils::culling::{Aabb, Frustum};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -47,6 +48,27 @@ impl Terrain {
         self.chunks
             .push(pipeline.gen_chunk(device, queue, corner, existing_chunk));
     }
+
+    /// 区块的包围盒：水平范围由区块大小决定
+    ///
+    /// 着色器中的高度为 `mix(min, max, fbm(p))`，而 `fbm` 的取值范围是 [-1, 1]，
+    /// 所以高度落在 `[2 * min - max, max]` 之间，而不是 `min_max_height` 本身
+    pub fn chunk_bounds(&self, chunk: &Chunk) -> Aabb {
+        let corner = chunk.corner.as_vec2();
+        let far_corner = corner + self.chunk_size.as_vec2();
+        let (min, max) = (self.min_max_height.x, self.min_max_height.y);
+        Aabb::new(
+            glam::vec3(corner.x, 2.0 * min - max, corner.y),
+            glam::vec3(far_corner.x, max, far_corner.y),
+        )
+    }
+
+    /// 与视锥体相交的区块
+    pub fn visible_chunks<'a>(&'a self, frustum: &'a Frustum) -> impl Iterator<Item = &'a Chunk> {
+        self.chunks
+            .iter()
+            .filter(|chunk| frustum.intersects_aabb(&self.chunk_bounds(chunk)))
+    }
 }
 
 pub struct Chunk {
@@ -177,13 +199,14 @@ impl TerrainPipeline {
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         terrain: &'a Terrain,
+        frustum: &Frustum,
         camera_bind_group: &'a wgpu::BindGroup,
         light_bind_group: &'a wgpu::BindGroup,
     ) {
         render_pass.set_pipeline(&self._render_pipeline);
         render_pass.set_bind_group(0, camera_bind_group, &[]);
         render_pass.set_bind_group(1, light_bind_group, &[]);
-        for chunk in &terrain.chunks {
+        for chunk in terrain.visible_chunks(frustum) {
             render_pass
                 .set_index_buffer(chunk.mesh.index_buffer.slice(..), chunk.mesh.index_format);
             render_pass.set_vertex_buffer(0, chunk.mesh.vertex_buffer.slice(..));
@@ -419,13 +442,14 @@ impl TerrainHackPipeline {
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         terrain: &'a Terrain,
+        frustum: &Frustum,
         camera_bind_group: &'a wgpu::BindGroup,
         light_bind_group: &'a wgpu::BindGroup,
     ) {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, camera_bind_group, &[]);
         render_pass.set_bind_group(1, light_bind_group, &[]);
-        for chunk in &terrain.chunks {
+        for chunk in terrain.visible_chunks(frustum) {
             render_pass
                 .set_index_buffer(chunk.mesh.index_buffer.slice(..), chunk.mesh.index_format);
             render_pass.set_vertex_buffer(0, chunk.mesh.vertex_buffer.slice(..));
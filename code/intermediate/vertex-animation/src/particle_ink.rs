@@ -164,7 +164,7 @@ impl ParticleInk {
             .with_vertex_buffer_layouts(vertex_buffer_layouts)
             .with_use_depth_stencil(true)
             .with_color_format(format);
-        let display_node = builder.build(&app.device);
+        let display_node = builder.build(&app.device).unwrap();
 
         // 准备绑定组需要的数据
         let bind_group_data = BindGroupData {
@@ -178,8 +178,8 @@ impl ParticleInk {
             ),
             ..Default::default()
         };
-        let move_node = ComputeNode::new(&app.device, &bind_group_data, &move_shader);
-        let reset_node = ComputeNode::new(&app.device, &bind_group_data, &reset_shader);
+        let move_node = ComputeNode::new(&app.device, &bind_group_data, &move_shader).unwrap();
+        let reset_node = ComputeNode::new(&app.device, &bind_group_data, &reset_shader).unwrap();
 
         Self {
             particle_count: (particle_num.width * particle_num.height) as usize,
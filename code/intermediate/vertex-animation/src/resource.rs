@@ -37,11 +37,5 @@ pub fn load_a_texture(app: &app_surface::AppSurface, img_data: &[u8]) -> AnyText
         format: Some(format.remove_srgb_suffix()),
         ..Default::default()
     });
-    AnyTexture {
-        size,
-        tex: texture,
-        tex_view,
-        format,
-        view_dimension: wgpu::TextureViewDimension::D2,
-    }
+    AnyTexture::from_parts(texture, tex_view, wgpu::TextureViewDimension::D2, None)
 }
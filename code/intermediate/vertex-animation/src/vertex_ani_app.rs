@@ -133,7 +133,7 @@ impl WgpuAppAction for VertexAnimationApp {
             .with_use_depth_stencil(true)
             .with_cull_mode(None)
             .with_color_format(format);
-        let turning_node = builder.build(&app.device);
+        let turning_node = builder.build(&app.device).unwrap();
 
         // 准备绑定组需要的数据
         let bind_group_data = BindGroupData {
@@ -149,7 +149,8 @@ impl WgpuAppAction for VertexAnimationApp {
             &bg_shader,
             None,
             1,
-        );
+        )
+        .unwrap();
 
         let size = PhysicalSize::new(app.config.width, app.config.height);
 
@@ -7,7 +7,12 @@ layout(location=0) out vec3 v_color;
 layout(set=0, binding=0)
 uniform Camera {
     vec3 u_view_position;
+    mat4 u_view;
+    mat4 u_proj;
     mat4 u_view_proj;
+    mat4 u_inv_view;
+    mat4 u_inv_proj;
+    mat4 u_inv_view_proj;
 };
 
 layout(set=1, binding=0)
@@ -1,6 +1,11 @@
 struct Camera {
     u_view_position: vec3<f32>,
+    u_view: mat4x4<f32>,
+    u_proj: mat4x4<f32>,
     u_view_proj: mat4x4<f32>,
+    u_inv_view: mat4x4<f32>,
+    u_inv_proj: mat4x4<f32>,
+    u_inv_view_proj: mat4x4<f32>,
 }
 
 struct Light {
@@ -25,15 +30,15 @@ var<private> gl_Position: vec4<f32>;
 fn main_1() {
     var v_position: vec3<f32>;
 
-    let _e11 = a_position_1;
-    let _e12 = scale;
-    let _e14 = global_1.u_position;
-    v_position = ((_e11 * _e12) + _e14);
-    let _e18 = global.u_view_proj;
-    let _e19 = v_position;
-    gl_Position = (_e18 * vec4<f32>(_e19.x, _e19.y, _e19.z, 1f));
-    let _e27 = global_1.u_color;
-    v_color = _e27;
+    let _e21 = a_position_1;
+    let _e22 = scale;
+    let _e24 = global_1.u_position;
+    v_position = ((_e21 * _e22) + _e24);
+    let _e28 = global.u_view_proj;
+    let _e29 = v_position;
+    gl_Position = (_e28 * vec4<f32>(_e29.x, _e29.y, _e29.z, 1f));
+    let _e37 = global_1.u_color;
+    v_color = _e37;
     return;
 }
 
@@ -41,7 +46,7 @@ fn main_1() {
 fn main(@location(0) a_position: vec3<f32>) -> VertexOutput {
     a_position_1 = a_position;
     main_1();
-    let _e18 = v_color;
-    let _e20 = gl_Position;
-    return VertexOutput(_e18, _e20);
+    let _e28 = v_color;
+    let _e30 = gl_Position;
+    return VertexOutput(_e28, _e30);
 }
@@ -2,14 +2,11 @@ use app_surface::{AppSurface, SurfaceFrame};
 use core::f32::consts;
 use rayon::prelude::*;
 use std::sync::Arc;
+use utils::camera::{Camera, CameraController, FlyController, Projection};
 use utils::framework::{WgpuAppAction, run};
 use wgpu::util::DeviceExt;
-use winit::{
-    dpi::{PhysicalPosition, PhysicalSize},
-    event::*,
-};
+use winit::{dpi::PhysicalSize, event::*};
 
-mod camera;
 mod model;
 mod pipeline; // NEW!
 mod texture;
@@ -19,28 +16,6 @@ use pipeline::create_render_pipeline;
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct CameraUniform {
-    view_position: [f32; 4],
-    view_proj: [[f32; 4]; 4],
-}
-
-impl CameraUniform {
-    fn new() -> Self {
-        Self {
-            view_position: [0.0; 4],
-            view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
-        }
-    }
-
-    // UPDATED!
-    fn update_view_proj(&mut self, camera: &camera::Camera, projection: &camera::Projection) {
-        self.view_position = camera.position.extend(1.0).into();
-        self.view_proj = (projection.calc_matrix() * camera.calc_matrix()).to_cols_array_2d()
-    }
-}
-
 struct Instance {
     position: glam::Vec3,
     rotation: glam::Quat,
@@ -135,10 +110,8 @@ struct WgpuApp {
     size_changed: bool,
     render_pipeline: wgpu::RenderPipeline,
     obj_model: model::Model,
-    camera: camera::Camera,
-    projection: camera::Projection,
-    camera_controller: camera::CameraController,
-    camera_uniform: CameraUniform,
+    camera: Camera,
+    camera_controller: FlyController,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     instances: Vec<Instance>,
@@ -151,8 +124,6 @@ struct WgpuApp {
     light_render_pipeline: wgpu::RenderPipeline,
     #[allow(dead_code)]
     debug_material: model::Material,
-    last_mouse_pos: PhysicalPosition<f64>,
-    mouse_pressed: bool,
 }
 
 impl WgpuApp {
@@ -162,7 +133,7 @@ impl WgpuApp {
             self.app
                 .resize_surface_by_size((self.size.width, self.size.height));
 
-            self.projection.resize(self.size.width, self.size.height);
+            self.camera.resize(self.size.width, self.size.height);
             self.depth_texture = texture::Texture::create_depth_texture(
                 &self.app.device,
                 &self.app.config,
@@ -223,16 +194,18 @@ impl WgpuAppAction for WgpuApp {
             });
 
         // UPDATED!
-        let camera = camera::Camera::new((0.0, 5.0, 10.0), -90.0, -20.0);
-        let projection = camera::Projection::new(config.width, config.height, 45.0, 0.1, 100.0);
-        let camera_controller = camera::CameraController::new(4.0, 0.4);
-
-        let mut camera_uniform = CameraUniform::new();
-        camera_uniform.update_view_proj(&camera, &projection);
+        let mut camera = Camera::new(
+            glam::vec3(0.0, 5.0, 10.0),
+            Projection::perspective(45.0, 0.1, 100.0),
+            config.width,
+            config.height,
+        );
+        camera.set_yaw_pitch((-90.0f32).to_radians(), (-20.0f32).to_radians());
+        let camera_controller = FlyController::new(4.0, 0.004);
 
         let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera_uniform]),
+            contents: bytemuck::bytes_of(&camera.uniform()),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -424,11 +397,9 @@ impl WgpuAppAction for WgpuApp {
             render_pipeline,
             obj_model,
             camera,
-            projection,
             camera_controller,
             camera_buffer,
             camera_bind_group,
-            camera_uniform,
             instances,
             instance_buffer,
             depth_texture,
@@ -438,8 +409,6 @@ impl WgpuAppAction for WgpuApp {
             light_render_pipeline,
             #[allow(dead_code)]
             debug_material,
-            last_mouse_pos: (0.0, 0.0).into(),
-            mouse_pressed: false,
         }
     }
 
@@ -456,45 +425,27 @@ impl WgpuAppAction for WgpuApp {
     }
 
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        self.camera_controller.process_keyboard(
-            &event.physical_key,
-            &event.logical_key,
-            event.state,
-        )
+        self.camera_controller.keyboard_input(event)
     }
 
     fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
-        if button == MouseButton::Left {
-            self.mouse_pressed = state == ElementState::Pressed;
-            true
-        } else {
-            false
-        }
+        self.camera_controller.mouse_click(state, button)
     }
 
-    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
-        self.camera_controller.process_scroll(&delta);
-        true
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase) -> bool {
+        self.camera_controller.mouse_wheel(delta, phase)
     }
 
-    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
-        let mouse_dx = position.x - self.last_mouse_pos.x;
-        let mouse_dy = position.y - self.last_mouse_pos.y;
-        self.last_mouse_pos = position;
-        if self.mouse_pressed {
-            self.camera_controller.process_mouse(mouse_dx, mouse_dy);
-        }
-        true
+    fn device_input(&mut self, event: &DeviceEvent) -> bool {
+        self.camera_controller.device_input(event)
     }
 
     fn update(&mut self, dt: core::time::Duration) {
-        self.camera_controller.update_camera(&mut self.camera, dt);
-        self.camera_uniform
-            .update_view_proj(&self.camera, &self.projection);
+        self.camera_controller.update(&mut self.camera, dt);
         self.app.queue.write_buffer(
             &self.camera_buffer,
             0,
-            bytemuck::cast_slice(&[self.camera_uniform]),
+            bytemuck::bytes_of(&self.camera.uniform()),
         );
 
         // Update the light
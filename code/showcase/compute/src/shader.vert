@@ -14,7 +14,12 @@ layout(location=3) out vec3 v_view_position; // NEW!
 layout(set=1, binding=0) 
 uniform Camera {
     vec3 u_view_position; 
+    mat4 u_view;
+    mat4 u_proj;
     mat4 u_view_proj;
+    mat4 u_inv_view;
+    mat4 u_inv_proj;
+    mat4 u_inv_view_proj;
 };
 
 layout(location=5) in vec4 model_matrix_0;
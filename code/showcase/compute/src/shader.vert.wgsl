@@ -1,6 +1,11 @@
 struct Camera {
     u_view_position: vec3<f32>,
+    u_view: mat4x4<f32>,
+    u_proj: mat4x4<f32>,
     u_view_proj: mat4x4<f32>,
+    u_inv_view: mat4x4<f32>,
+    u_inv_proj: mat4x4<f32>,
+    u_inv_view_proj: mat4x4<f32>,
 }
 
 struct Light {
@@ -47,45 +52,45 @@ fn main_1() {
     var tangent_matrix: mat3x3<f32>;
     var model_space: vec4<f32>;
 
-    let _e24 = model_matrix_0_1;
-    let _e25 = model_matrix_1_1;
-    let _e26 = model_matrix_2_1;
-    let _e27 = model_matrix_3_1;
-    model_matrix = mat4x4<f32>(vec4<f32>(_e24.x, _e24.y, _e24.z, _e24.w), vec4<f32>(_e25.x, _e25.y, _e25.z, _e25.w), vec4<f32>(_e26.x, _e26.y, _e26.z, _e26.w), vec4<f32>(_e27.x, _e27.y, _e27.z, _e27.w));
-    let _e50 = a_tex_coords_1;
-    v_tex_coords = _e50;
-    let _e51 = normal_matrix_0_1;
-    let _e52 = normal_matrix_1_1;
-    let _e53 = normal_matrix_2_1;
-    normal_matrix = mat3x3<f32>(vec3<f32>(_e51.x, _e51.y, _e51.z), vec3<f32>(_e52.x, _e52.y, _e52.z), vec3<f32>(_e53.x, _e53.y, _e53.z));
-    let _e68 = normal_matrix;
-    let _e69 = a_normal_1;
-    normal = normalize((_e68 * _e69));
-    let _e73 = normal_matrix;
-    let _e74 = a_tangent_1;
-    tangent = normalize((_e73 * _e74));
+    let _e34 = model_matrix_0_1;
+    let _e35 = model_matrix_1_1;
+    let _e36 = model_matrix_2_1;
+    let _e37 = model_matrix_3_1;
+    model_matrix = mat4x4<f32>(vec4<f32>(_e34.x, _e34.y, _e34.z, _e34.w), vec4<f32>(_e35.x, _e35.y, _e35.z, _e35.w), vec4<f32>(_e36.x, _e36.y, _e36.z, _e36.w), vec4<f32>(_e37.x, _e37.y, _e37.z, _e37.w));
+    let _e60 = a_tex_coords_1;
+    v_tex_coords = _e60;
+    let _e61 = normal_matrix_0_1;
+    let _e62 = normal_matrix_1_1;
+    let _e63 = normal_matrix_2_1;
+    normal_matrix = mat3x3<f32>(vec3<f32>(_e61.x, _e61.y, _e61.z), vec3<f32>(_e62.x, _e62.y, _e62.z), vec3<f32>(_e63.x, _e63.y, _e63.z));
     let _e78 = normal_matrix;
-    let _e79 = a_bitangent_1;
-    bitangent = normalize((_e78 * _e79));
-    let _e83 = tangent;
-    let _e84 = bitangent;
-    let _e85 = normal;
-    tangent_matrix = transpose(mat3x3<f32>(vec3<f32>(_e83.x, _e83.y, _e83.z), vec3<f32>(_e84.x, _e84.y, _e84.z), vec3<f32>(_e85.x, _e85.y, _e85.z)));
-    let _e101 = model_matrix;
-    let _e102 = a_position_1;
-    model_space = (_e101 * vec4<f32>(_e102.x, _e102.y, _e102.z, 1f));
-    let _e110 = tangent_matrix;
-    let _e111 = model_space;
-    v_position = (_e110 * _e111.xyz);
-    let _e114 = tangent_matrix;
-    let _e115 = global_1.light_position;
-    v_light_position = (_e114 * _e115);
-    let _e117 = tangent_matrix;
-    let _e118 = global.u_view_position;
-    v_view_position = (_e117 * _e118);
-    let _e121 = global.u_view_proj;
-    let _e122 = model_space;
-    gl_Position = (_e121 * _e122);
+    let _e79 = a_normal_1;
+    normal = normalize((_e78 * _e79));
+    let _e83 = normal_matrix;
+    let _e84 = a_tangent_1;
+    tangent = normalize((_e83 * _e84));
+    let _e88 = normal_matrix;
+    let _e89 = a_bitangent_1;
+    bitangent = normalize((_e88 * _e89));
+    let _e93 = tangent;
+    let _e94 = bitangent;
+    let _e95 = normal;
+    tangent_matrix = transpose(mat3x3<f32>(vec3<f32>(_e93.x, _e93.y, _e93.z), vec3<f32>(_e94.x, _e94.y, _e94.z), vec3<f32>(_e95.x, _e95.y, _e95.z)));
+    let _e111 = model_matrix;
+    let _e112 = a_position_1;
+    model_space = (_e111 * vec4<f32>(_e112.x, _e112.y, _e112.z, 1f));
+    let _e120 = tangent_matrix;
+    let _e121 = model_space;
+    v_position = (_e120 * _e121.xyz);
+    let _e124 = tangent_matrix;
+    let _e125 = global_1.light_position;
+    v_light_position = (_e124 * _e125);
+    let _e127 = tangent_matrix;
+    let _e128 = global.u_view_position;
+    v_view_position = (_e127 * _e128);
+    let _e131 = global.u_view_proj;
+    let _e132 = model_space;
+    gl_Position = (_e131 * _e132);
     return;
 }
 
@@ -104,10 +109,10 @@ fn main(@location(0) a_position: vec3<f32>, @location(1) a_tex_coords: vec2<f32>
     normal_matrix_1_1 = normal_matrix_1_;
     normal_matrix_2_1 = normal_matrix_2_;
     main_1();
-    let _e65 = v_tex_coords;
-    let _e67 = v_position;
-    let _e69 = v_light_position;
-    let _e71 = v_view_position;
-    let _e73 = gl_Position;
-    return VertexOutput(_e65, _e67, _e69, _e71, _e73);
+    let _e75 = v_tex_coords;
+    let _e77 = v_position;
+    let _e79 = v_light_position;
+    let _e81 = v_view_position;
+    let _e83 = gl_Position;
+    return VertexOutput(_e75, _e77, _e79, _e81, _e83);
 }
@@ -1,5 +1,3 @@
-use core::mem;
-
 async fn run() {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
         backends: wgpu::Backends::all(),
@@ -54,26 +52,6 @@ async fn run() {
         None,
     );
 
-    // wgpu requires texture -> buffer copies to be aligned using
-    // wgpu::COPY_BYTES_PER_ROW_ALIGNMENT. Because of this we'll
-    // need to save both the padded_bytes_per_row as well as the
-    // unpadded_bytes_per_row
-    let pixel_size = mem::size_of::<[u8; 4]>() as u32;
-    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
-    let unpadded_bytes_per_row = pixel_size * texture_size;
-    let padding = (align - unpadded_bytes_per_row % align) % align;
-    let padded_bytes_per_row = unpadded_bytes_per_row + padding;
-
-    // create a buffer to copy the texture to so we can get the data
-    let buffer_size = (padded_bytes_per_row * texture_size) as wgpu::BufferAddress;
-    let buffer_desc = wgpu::BufferDescriptor {
-        size: buffer_size,
-        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-        label: Some("Output Buffer"),
-        mapped_at_creation: false,
-    };
-    let output_buffer = device.create_buffer(&buffer_desc);
-
     // a simple render pipeline that draws a triangle
     let render_pipeline = create_render_pipeline(&device, &render_target);
 
@@ -107,52 +85,11 @@ async fn run() {
 
         drop(rpass);
 
-        encoder.copy_texture_to_buffer(
-            wgpu::TexelCopyTextureInfo {
-                aspect: wgpu::TextureAspect::All,
-                texture: &render_target.tex,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            wgpu::TexelCopyBufferInfo {
-                buffer: &output_buffer,
-                layout: wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(padded_bytes_per_row),
-                    rows_per_image: Some(texture_size),
-                },
-            },
-            render_target.size,
-        );
-
         queue.submit(Some(encoder.finish()));
 
-        // Create the map request
-        let buffer_slice = output_buffer.slice(..);
-        let (tx, rx) = flume::bounded(1);
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            tx.send(result).unwrap();
-        });
-        // wait for the GPU to finish
-        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
-
-        if let Ok(Ok(())) = rx.recv_async().await {
-            let padded_data = buffer_slice.get_mapped_range().unwrap();
-            let data = padded_data
-                .chunks(padded_bytes_per_row as _)
-                .flat_map(|chunk| &chunk[..unpadded_bytes_per_row as _])
-                .copied()
-                .collect::<Vec<_>>();
-
-            frames.push(data);
-
-            // 必须确保所有映射视图在解除缓冲区映射之前被释放
-            drop(padded_data);
-            // 解除缓冲区映射
-            output_buffer.unmap();
-        } else {
-            panic!("从 gpu 读取数据失败！");
-        }
+        // read_pixels 负责暂存缓冲区、行对齐填充与映射，返回紧凑的像素数据
+        let data = render_target.read_pixels(&device, &queue).await.unwrap();
+        frames.push(data);
     }
 
     save_gif("output.gif", &mut frames, 10, texture_size as u16).unwrap();
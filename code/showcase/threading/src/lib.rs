@@ -2,11 +2,11 @@ use app_surface::{AppSurface, SurfaceFrame};
 use core::f32::consts;
 use rayon::prelude::*;
 use std::sync::Arc;
+use utils::camera::{Camera, CameraController, FlyController, Projection};
 use utils::framework::WgpuAppAction;
 use wgpu::util::DeviceExt;
 use winit::{dpi::PhysicalSize, event::*};
 
-mod camera;
 mod model;
 mod resources;
 mod texture;
@@ -15,28 +15,6 @@ use model::{DrawLight, DrawModel, Vertex};
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct CameraUniform {
-    view_position: [f32; 4],
-    view_proj: [[f32; 4]; 4],
-}
-
-impl CameraUniform {
-    fn new() -> Self {
-        Self {
-            view_position: [0.0; 4],
-            view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
-        }
-    }
-
-    // UPDATED!
-    fn update_view_proj(&mut self, camera: &camera::Camera, projection: &camera::Projection) {
-        self.view_position = camera.position.extend(1.0).into();
-        self.view_proj = (projection.calc_matrix() * camera.calc_matrix()).to_cols_array_2d()
-    }
-}
-
 struct Instance {
     position: glam::Vec3,
     rotation: glam::Quat,
@@ -130,10 +108,8 @@ pub struct WgpuApp {
     size_changed: bool,
     render_pipeline: wgpu::RenderPipeline,
     obj_model: model::Model,
-    camera: camera::Camera,
-    projection: camera::Projection,
-    camera_controller: camera::CameraController,
-    camera_uniform: CameraUniform,
+    camera: Camera,
+    camera_controller: FlyController,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     instances: Vec<Instance>,
@@ -147,7 +123,6 @@ pub struct WgpuApp {
     light_render_pipeline: wgpu::RenderPipeline,
     #[allow(dead_code)]
     debug_material: model::Material,
-    mouse_pressed: bool,
 }
 
 impl WgpuApp {
@@ -157,7 +132,7 @@ impl WgpuApp {
             self.app
                 .resize_surface_by_size((self.size.width, self.size.height));
 
-            self.projection.resize(self.size.width, self.size.height);
+            self.camera.resize(self.size.width, self.size.height);
             self.depth_texture = texture::Texture::create_depth_texture(
                 &self.app.device,
                 &self.app.config,
@@ -281,16 +256,18 @@ impl WgpuAppAction for WgpuApp {
             });
 
         // UPDATED!
-        let camera = camera::Camera::new((0.0, 5.0, 10.0), -90.0, -20.0);
-        let projection = camera::Projection::new(config.width, config.height, 45.0, 0.1, 100.0);
-        let camera_controller = camera::CameraController::new(4.0, 0.4);
-
-        let mut camera_uniform = CameraUniform::new();
-        camera_uniform.update_view_proj(&camera, &projection);
+        let mut camera = Camera::new(
+            glam::vec3(0.0, 5.0, 10.0),
+            Projection::perspective(45.0, 0.1, 100.0),
+            config.width,
+            config.height,
+        );
+        camera.set_yaw_pitch((-90.0f32).to_radians(), (-20.0f32).to_radians());
+        let camera_controller = FlyController::new(4.0, 0.004);
 
         let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera_uniform]),
+            contents: bytemuck::bytes_of(&camera.uniform()),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -491,11 +468,9 @@ impl WgpuAppAction for WgpuApp {
             render_pipeline,
             obj_model,
             camera,
-            projection,
             camera_controller,
             camera_buffer,
             camera_bind_group,
-            camera_uniform,
             instances,
             instance_buffer,
             depth_texture,
@@ -505,7 +480,6 @@ impl WgpuAppAction for WgpuApp {
             light_render_pipeline,
             #[allow(dead_code)]
             debug_material,
-            mouse_pressed: false,
         }
     }
 
@@ -522,45 +496,27 @@ impl WgpuAppAction for WgpuApp {
     }
 
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        self.camera_controller.process_keyboard(
-            &event.physical_key,
-            &event.logical_key,
-            event.state,
-        )
+        self.camera_controller.keyboard_input(event)
     }
 
     fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
-        if button == MouseButton::Left {
-            self.mouse_pressed = state == ElementState::Pressed;
-            true
-        } else {
-            false
-        }
+        self.camera_controller.mouse_click(state, button)
     }
 
-    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
-        self.camera_controller.process_scroll(&delta);
-        true
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase) -> bool {
+        self.camera_controller.mouse_wheel(delta, phase)
     }
 
     fn device_input(&mut self, event: &DeviceEvent) -> bool {
-        match event {
-            DeviceEvent::MouseMotion { delta } if self.mouse_pressed => {
-                self.camera_controller.process_mouse(delta.0, delta.1);
-                true
-            }
-            _ => false,
-        }
+        self.camera_controller.device_input(event)
     }
 
     fn update(&mut self, dt: instant::Duration) {
-        self.camera_controller.update_camera(&mut self.camera, dt);
-        self.camera_uniform
-            .update_view_proj(&self.camera, &self.projection);
+        self.camera_controller.update(&mut self.camera, dt);
         self.app.queue.write_buffer(
             &self.camera_buffer,
             0,
-            bytemuck::cast_slice(&[self.camera_uniform]),
+            bytemuck::bytes_of(&self.camera.uniform()),
         );
 
         // Update the light
@@ -2,7 +2,12 @@
 
 struct Camera {
     view_pos: vec4f,
+    view: mat4x4f,
+    proj: mat4x4f,
     view_proj: mat4x4f,
+    inv_view: mat4x4f,
+    inv_proj: mat4x4f,
+    inv_view_proj: mat4x4f,
 }
 @group(0) @binding(0)
 var<uniform> camera: Camera;
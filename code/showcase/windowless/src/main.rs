@@ -32,19 +32,6 @@ async fn run() {
     let texture = device.create_texture(&texture_desc);
     let texture_view = texture.create_view(&Default::default());
 
-    // we need to store this for later
-    let u32_size = core::mem::size_of::<u32>() as u32;
-
-    let output_buffer_size = (u32_size * texture_size * texture_size) as wgpu::BufferAddress;
-    let output_buffer_desc = wgpu::BufferDescriptor {
-        size: output_buffer_size,
-        usage: wgpu::BufferUsages::COPY_DST
-            // MAP_READ 告诉 wpgu 我们要在 cpu 端读取此缓冲区
-            | wgpu::BufferUsages::MAP_READ,
-        label: None,
-        mapped_at_creation: false,
-    };
-    let output_buffer = device.create_buffer(&output_buffer_desc);
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Shader"),
         source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
@@ -125,56 +112,20 @@ async fn run() {
         render_pass.draw(0..3, 0..1);
     }
 
-    encoder.copy_texture_to_buffer(
-        wgpu::TexelCopyTextureInfo {
-            aspect: wgpu::TextureAspect::All,
-            texture: &texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-        },
-        wgpu::TexelCopyBufferInfo {
-            buffer: &output_buffer,
-            layout: wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(u32_size * texture_size),
-                rows_per_image: Some(texture_size),
-            },
-        },
-        texture_desc.size,
-    );
-
     queue.submit(Some(encoder.finish()));
 
-    // 需要对映射变量设置范围，以便我们能够解除缓冲区的映射
-    {
-        let buffer_slice = output_buffer.slice(..);
-
-        // 注意：我们必须在 await future 之前先创建映射，然后再调用 device.poll()。
-        // 否则，应用程序将停止响应。
-        let (tx, rx) = flume::bounded(1);
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            tx.send(result).unwrap();
-        });
-        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
-
-        if let Ok(Ok(())) = rx.recv_async().await {
-            let data = buffer_slice.get_mapped_range().unwrap();
-
-            use image::{ImageBuffer, Rgba};
-            let buffer =
-                ImageBuffer::<Rgba<u8>, _>::from_raw(texture_size, texture_size, data).unwrap();
-            buffer.save("image.png").unwrap();
-
-            println!("保存图片成功！");
-
-            // 须确保在解除缓冲区映射之前已删除所有已映射的视图
-            drop(buffer);
-            // 解除缓冲区映射
-            output_buffer.unmap();
-        } else {
-            panic!("从 gpu 读取数据失败！");
-        }
-    }
+    // save_png 负责把纹理复制到暂存缓冲区、去掉行对齐填充并映射读取
+    let texture = utils::AnyTexture::from_parts(
+        texture,
+        texture_view,
+        wgpu::TextureViewDimension::D2,
+        Some("output"),
+    );
+    texture
+        .save_png("image.png", &device, &queue)
+        .await
+        .unwrap();
+    println!("保存图片成功！");
 }
 
 fn main() {
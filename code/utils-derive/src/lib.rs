@@ -0,0 +1,167 @@
+//! `utils` 的过程宏
+//!
+//! 使用时通过 `utils::vertex::Vertex` 引入，不需要直接依赖本 crate。
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, ExprLit, Fields, Lit, Member, Meta, parse_macro_input};
+
+/// 为 `#[repr(C)]` 结构体实现 `utils::vertex::Vertex`
+///
+/// 字段的 `VertexFormat` 由字段类型上的 `utils::vertex::VertexFormatOf` 决定，
+/// 偏移量由 `core::mem::offset_of!` 计算。字段上可使用：
+/// - `#[location = N]`：该字段的 shader location 为 `offset + N`，后续字段从此处接着递增
+/// - `#[format = "Unorm8x4"]`：覆盖字段类型对应的格式
+/// - `#[skip]`：不生成顶点属性
+#[proc_macro_derive(Vertex, attributes(location, format, skip))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "#[derive(Vertex)] 不支持泛型结构体",
+        ));
+    }
+    if !has_repr_c(input)? {
+        return Err(syn::Error::new_spanned(
+            name,
+            "#[derive(Vertex)] 需要 #[repr(C)]，否则字段偏移量不确定",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "#[derive(Vertex)] 只能用于结构体",
+        ));
+    };
+
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => vec![],
+    };
+    let mut pushes = vec![];
+    for (index, field) in fields.into_iter().enumerate() {
+        let attrs = FieldAttrs::parse(field)?;
+        if attrs.skip {
+            continue;
+        }
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let ty = &field.ty;
+        let set_location = attrs
+            .location
+            .map(|location| quote!(location = offset + #location;));
+        let push = match attrs.format {
+            Some(format) => quote! {
+                ::utils::vertex::__push_format_attribute::<#ty>(
+                    &mut attributes,
+                    &mut location,
+                    ::core::mem::offset_of!(#name, #member) as u64,
+                    ::utils::vertex::__wgpu::VertexFormat::#format,
+                );
+            },
+            None => quote! {
+                ::utils::vertex::__push_attributes::<#ty>(
+                    &mut attributes,
+                    &mut location,
+                    ::core::mem::offset_of!(#name, #member) as u64,
+                );
+            },
+        };
+        pushes.push(quote!(#set_location #push));
+    }
+
+    Ok(quote! {
+        impl ::utils::vertex::Vertex for #name {
+            #[allow(unused_mut, unused_variables, unused_assignments)]
+            fn vertex_attributes(offset: u32) -> Vec<::utils::vertex::__wgpu::VertexAttribute> {
+                let mut attributes = Vec::new();
+                let mut location = offset;
+                #(#pushes)*
+                attributes
+            }
+        }
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                repr_c = true;
+            }
+            // 忽略 align(N) 等其余参数
+            if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    location: Option<u32>,
+    format: Option<syn::Ident>,
+    skip: bool,
+}
+
+impl FieldAttrs {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in &field.attrs {
+            if attr.path().is_ident("skip") {
+                attr.meta.require_path_only()?;
+                attrs.skip = true;
+            } else if attr.path().is_ident("location") {
+                let lit = name_value_lit(&attr.meta)?;
+                let Lit::Int(location) = lit else {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "期望整数，例如 #[location = 3]",
+                    ));
+                };
+                attrs.location = Some(location.base10_parse()?);
+            } else if attr.path().is_ident("format") {
+                let lit = name_value_lit(&attr.meta)?;
+                let Lit::Str(format) = lit else {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "期望字符串，例如 #[format = \"Unorm8x4\"]",
+                    ));
+                };
+                attrs.format = Some(format.parse()?);
+            }
+        }
+        if attrs.skip && (attrs.location.is_some() || attrs.format.is_some()) {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[skip] 不能与 #[location] 或 #[format] 同时使用",
+            ));
+        }
+        Ok(attrs)
+    }
+}
+
+fn name_value_lit(meta: &Meta) -> syn::Result<&Lit> {
+    let value = &meta.require_name_value()?.value;
+    match value {
+        Expr::Lit(ExprLit { lit, .. }) => Ok(lit),
+        _ => Err(syn::Error::new_spanned(value, "期望字面量")),
+    }
+}
@@ -0,0 +1,415 @@
+//! 可插拔的资源来源
+//!
+//! 加载器不再直接读文件或拼接 URL，而是通过 [`load`] 依次询问已挂载的 [`AssetSource`]，
+//! 第一个找到该路径的来源返回数据。
+//!
+//! ```ignore
+//! // 打包进程序的资源优先于磁盘上的同名文件
+//! utils::assets::mount(EmbeddedSource::new(utils::embedded_assets!("../assets", ["sky.png"])), 10);
+//! utils::assets::mount(ZipSource::open("assets.zip").await?, 5);
+//! let bytes = utils::assets::load("sky.png").await?;
+//! ```
+//!
+//! 没有挂载任何来源时使用默认来源：
+//! 桌面端为当前工作目录与可执行文件所在目录，web 端为当前页面所在的 URL 目录。
+//! 资源实际存放的位置由应用自己挂载，utils 不再假设任何站点或目录结构。
+use crate::{Error, Result};
+use parking_lot::Mutex;
+use std::{future::Future, io::Read, path::PathBuf, pin::Pin, sync::Arc};
+
+/// `Ok(None)` 表示该来源中没有这个路径，会继续尝试下一个来源
+///
+/// 桌面端需要 `Send`，以便加载器可以在 [`WgpuAppAction::new`](crate::WgpuAppAction::new) 等
+/// 要求 `Send` 的 future 中使用；web 端的网络请求不是 `Send` 的
+#[cfg(not(target_arch = "wasm32"))]
+pub type AssetFuture<'a> = Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>>> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+pub type AssetFuture<'a> = Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>>> + 'a>>;
+
+/// 资源来源
+///
+/// 路径使用 `/` 分隔，相对于来源的根
+pub trait AssetSource: Send + Sync {
+    /// 用于日志输出
+    fn name(&self) -> &str;
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a>;
+}
+
+impl<T: AssetSource + ?Sized> AssetSource for Box<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a> {
+        (**self).load(path)
+    }
+}
+
+/// 本地目录
+pub struct DirSource {
+    root: PathBuf,
+    name: String,
+}
+
+#[allow(dead_code)]
+impl DirSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let name = format!("dir:{}", root.display());
+        Self { root, name }
+    }
+}
+
+impl AssetSource for DirSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a> {
+        Box::pin(async move {
+            // 绝对路径会替换掉 root
+            match std::fs::read(self.root.join(path)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+}
+
+/// 编译期打包进程序的资源，通常由 [`embedded_assets!`](crate::embedded_assets) 生成
+pub struct EmbeddedSource {
+    files: &'static [(&'static str, &'static [u8])],
+}
+
+#[allow(dead_code)]
+impl EmbeddedSource {
+    pub const fn new(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self { files }
+    }
+}
+
+impl AssetSource for EmbeddedSource {
+    fn name(&self) -> &str {
+        "embedded"
+    }
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a> {
+        let found = self
+            .files
+            .iter()
+            .find(|(name, _)| *name == path)
+            .map(|(_, bytes)| bytes.to_vec());
+        Box::pin(async move { Ok(found) })
+    }
+}
+
+/// 生成 [`EmbeddedSource`] 所需的 `(路径, 数据)` 列表
+///
+/// 目录相对于调用处的源文件：
+/// ```ignore
+/// static FILES: &[(&str, &[u8])] = utils::embedded_assets!("../../assets", ["sky.png", "fonts/a.ttf"]);
+/// ```
+#[macro_export]
+macro_rules! embedded_assets {
+    ($dir:literal, [$($file:literal),* $(,)?]) => {
+        &[$(($file, include_bytes!(concat!($dir, "/", $file)) as &[u8])),*]
+    };
+}
+
+/// zip 压缩包
+pub struct ZipSource {
+    archive: Mutex<zip::ZipArchive<std::io::Cursor<Vec<u8>>>>,
+    name: String,
+}
+
+#[allow(dead_code)]
+impl ZipSource {
+    pub fn from_bytes(bytes: Vec<u8>, name: &str) -> Result<Self> {
+        let archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(zip_error)?;
+        Ok(Self {
+            archive: Mutex::new(archive),
+            name: format!("zip:{name}"),
+        })
+    }
+
+    /// 压缩包本身也可以来自其它来源，例如 web 端先通过 [`HttpSource`] 下载
+    pub async fn open(path: &str) -> Result<Self> {
+        let bytes = load(path).await?;
+        Self::from_bytes(bytes, path)
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> Error {
+    match e {
+        zip::result::ZipError::Io(e) => Error::Io(e),
+        e => Error::Decode(e.to_string()),
+    }
+}
+
+impl AssetSource for ZipSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a> {
+        let read = || {
+            let mut archive = self.archive.lock();
+            let mut file = match archive.by_name(path) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(zip_error(e)),
+            };
+            let mut bytes = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut bytes)?;
+            Ok(Some(bytes))
+        };
+        let result = read();
+        Box::pin(async move { result })
+    }
+}
+
+/// HTTP 服务器上的目录
+///
+/// 桌面端的请求是阻塞的，适合在加载阶段使用
+pub struct HttpSource {
+    base_url: String,
+}
+
+#[allow(dead_code)]
+impl HttpSource {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        Self { base_url }
+    }
+}
+
+impl AssetSource for HttpSource {
+    fn name(&self) -> &str {
+        &self.base_url
+    }
+
+    fn load<'a>(&'a self, path: &'a str) -> AssetFuture<'a> {
+        let url = format!("{}{}", self.base_url, path.trim_start_matches('/'));
+        Box::pin(async move {
+            std::cfg_select! {
+                target_arch = "wasm32" => {
+                    let response = reqwest::get(&url).await?;
+                    if response.status() == reqwest::StatusCode::NOT_FOUND {
+                        return Ok(None);
+                    }
+                    let bytes = response.error_for_status()?.bytes().await?;
+                    Ok(Some(bytes.to_vec()))
+                }
+                _ => {
+                    match ureq::get(&url).call() {
+                        Ok(mut response) => response
+                            .body_mut()
+                            .read_to_vec()
+                            .map(Some)
+                            .map_err(|e| Error::Network(e.to_string())),
+                        Err(ureq::Error::StatusCode(404)) => Ok(None),
+                        Err(e) => Err(Error::Network(e.to_string())),
+                    }
+                }
+            }
+        })
+    }
+}
+
+struct Mounted {
+    priority: i32,
+    source: Arc<dyn AssetSource>,
+}
+
+static SOURCES: Mutex<Vec<Mounted>> = Mutex::new(Vec::new());
+
+/// 挂载资源来源，`priority` 越大越先被查询，相同优先级按挂载顺序查询
+///
+/// 挂载任意来源后默认来源不再生效，需要时可以用 [`default_sources`] 一起挂载
+pub fn mount(source: impl AssetSource + 'static, priority: i32) {
+    insert_mounted(&mut SOURCES.lock(), Arc::new(source), priority);
+}
+
+fn insert_mounted(sources: &mut Vec<Mounted>, source: Arc<dyn AssetSource>, priority: i32) {
+    let index = sources.partition_point(|m| m.priority >= priority);
+    sources.insert(index, Mounted { priority, source });
+}
+
+/// 移除所有已挂载的来源，恢复默认来源
+pub fn unmount_all() {
+    SOURCES.lock().clear();
+}
+
+/// 没有挂载来源时使用的默认来源
+pub fn default_sources() -> Result<Vec<Box<dyn AssetSource>>> {
+    std::cfg_select! {
+        target_arch = "wasm32" => {
+            let base_uri = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.base_uri().ok().flatten())
+                .ok_or_else(|| Error::Network("无法获取页面的 base URI".into()))?;
+            Ok(vec![Box::new(HttpSource::new(url_dir(&base_uri)))])
+        }
+        _ => {
+            let mut sources: Vec<Box<dyn AssetSource>> = vec![Box::new(DirSource::new("."))];
+            if let Some(exe_dir) = std::env::current_exe()?.parent() {
+                sources.push(Box::new(DirSource::new(exe_dir)));
+            }
+            Ok(sources)
+        }
+    }
+}
+
+/// 挂载示例自带的 res 目录，重复调用只会挂载一次
+///
+/// 桌面端为构建脚本复制到 `out_dir`（即调用方的 `env!("OUT_DIR")`）下的 res 目录；
+/// web 端不使用 `out_dir`，改为站点上的 `RES_PATH` 目录（构建时未设置则为 `res`）
+pub fn mount_out_dir_res(out_dir: &str) {
+    static MOUNT: std::sync::Once = std::sync::Once::new();
+    MOUNT.call_once(|| {
+        std::cfg_select! {
+            target_arch = "wasm32" => {
+                let _ = out_dir;
+                let origin = web_sys::window().unwrap().location().origin().unwrap();
+                mount(HttpSource::new(format!("{origin}/{RES_PATH}")), 0);
+            }
+            _ => {
+                mount(DirSource::new(std::path::Path::new(out_dir).join("res")), 0);
+            }
+        }
+    });
+}
+
+/// web 端示例资源在站点上的目录
+#[cfg(target_arch = "wasm32")]
+pub const RES_PATH: &str = match option_env!("RES_PATH") {
+    Some(path) => path,
+    None => "res",
+};
+
+/// 去掉 URL 中最后一个 `/` 之后的文件名、查询字符串与片段
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+fn url_dir(url: &str) -> &str {
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    match url.rfind('/') {
+        Some(i) => &url[..=i],
+        None => url,
+    }
+}
+
+/// 按优先级依次从已挂载的来源加载资源
+pub async fn load(path: &str) -> Result<Vec<u8>> {
+    // 不持有锁跨越 await
+    let mounted: Vec<Arc<dyn AssetSource>> =
+        SOURCES.lock().iter().map(|m| m.source.clone()).collect();
+    let sources: Vec<Arc<dyn AssetSource>> = if mounted.is_empty() {
+        default_sources()?.into_iter().map(Arc::from).collect()
+    } else {
+        mounted
+    };
+    load_from(&sources, path).await
+}
+
+async fn load_from(sources: &[Arc<dyn AssetSource>], path: &str) -> Result<Vec<u8>> {
+    for source in sources {
+        if let Some(bytes) = source.load(path).await? {
+            log::info!("asset: {path} <- {}", source.name());
+            return Ok(bytes);
+        }
+    }
+    Err(Error::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("所有资源来源中都没有找到 {path}"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    static EMBEDDED: &[(&str, &[u8])] = &[("a.txt", b"embedded a"), ("b.txt", b"embedded b")];
+
+    fn zip_source(files: &[(&str, &[u8])]) -> ZipSource {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, bytes) in files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+        ZipSource::from_bytes(bytes, "test.zip").unwrap()
+    }
+
+    fn sorted(mounted: Vec<Mounted>) -> Vec<Arc<dyn AssetSource>> {
+        mounted.into_iter().map(|m| m.source).collect()
+    }
+
+    #[test]
+    fn mount_orders_by_priority_then_insertion() {
+        let mut mounted = Vec::new();
+        insert_mounted(&mut mounted, Arc::new(DirSource::new("low")), 0);
+        insert_mounted(&mut mounted, Arc::new(DirSource::new("high")), 10);
+        insert_mounted(&mut mounted, Arc::new(DirSource::new("low2")), 0);
+        insert_mounted(&mut mounted, Arc::new(DirSource::new("high2")), 10);
+        let names: Vec<String> = sorted(mounted)
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect();
+        assert_eq!(names, ["dir:high", "dir:high2", "dir:low", "dir:low2"]);
+    }
+
+    #[test]
+    fn higher_priority_source_wins() {
+        let mut mounted = Vec::new();
+        insert_mounted(
+            &mut mounted,
+            Arc::new(zip_source(&[("a.txt", b"zip a")])),
+            0,
+        );
+        insert_mounted(&mut mounted, Arc::new(EmbeddedSource::new(EMBEDDED)), 5);
+        let sources = sorted(mounted);
+        let bytes = pollster::block_on(load_from(&sources, "a.txt")).unwrap();
+        assert_eq!(bytes, b"embedded a");
+    }
+
+    #[test]
+    fn falls_through_missing_sources() {
+        let dir = std::env::temp_dir().join(format!("utils-assets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("c.txt"), b"dir c").unwrap();
+
+        let mut mounted = Vec::new();
+        insert_mounted(&mut mounted, Arc::new(DirSource::new(&dir)), 10);
+        insert_mounted(&mut mounted, Arc::new(EmbeddedSource::new(EMBEDDED)), 5);
+        insert_mounted(
+            &mut mounted,
+            Arc::new(zip_source(&[("z.txt", b"zip z")])),
+            0,
+        );
+        let sources = sorted(mounted);
+
+        let load = |path| pollster::block_on(load_from(&sources, path));
+        assert_eq!(load("c.txt").unwrap(), b"dir c");
+        assert_eq!(load("b.txt").unwrap(), b"embedded b");
+        assert_eq!(load("z.txt").unwrap(), b"zip z");
+        assert!(matches!(
+            load("missing.txt"),
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn url_dir_strips_file_and_query() {
+        assert_eq!(url_dir("https://a.b/x/index.html?q=1#f"), "https://a.b/x/");
+        assert_eq!(url_dir("https://a.b/x/"), "https://a.b/x/");
+    }
+}
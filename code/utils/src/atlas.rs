@@ -0,0 +1,427 @@
+use crate::load_texture::{self, AnyTexture, ColorSpace};
+use crate::mipmap::{self, MipFilter};
+use crate::{Error, Result, ValidationScope};
+use std::collections::HashMap;
+use wgpu::Sampler;
+
+/// skyline 矩形装箱器，使用 bottom-left 策略：优先放在最低处，其次最靠左
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    // 按 x 排序、首尾相接的轮廓线段 (x, y, width)
+    skyline: Vec<(u32, u32, u32)>,
+}
+
+impl SkylinePacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![(0, 0, width)],
+        }
+    }
+
+    /// 返回矩形左上角的位置，放不下时返回 `None`
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        // (底部 y, 线段宽度, 线段索引)
+        let mut best: Option<(u32, u32, usize)> = None;
+        for index in 0..self.skyline.len() {
+            if let Some(y) = self.fit(index, width, height) {
+                let candidate = (y + height, self.skyline[index].2, index);
+                if best.is_none_or(|b| (candidate.0, candidate.1) < (b.0, b.1)) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        let (bottom, _, index) = best?;
+        let x = self.skyline[index].0;
+        self.insert(index, x, bottom, width);
+        Some((x, bottom - height))
+    }
+
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].0;
+        if x + width > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut remaining = width;
+        for &(_, segment_y, segment_width) in &self.skyline[index..] {
+            y = y.max(segment_y);
+            if y + height > self.height {
+                return None;
+            }
+            if segment_width >= remaining {
+                return Some(y);
+            }
+            remaining -= segment_width;
+        }
+        None
+    }
+
+    fn insert(&mut self, index: usize, x: u32, y: u32, width: u32) {
+        self.skyline.insert(index, (x, y, width));
+        let end = x + width;
+        // 裁掉被新线段覆盖的部分
+        while index + 1 < self.skyline.len() {
+            let next = &mut self.skyline[index + 1];
+            if next.0 >= end {
+                break;
+            }
+            let shrink = end - next.0;
+            if next.2 <= shrink {
+                self.skyline.remove(index + 1);
+            } else {
+                next.0 += shrink;
+                next.2 -= shrink;
+                break;
+            }
+        }
+        // 合并高度相同的相邻线段
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].1 == self.skyline[i + 1].1 {
+                self.skyline[i].2 += self.skyline[i + 1].2;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// 子图在图集中的位置
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    /// 以像素为单位，不含填充与外扩的边缘
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// 归一化的纹理坐标矩形 (x, y, width, height)，可直接传给 `ViewNodeBuilder::with_tex_rect`
+    pub uv: glam::Vec4,
+}
+
+impl AtlasRegion {
+    /// 左上角与右下角的纹理坐标
+    pub fn uv_min_max(&self) -> (glam::Vec2, glam::Vec2) {
+        let min = glam::Vec2::new(self.uv.x, self.uv.y);
+        (min, min + glam::Vec2::new(self.uv.z, self.uv.w))
+    }
+}
+
+pub struct Atlas {
+    pub texture: AnyTexture,
+    pub regions: HashMap<String, AtlasRegion>,
+}
+
+impl Atlas {
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+}
+
+/// 将许多小图片打包到一张纹理中
+///
+/// ```ignore
+/// let mut builder = AtlasBuilder::new().with_padding(2).with_extrude(1);
+/// builder.add_path("sprites/ball.png").await?;
+/// builder.add_rgba("white", 1, 1, vec![255; 4])?;
+/// let (atlas, sampler) = builder.build(&app.device, &app.queue, Some("sprites"))?;
+/// let uv = atlas.region("sprites/ball.png").unwrap().uv;
+/// ```
+pub struct AtlasBuilder {
+    images: Vec<(String, image::RgbaImage)>,
+    padding: u32,
+    extrude: u32,
+    mipmaps: bool,
+    color_space: ColorSpace,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl AtlasBuilder {
+    pub fn new() -> Self {
+        Self {
+            images: vec![],
+            padding: 1,
+            extrude: 1,
+            mipmaps: true,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// 子图之间留空的像素数
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// 将子图的边缘像素向外复制的像素数，避免线性过滤与 mip 采样到相邻子图
+    pub fn with_extrude(mut self, extrude: u32) -> Self {
+        self.extrude = extrude;
+        self
+    }
+
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn add_image(&mut self, name: &str, img: image::DynamicImage) {
+        self.images.push((name.to_string(), img.into_rgba8()));
+    }
+
+    /// `pixels` 为紧凑排列的 RGBA8 数据，长度不足 `width * height * 4` 时返回错误
+    pub fn add_rgba(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) -> Result<()> {
+        let len = pixels.len();
+        let img = image::RgbaImage::from_raw(width, height, pixels).ok_or_else(|| {
+            Error::Decode(format!(
+                "{name}: {width}x{height} 的 RGBA8 数据需要 {} 字节，实际为 {len} 字节",
+                width as u64 * height as u64 * 4
+            ))
+        })?;
+        self.images.push((name.to_string(), img));
+        Ok(())
+    }
+
+    /// 通过 [`assets`](crate::assets) 加载图片，以路径作为子图名
+    pub async fn add_path(&mut self, path: &str) -> Result<()> {
+        let bytes = crate::assets::load(path).await?;
+        let img = load_texture::decode_image(&bytes, path)?;
+        self.add_image(path, img);
+        Ok(())
+    }
+
+    /// 计算所有子图的位置，返回图集尺寸与各子图的像素矩形
+    ///
+    /// 图集为边长不超过 `max_size` 的最小的 2 的幂正方形，宽或高为 0 的子图会返回错误
+    pub fn pack(&self, max_size: u32) -> Result<(u32, Vec<(u32, u32)>)> {
+        if let Some((name, _)) = self
+            .images
+            .iter()
+            .find(|(_, img)| img.width() == 0 || img.height() == 0)
+        {
+            return Err(Error::Validation(format!("子图 {name} 的宽或高为 0")));
+        }
+        let margin = 2 * self.extrude + self.padding;
+        let cells: Vec<(u32, u32)> = self
+            .images
+            .iter()
+            .map(|(_, img)| (img.width() + margin, img.height() + margin))
+            .collect();
+        // 先放高的，再放宽的
+        let mut order: Vec<usize> = (0..cells.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse((cells[i].1, cells[i].0)));
+
+        let area: u64 = cells.iter().map(|&(w, h)| w as u64 * h as u64).sum();
+        let longest = cells.iter().map(|&(w, h)| w.max(h)).max().unwrap_or(1);
+        let mut size = ((area as f64).sqrt().ceil() as u32)
+            .max(longest)
+            .next_power_of_two();
+        while size <= max_size {
+            let mut packer = SkylinePacker::new(size, size);
+            let mut positions = vec![(0, 0); cells.len()];
+            let packed = order
+                .iter()
+                .all(|&i| match packer.pack(cells[i].0, cells[i].1) {
+                    Some((x, y)) => {
+                        positions[i] = (x + self.extrude, y + self.extrude);
+                        true
+                    }
+                    None => false,
+                });
+            if packed {
+                return Ok((size, positions));
+            }
+            size *= 2;
+        }
+        Err(Error::Validation(format!(
+            "{} 张图片无法装入 {max_size}x{max_size} 的图集",
+            self.images.len()
+        )))
+    }
+
+    #[track_caller]
+    pub fn build(
+        self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: Option<&str>,
+    ) -> Result<(Atlas, Sampler)> {
+        let (size, positions) = self.pack(device.limits().max_texture_dimension_2d)?;
+
+        let mut pixels = vec![0u8; (size * size * 4) as usize];
+        let mut regions = HashMap::with_capacity(self.images.len());
+        for ((name, img), &(x, y)) in self.images.iter().zip(positions.iter()) {
+            blit_extruded(&mut pixels, size, img, x, y, self.extrude);
+            let (w, h) = img.dimensions();
+            let inv = 1.0 / size as f32;
+            regions.insert(
+                name.clone(),
+                AtlasRegion {
+                    x,
+                    y,
+                    width: w,
+                    height: h,
+                    uv: glam::Vec4::new(x as f32, y as f32, w as f32, h as f32) * inv,
+                },
+            );
+        }
+
+        let format = match self.color_space {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        };
+        let mip_usage = if self.mipmaps {
+            mipmap::required_usage(device, format)
+        } else {
+            None
+        };
+        let mip_level_count = if mip_usage.is_some() {
+            mipmap::mip_level_count(size, size)
+        } else {
+            1
+        };
+        let extent = wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        };
+
+        let scope = ValidationScope::push(device);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: extent,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | mip_usage.unwrap_or(wgpu::TextureUsages::empty()),
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size * 4),
+                rows_per_image: Some(size),
+            },
+            extent,
+        );
+        let sampler = if mip_level_count > 1 {
+            mipmap::generate_mipmaps(device, queue, &texture, MipFilter::Color);
+            load_texture::bilinear_sampler(device)
+        } else {
+            load_texture::default_sampler(device)
+        };
+        scope.pop()?;
+
+        let tex_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let texture =
+            AnyTexture::from_parts(texture, tex_view, wgpu::TextureViewDimension::D2, label);
+        Ok((Atlas { texture, regions }, sampler))
+    }
+}
+
+/// 将图片写到 (x, y) 处，并把边缘像素向四周复制 `extrude` 像素
+fn blit_extruded(
+    dst: &mut [u8],
+    dst_width: u32,
+    img: &image::RgbaImage,
+    x: u32,
+    y: u32,
+    extrude: u32,
+) {
+    let (w, h) = img.dimensions();
+    let e = extrude as i64;
+    for row in -e..h as i64 + e {
+        let src_y = row.clamp(0, h as i64 - 1) as u32;
+        let dst_y = (y as i64 + row) as u32;
+        for col in -e..w as i64 + e {
+            let src_x = col.clamp(0, w as i64 - 1) as u32;
+            let dst_x = (x as i64 + col) as u32;
+            let offset = ((dst_y * dst_width + dst_x) * 4) as usize;
+            dst[offset..offset + 4].copy_from_slice(&img.get_pixel(src_x, src_y).0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AtlasBuilder, SkylinePacker};
+    use crate::Error;
+
+    #[test]
+    fn packed_rects_do_not_overlap() {
+        let sizes = [
+            (30, 20),
+            (10, 40),
+            (25, 25),
+            (40, 8),
+            (12, 12),
+            (12, 12),
+            (5, 30),
+        ];
+        let mut packer = SkylinePacker::new(64, 64);
+        let rects: Vec<(u32, u32, u32, u32)> = sizes
+            .iter()
+            .map(|&(w, h)| {
+                let (x, y) = packer.pack(w, h).unwrap();
+                assert!(x + w <= 64 && y + h <= 64);
+                (x, y, w, h)
+            })
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                let overlap =
+                    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_rect() {
+        let mut packer = SkylinePacker::new(16, 16);
+        assert_eq!(packer.pack(16, 16), Some((0, 0)));
+        assert_eq!(packer.pack(1, 1), None);
+        assert_eq!(SkylinePacker::new(16, 16).pack(17, 1), None);
+    }
+
+    #[test]
+    fn empty_images_are_rejected() {
+        let mut builder = AtlasBuilder::new();
+        builder.add_rgba("dot", 1, 1, vec![255; 4]).unwrap();
+        builder.add_rgba("empty", 0, 4, vec![]).unwrap();
+        assert!(matches!(builder.pack(64), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn short_pixel_buffer_is_rejected() {
+        let mut builder = AtlasBuilder::new();
+        assert!(matches!(
+            builder.add_rgba("short", 2, 2, vec![255; 12]),
+            Err(Error::Decode(_))
+        ));
+        assert!(builder.images.is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::resource_tracker::TrackedResource;
+use crate::{Error, Result};
 use bytemuck::Pod;
 use wgpu::util::DeviceExt;
 
@@ -12,24 +14,58 @@ pub struct BufferObj {
     // 已占用的坑位，若要计算字节数，需 used_count * 坑位字节长度
     // 对于需要按索引来计算偏移量的 buffer, 不使用 used_count，比如 ModelUniformData buffer
     pub used_count: u64,
+    // 由 `TypedBuffer` 设置为单个元素的字节长度，作为 uniform 绑定时用作 `min_binding_size`；
+    // 元素类型未知的缓冲区为 None，不对 uniform 的大小做检查
+    pub(crate) uniform_binding_size: Option<wgpu::BufferSize>,
+    // 在资源登记表中的登记，随 BufferObj 一起释放
+    pub(crate) tracked: TrackedResource,
 }
 
 #[allow(dead_code)]
 impl BufferObj {
     pub fn used_bytes(&self) -> u64 {
-        self.used_count * 4
+        // min_binding_size 即坑位的字节长度，未知时按 4 字节计算
+        self.used_count * self.min_binding_size.map_or(4, |size| size.get())
     }
+    /// 回读缓冲区中的全部数据
+    ///
+    /// 缓冲区需带有 `COPY_SRC` 用途，除 `create_empty_storage_buffer` 外的构造函数都会自动添加
+    pub async fn read<T: Pod>(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<T>> {
+        if !self.buffer.usage().contains(wgpu::BufferUsages::COPY_SRC) {
+            return Err(Error::Validation("回读的缓冲区需带有 COPY_SRC 用途".into()));
+        }
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback staging buffer"),
+            size: self.size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("readback encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &staging, 0, self.size);
+        queue.submit(Some(encoder.finish()));
+
+        let data = crate::readback::map_staging_buffer(device, &staging).await?;
+        // 映射得到的 Vec<u8> 不保证满足 T 的对齐要求
+        Ok(bytemuck::pod_collect_to_vec(&data))
+    }
+
+    #[track_caller]
     pub fn create_by_buffer(buffer: wgpu::Buffer, size: u64) -> Self {
         BufferObj {
+            tracked: TrackedResource::buffer(&buffer, None),
             buffer,
             size,
             min_binding_size: None,
             has_dynamic_offset: false,
             read_only: true,
             used_count: 0,
+            uniform_binding_size: None,
         }
     }
 
+    #[track_caller]
     pub fn create_storage_buffer<T>(
         device: &wgpu::Device,
         slice: &[T],
@@ -47,6 +83,7 @@ impl BufferObj {
         )
     }
 
+    #[track_caller]
     pub fn create_empty_storage_buffer(
         device: &wgpu::Device,
         size: wgpu::BufferAddress,
@@ -60,15 +97,18 @@ impl BufferObj {
             mapped_at_creation: false,
         });
         BufferObj {
+            tracked: TrackedResource::buffer(&buffer, label),
             buffer,
             size,
             min_binding_size: None,
             has_dynamic_offset: false,
             read_only: false,
             used_count: 0,
+            uniform_binding_size: None,
         }
     }
 
+    #[track_caller]
     pub fn create_empty_uniform_buffer(
         device: &wgpu::Device,
         size: wgpu::BufferAddress,
@@ -78,20 +118,25 @@ impl BufferObj {
     ) -> Self {
         let buffer = device.create_buffer(&wgpu::BufferDescriptor {
             size,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::UNIFORM
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
             label,
             mapped_at_creation: false,
         });
         BufferObj {
+            tracked: TrackedResource::buffer(&buffer, label),
             buffer,
             size,
             min_binding_size: wgpu::BufferSize::new(min_binding_size),
             has_dynamic_offset: is_dynamic,
             read_only: true,
             used_count: 0,
+            uniform_binding_size: None,
         }
     }
 
+    #[track_caller]
     pub fn create_uniform_buffer<T>(
         device: &wgpu::Device,
         uniform: &T,
@@ -109,6 +154,7 @@ impl BufferObj {
         )
     }
 
+    #[track_caller]
     pub fn create_uniforms_buffer<T>(
         device: &wgpu::Device,
         slice: &[T],
@@ -126,6 +172,25 @@ impl BufferObj {
         )
     }
 
+    /// 间接绘制参数缓冲区
+    ///
+    /// 同时带有 STORAGE 用途，以便计算着色器直接写入实例数等参数
+    #[track_caller]
+    pub fn create_indirect_buffer(
+        device: &wgpu::Device,
+        contents: &[u8],
+        label: Option<&'static str>,
+    ) -> Self {
+        BufferObj::create_buffer(
+            device,
+            Some(contents),
+            None,
+            wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE,
+            label,
+        )
+    }
+
+    #[track_caller]
     pub fn create_buffer<T>(
         device: &wgpu::Device,
         slice: Option<&[T]>,
@@ -138,8 +203,10 @@ impl BufferObj {
     {
         let min_binding_size = core::mem::size_of::<T>() as wgpu::BufferAddress;
         let mut size = min_binding_size;
+        let mut used_count = 1;
         let data: &[u8] = if let Some(slice) = slice {
             size *= slice.len() as wgpu::BufferAddress;
+            used_count = slice.len() as u64;
             bytemuck::cast_slice(slice)
         } else {
             bytemuck::bytes_of(item.unwrap())
@@ -147,16 +214,18 @@ impl BufferObj {
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label,
             contents: data,
-            usage: usage | wgpu::BufferUsages::COPY_DST,
+            // 带上 COPY_SRC，使 `read` 可以回读任意由此创建的缓冲区
+            usage: usage | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
         });
         BufferObj {
+            tracked: TrackedResource::buffer(&buffer, label),
             buffer,
             size,
             min_binding_size: wgpu::BufferSize::new(min_binding_size),
             has_dynamic_offset: false,
             read_only: false,
-            // TODO: 待计算正确的 used_count
-            used_count: 0,
+            used_count,
+            uniform_binding_size: None,
         }
     }
 }
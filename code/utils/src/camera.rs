@@ -0,0 +1,945 @@
+//! 相机、投影与可互换的相机控制器
+//!
+//! ```ignore
+//! let mut camera = Camera::new(
+//!     glam::vec3(0.0, 5.0, 10.0),
+//!     Projection::perspective(45.0, 0.1, 100.0),
+//!     config.width,
+//!     config.height,
+//! )
+//! .looking_at(glam::Vec3::ZERO);
+//! let mut controller: Box<dyn CameraController> = Box::new(OrbitController::new(glam::Vec3::ZERO));
+//! let camera_buf = BufferObj::create_uniform_buffer(&device, &camera.uniform(), Some("camera"));
+//!
+//! // WgpuAppAction 的输入事件直接转发给 controller，每帧：
+//! controller.update(&mut camera, dt);
+//! queue.write_buffer(&camera_buf.buffer, 0, bytemuck::bytes_of(&camera.uniform()));
+//! ```
+//!
+//! 相机在局部空间中朝向 -Z、+Y 朝上（右手坐标系）。
+//! 控制器在第一次 `update` 时从相机读取初始状态，因此可以随时替换成另一种控制器。
+
+use bytemuck::{Pod, Zeroable};
+use core::f32::consts::FRAC_PI_2;
+use core::time::Duration;
+use glam::{Mat4, Quat, Vec2, Vec3};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+const SAFE_FRAC_PI_2: f32 = FRAC_PI_2 - 0.0001;
+
+/// 投影方式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// `fovy` 为弧度
+    Perspective { fovy: f32, znear: f32, zfar: f32 },
+    /// `height` 为视口在世界空间中的高度，宽度由宽高比决定
+    Orthographic { height: f32, znear: f32, zfar: f32 },
+    /// 远平面在无穷远处、近平面深度为 1 的透视投影，
+    /// 需要配合 [`depth_compare`](Self::depth_compare) 与 [`depth_clear_value`](Self::depth_clear_value) 使用
+    InfiniteReverseZ { fovy: f32, znear: f32 },
+}
+
+#[allow(dead_code)]
+impl Projection {
+    /// `fovy` 为角度
+    pub fn perspective(fovy: f32, znear: f32, zfar: f32) -> Self {
+        Self::Perspective {
+            fovy: fovy.to_radians(),
+            znear,
+            zfar,
+        }
+    }
+
+    pub fn orthographic(height: f32, znear: f32, zfar: f32) -> Self {
+        Self::Orthographic {
+            height,
+            znear,
+            zfar,
+        }
+    }
+
+    /// `fovy` 为角度
+    pub fn infinite_reverse_z(fovy: f32, znear: f32) -> Self {
+        Self::InfiniteReverseZ {
+            fovy: fovy.to_radians(),
+            znear,
+        }
+    }
+
+    pub fn matrix(&self, aspect: f32) -> Mat4 {
+        match *self {
+            Self::Perspective { fovy, znear, zfar } => {
+                Mat4::perspective_rh(fovy, aspect, znear, zfar)
+            }
+            Self::Orthographic {
+                height,
+                znear,
+                zfar,
+            } => {
+                let half_h = height * 0.5;
+                let half_w = half_h * aspect;
+                Mat4::orthographic_rh(-half_w, half_w, -half_h, half_h, znear, zfar)
+            }
+            Self::InfiniteReverseZ { fovy, znear } => {
+                Mat4::perspective_infinite_reverse_rh(fovy, aspect, znear)
+            }
+        }
+    }
+
+    /// 深度测试使用的比较函数，反向 Z 时离相机越近深度值越大
+    pub fn depth_compare(&self) -> wgpu::CompareFunction {
+        match self {
+            Self::InfiniteReverseZ { .. } => wgpu::CompareFunction::Greater,
+            _ => wgpu::CompareFunction::Less,
+        }
+    }
+
+    /// 深度缓冲区的清除值
+    pub fn depth_clear_value(&self) -> f32 {
+        match self {
+            Self::InfiniteReverseZ { .. } => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// 相机：位置、朝向与投影
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub projection: Projection,
+    /// 视口宽高比，窗口大小变化时通过 [`resize`](Self::resize) 更新
+    pub aspect: f32,
+}
+
+#[allow(dead_code)]
+impl Camera {
+    pub fn new(position: Vec3, projection: Projection, width: u32, height: u32) -> Self {
+        Self {
+            position,
+            rotation: Quat::IDENTITY,
+            projection,
+            aspect: width as f32 / height.max(1) as f32,
+        }
+    }
+
+    /// 朝向 `target`，`target` 与相机位置在同一竖直线上时保持当前朝向
+    pub fn looking_at(mut self, target: Vec3) -> Self {
+        self.look_at(target);
+        self
+    }
+
+    pub fn look_at(&mut self, target: Vec3) {
+        let dir = (target - self.position).normalize_or_zero();
+        if dir != Vec3::ZERO && dir.cross(Vec3::Y).length_squared() > 1e-8 {
+            self.rotation = Quat::from_mat4(&Mat4::look_to_rh(Vec3::ZERO, dir, Vec3::Y)).inverse();
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height.max(1) as f32;
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    /// 偏航角与俯仰角（弧度），偏航角为 0 时朝向 +X，与教程中 FPS 相机的约定一致
+    pub fn yaw_pitch(&self) -> (f32, f32) {
+        let forward = self.forward();
+        (
+            forward.z.atan2(forward.x),
+            forward.y.clamp(-1.0, 1.0).asin(),
+        )
+    }
+
+    pub fn set_yaw_pitch(&mut self, yaw: f32, pitch: f32) {
+        self.rotation = rotation_from_yaw_pitch(yaw, pitch);
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        self.projection.matrix(self.aspect)
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform::new(self)
+    }
+}
+
+fn rotation_from_yaw_pitch(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_rotation_y(-yaw - FRAC_PI_2) * Quat::from_rotation_x(pitch)
+}
+
+/// 相机的 uniform 数据，可直接用于 `BufferObj::create_uniform_buffer`
+///
+/// 对应的 WGSL 结构体：
+/// ```wgsl
+/// struct Camera {
+///     view_position: vec4f,
+///     view: mat4x4f,
+///     proj: mat4x4f,
+///     view_proj: mat4x4f,
+///     inv_view: mat4x4f,
+///     inv_proj: mat4x4f,
+///     inv_view_proj: mat4x4f,
+/// }
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct CameraUniform {
+    pub view_position: [f32; 4],
+    pub view: [[f32; 4]; 4],
+    pub proj: [[f32; 4]; 4],
+    pub view_proj: [[f32; 4]; 4],
+    pub inv_view: [[f32; 4]; 4],
+    pub inv_proj: [[f32; 4]; 4],
+    pub inv_view_proj: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    pub fn new(camera: &Camera) -> Self {
+        let view = camera.view_matrix();
+        let proj = camera.projection_matrix();
+        let view_proj = proj * view;
+        Self {
+            view_position: camera.position.extend(1.0).into(),
+            view: view.to_cols_array_2d(),
+            proj: proj.to_cols_array_2d(),
+            view_proj: view_proj.to_cols_array_2d(),
+            inv_view: view.inverse().to_cols_array_2d(),
+            inv_proj: proj.inverse().to_cols_array_2d(),
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+        }
+    }
+}
+
+/// 相机控制器
+///
+/// 输入方法与 [`WgpuAppAction`](crate::WgpuAppAction) 的同名方法一一对应，返回 true 表示事件已处理
+pub trait CameraController {
+    fn keyboard_input(&mut self, _event: &KeyEvent) -> bool {
+        false
+    }
+
+    fn mouse_click(&mut self, _state: ElementState, _button: MouseButton) -> bool {
+        false
+    }
+
+    fn mouse_wheel(&mut self, _delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        false
+    }
+
+    fn cursor_move(&mut self, _position: PhysicalPosition<f64>) -> bool {
+        false
+    }
+
+    fn device_input(&mut self, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    /// 视口的像素大小，需要把光标位置换算为角度或距离的控制器会用到
+    fn resize(&mut self, _size: PhysicalSize<u32>) {}
+
+    /// 下一次 `update` 时重新从相机读取状态，在外部直接修改了相机之后调用
+    fn reset(&mut self);
+
+    /// 应用累积的输入并更新相机
+    fn update(&mut self, camera: &mut Camera, dt: Duration);
+}
+
+/// 平滑系数：`half_life` 秒内走完剩余距离的一半，`half_life` 为 0 时不平滑
+fn smoothing_factor(half_life: f32, dt: f32) -> f32 {
+    if half_life <= 0.0 {
+        1.0
+    } else {
+        1.0 - 0.5f32.powf(dt / half_life)
+    }
+}
+
+/// 在距离相机 `distance` 处，一个像素对应的世界空间长度
+fn units_per_pixel(projection: &Projection, distance: f32, viewport_height: u32) -> f32 {
+    let height = match *projection {
+        Projection::Orthographic { height, .. } => height,
+        Projection::Perspective { fovy, .. } | Projection::InfiniteReverseZ { fovy, .. } => {
+            2.0 * distance * (fovy * 0.5).tan()
+        }
+    };
+    height / viewport_height.max(1) as f32
+}
+
+/// 滚轮的滚动行数，像素形式的滚动按每行约 100 像素换算
+fn scroll_lines(delta: &MouseScrollDelta) -> f32 {
+    match delta {
+        MouseScrollDelta::LineDelta(_, y) => *y,
+        MouseScrollDelta::PixelDelta(PhysicalPosition { y, .. }) => *y as f32 / 100.0,
+    }
+}
+
+// 记录拖拽中的按键与光标位移
+#[derive(Debug, Default)]
+struct Drag {
+    left: bool,
+    right: bool,
+    middle: bool,
+    cursor: Option<Vec2>,
+    delta: Vec2,
+}
+
+impl Drag {
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        let pressed = state == ElementState::Pressed;
+        match button {
+            MouseButton::Left => self.left = pressed,
+            MouseButton::Right => self.right = pressed,
+            MouseButton::Middle => self.middle = pressed,
+            _ => return false,
+        }
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        let position = Vec2::new(position.x as f32, position.y as f32);
+        let dragging = self.left || self.right || self.middle;
+        if let Some(last) = self.cursor
+            && dragging
+        {
+            self.delta += position - last;
+        }
+        self.cursor = Some(position);
+        dragging
+    }
+
+    fn take_delta(&mut self) -> Vec2 {
+        std::mem::take(&mut self.delta)
+    }
+}
+
+/// FPS 式的自由飞行：WASD / 方向键移动，空格上升，左 Shift 下降，按住左键拖动鼠标转向
+#[derive(Debug)]
+pub struct FlyController {
+    /// 每秒移动的距离
+    pub speed: f32,
+    /// 每像素鼠标位移转过的弧度
+    pub sensitivity: f32,
+    /// 平滑的半衰期（秒），0 表示不平滑
+    pub smoothing: f32,
+    forward: f32,
+    backward: f32,
+    left: f32,
+    right: f32,
+    up: f32,
+    down: f32,
+    mouse_pressed: bool,
+    rotate: Vec2,
+    scroll: f32,
+    // 目标与当前的（偏航角，俯仰角）
+    target_angles: Option<Vec2>,
+    angles: Vec2,
+    velocity: Vec3,
+}
+
+#[allow(dead_code)]
+impl FlyController {
+    pub fn new(speed: f32, sensitivity: f32) -> Self {
+        Self {
+            speed,
+            sensitivity,
+            smoothing: 0.05,
+            forward: 0.0,
+            backward: 0.0,
+            left: 0.0,
+            right: 0.0,
+            up: 0.0,
+            down: 0.0,
+            mouse_pressed: false,
+            rotate: Vec2::ZERO,
+            scroll: 0.0,
+            target_angles: None,
+            angles: Vec2::ZERO,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_smoothing(mut self, half_life: f32) -> Self {
+        self.smoothing = half_life;
+        self
+    }
+}
+
+impl CameraController for FlyController {
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        let amount = if event.state == ElementState::Pressed {
+            1.0
+        } else {
+            0.0
+        };
+        let PhysicalKey::Code(code) = event.physical_key else {
+            return false;
+        };
+        let slot = match code {
+            KeyCode::KeyW | KeyCode::ArrowUp => &mut self.forward,
+            KeyCode::KeyS | KeyCode::ArrowDown => &mut self.backward,
+            KeyCode::KeyA | KeyCode::ArrowLeft => &mut self.left,
+            KeyCode::KeyD | KeyCode::ArrowRight => &mut self.right,
+            KeyCode::Space => &mut self.up,
+            KeyCode::ShiftLeft => &mut self.down,
+            _ => return false,
+        };
+        *slot = amount;
+        true
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button == MouseButton::Left {
+            self.mouse_pressed = state == ElementState::Pressed;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.scroll += scroll_lines(&delta);
+        true
+    }
+
+    fn device_input(&mut self, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta } if self.mouse_pressed => {
+                self.rotate += Vec2::new(delta.0 as f32, delta.1 as f32);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.target_angles = None;
+        self.velocity = Vec3::ZERO;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        let target = self.target_angles.get_or_insert_with(|| {
+            let (yaw, pitch) = camera.yaw_pitch();
+            self.angles = Vec2::new(yaw, pitch);
+            self.angles
+        });
+        target.x += self.rotate.x * self.sensitivity;
+        target.y =
+            (target.y - self.rotate.y * self.sensitivity).clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+        self.rotate = Vec2::ZERO;
+
+        let t = smoothing_factor(self.smoothing, dt);
+        self.angles = self.angles.lerp(*target, t);
+        camera.set_yaw_pitch(self.angles.x, self.angles.y);
+
+        // 水平移动不受俯仰角影响，上升/下降沿世界 Y 轴
+        let (yaw_sin, yaw_cos) = self.angles.x.sin_cos();
+        let forward = Vec3::new(yaw_cos, 0.0, yaw_sin);
+        let right = Vec3::new(-yaw_sin, 0.0, yaw_cos);
+        let target_velocity = (forward * (self.forward - self.backward)
+            + right * (self.right - self.left)
+            + Vec3::Y * (self.up - self.down))
+            * self.speed;
+        self.velocity = self.velocity.lerp(target_velocity, t);
+        camera.position += self.velocity * dt;
+
+        // 滚轮沿视线方向前后移动，每行移动 0.5 秒的距离
+        camera.position += camera.forward() * self.scroll * self.speed * 0.5;
+        self.scroll = 0.0;
+    }
+}
+
+/// 围绕目标点旋转：左键拖动旋转，右键或中键拖动平移，滚轮缩放距离
+#[derive(Debug)]
+pub struct OrbitController {
+    pub target: Vec3,
+    /// 每像素拖动转过的弧度
+    pub rotate_speed: f32,
+    /// 每行滚动缩放的比例
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// 平滑的半衰期（秒），0 表示不平滑
+    pub smoothing: f32,
+    drag: Drag,
+    scroll: f32,
+    viewport: PhysicalSize<u32>,
+    // 目标状态：偏航角、俯仰角、距离、目标点
+    goal: Option<(Vec2, f32, Vec3)>,
+    angles: Vec2,
+    distance: f32,
+    focus: Vec3,
+}
+
+#[allow(dead_code)]
+impl OrbitController {
+    pub fn new(target: Vec3) -> Self {
+        Self {
+            target,
+            rotate_speed: 0.005,
+            zoom_speed: 0.1,
+            min_distance: 0.01,
+            max_distance: f32::MAX,
+            smoothing: 0.05,
+            drag: Drag::default(),
+            scroll: 0.0,
+            viewport: PhysicalSize::new(1, 1),
+            goal: None,
+            angles: Vec2::ZERO,
+            distance: 1.0,
+            focus: target,
+        }
+    }
+
+    pub fn with_distance_limits(mut self, min: f32, max: f32) -> Self {
+        self.min_distance = min;
+        self.max_distance = max;
+        self
+    }
+
+    pub fn with_smoothing(mut self, half_life: f32) -> Self {
+        self.smoothing = half_life;
+        self
+    }
+}
+
+impl CameraController for OrbitController {
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        self.drag.mouse_click(state, button)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.scroll += scroll_lines(&delta);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.drag.cursor_move(position)
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    fn reset(&mut self) {
+        self.goal = None;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: Duration) {
+        let (goal_angles, goal_distance, goal_focus) = self.goal.get_or_insert_with(|| {
+            // 以相机当前的位置和朝向反推轨道参数
+            let offset = camera.position - self.target;
+            let distance = offset.length().max(self.min_distance);
+            let dir = -offset.normalize_or(Vec3::Z);
+            self.angles = Vec2::new(dir.z.atan2(dir.x), dir.y.clamp(-1.0, 1.0).asin());
+            self.distance = distance;
+            self.focus = self.target;
+            (self.angles, distance, self.target)
+        });
+
+        let delta = self.drag.take_delta();
+        if self.drag.left {
+            goal_angles.x += delta.x * self.rotate_speed;
+            // 向下拖动时相机向上转到目标点上方
+            goal_angles.y = (goal_angles.y - delta.y * self.rotate_speed)
+                .clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+        } else if self.drag.right || self.drag.middle {
+            // 让目标点所在平面上的内容跟随光标
+            let scale = units_per_pixel(&camera.projection, *goal_distance, self.viewport.height);
+            self.target += (camera.right() * -delta.x + camera.up() * delta.y) * scale;
+        }
+        *goal_focus = self.target;
+
+        *goal_distance = (*goal_distance * (1.0 - self.zoom_speed).powf(self.scroll))
+            .clamp(self.min_distance, self.max_distance);
+        self.scroll = 0.0;
+
+        let t = smoothing_factor(self.smoothing, dt.as_secs_f32());
+        self.angles = self.angles.lerp(*goal_angles, t);
+        self.distance += (*goal_distance - self.distance) * t;
+        self.focus = self.focus.lerp(*goal_focus, t);
+
+        camera.set_yaw_pitch(self.angles.x, self.angles.y);
+        camera.position = self.focus - camera.forward() * self.distance;
+    }
+}
+
+/// 轨迹球：拖动时把光标投影到包围目标点的虚拟球面上，按球面上的弧旋转，可以自由翻转
+///
+/// 左键拖动旋转，滚轮缩放距离。需要在窗口大小变化时调用 [`resize`](CameraController::resize)
+#[derive(Debug)]
+pub struct ArcballController {
+    pub target: Vec3,
+    /// 每行滚动缩放的比例
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    /// 平滑的半衰期（秒），0 表示不平滑
+    pub smoothing: f32,
+    drag: Drag,
+    last_sphere_point: Option<Vec3>,
+    pending: Quat,
+    scroll: f32,
+    viewport: PhysicalSize<u32>,
+    goal: Option<(Quat, f32)>,
+    rotation: Quat,
+    distance: f32,
+}
+
+#[allow(dead_code)]
+impl ArcballController {
+    pub fn new(target: Vec3) -> Self {
+        Self {
+            target,
+            zoom_speed: 0.1,
+            min_distance: 0.01,
+            smoothing: 0.05,
+            drag: Drag::default(),
+            last_sphere_point: None,
+            pending: Quat::IDENTITY,
+            scroll: 0.0,
+            viewport: PhysicalSize::new(1, 1),
+            goal: None,
+            rotation: Quat::IDENTITY,
+            distance: 1.0,
+        }
+    }
+
+    pub fn with_smoothing(mut self, half_life: f32) -> Self {
+        self.smoothing = half_life;
+        self
+    }
+
+    /// 把光标位置映射到单位球面上（视图空间），球外的点落在 Bell 双曲面上以保证连续
+    fn sphere_point(&self, cursor: Vec2) -> Vec3 {
+        let size =
+            Vec2::new(self.viewport.width as f32, self.viewport.height as f32).max(Vec2::ONE);
+        let p = (cursor * 2.0 - size) / size.min_element() * Vec2::new(1.0, -1.0);
+        let d2 = p.length_squared();
+        let z = if d2 <= 0.5 {
+            (1.0 - d2).sqrt()
+        } else {
+            0.5 / d2.sqrt()
+        };
+        Vec3::new(p.x, p.y, z).normalize()
+    }
+}
+
+impl CameraController for ArcballController {
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        let handled = self.drag.mouse_click(state, button);
+        if button == MouseButton::Left {
+            self.last_sphere_point = None;
+        }
+        handled
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.scroll += scroll_lines(&delta);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.drag.cursor_move(position);
+        if !self.drag.left {
+            return false;
+        }
+        let point = self.sphere_point(Vec2::new(position.x as f32, position.y as f32));
+        if let Some(last) = self.last_sphere_point {
+            // 物体随光标转动，相当于相机反向转动
+            self.pending = Quat::from_rotation_arc(point, last) * self.pending;
+        }
+        self.last_sphere_point = Some(point);
+        true
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    fn reset(&mut self) {
+        self.goal = None;
+        self.pending = Quat::IDENTITY;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: Duration) {
+        let (goal_rotation, goal_distance) = self.goal.get_or_insert_with(|| {
+            camera.look_at(self.target);
+            self.rotation = camera.rotation;
+            self.distance = (camera.position - self.target)
+                .length()
+                .max(self.min_distance);
+            (self.rotation, self.distance)
+        });
+        self.drag.take_delta();
+
+        // pending 是视图空间中的旋转，转换到世界空间后作用在相机朝向上
+        *goal_rotation =
+            (*goal_rotation * std::mem::replace(&mut self.pending, Quat::IDENTITY)).normalize();
+        *goal_distance =
+            (*goal_distance * (1.0 - self.zoom_speed).powf(self.scroll)).max(self.min_distance);
+        self.scroll = 0.0;
+
+        let t = smoothing_factor(self.smoothing, dt.as_secs_f32());
+        self.rotation = self.rotation.slerp(*goal_rotation, t);
+        self.distance += (*goal_distance - self.distance) * t;
+
+        camera.rotation = self.rotation;
+        camera.position = self.target - camera.forward() * self.distance;
+    }
+}
+
+/// 2D 平移缩放：左键或中键拖动平移，滚轮以光标为中心缩放
+///
+/// 配合 [`Projection::Orthographic`] 使用，缩放时修改投影的 `height`；
+/// 透视投影下缩放改为沿视线方向移动相机
+#[derive(Debug)]
+pub struct PanZoomController {
+    /// 每行滚动缩放的比例
+    pub zoom_speed: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+    /// 平滑的半衰期（秒），0 表示不平滑
+    pub smoothing: f32,
+    drag: Drag,
+    scroll: f32,
+    viewport: PhysicalSize<u32>,
+    goal: Option<(Vec3, f32)>,
+}
+
+#[allow(dead_code)]
+impl PanZoomController {
+    pub fn new() -> Self {
+        Self {
+            zoom_speed: 0.1,
+            min_height: 1e-3,
+            max_height: 1e6,
+            smoothing: 0.05,
+            drag: Drag::default(),
+            scroll: 0.0,
+            viewport: PhysicalSize::new(1, 1),
+            goal: None,
+        }
+    }
+
+    pub fn with_smoothing(mut self, half_life: f32) -> Self {
+        self.smoothing = half_life;
+        self
+    }
+}
+
+impl Default for PanZoomController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController for PanZoomController {
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        self.drag.mouse_click(state, button)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.scroll += scroll_lines(&delta);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.drag.cursor_move(position)
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    fn reset(&mut self) {
+        self.goal = None;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: Duration) {
+        let height = match camera.projection {
+            Projection::Orthographic { height, .. } => height,
+            Projection::Perspective { .. } | Projection::InfiniteReverseZ { .. } => 0.0,
+        };
+        let (goal_position, goal_height) = self.goal.get_or_insert((camera.position, height));
+        let viewport =
+            Vec2::new(self.viewport.width as f32, self.viewport.height as f32).max(Vec2::ONE);
+        // 正交投影按目标高度换算，平滑过程中连续缩放时光标下的点才不会漂移；
+        // 透视投影下按到原点的距离换算
+        let units_per_pixel = if *goal_height > 0.0 {
+            *goal_height / viewport.y
+        } else {
+            units_per_pixel(
+                &camera.projection,
+                goal_position.length().max(1.0),
+                self.viewport.height,
+            )
+        };
+
+        let delta = self.drag.take_delta();
+        if self.drag.left || self.drag.middle {
+            *goal_position += (camera.right() * -delta.x + camera.up() * delta.y) * units_per_pixel;
+        }
+
+        if self.scroll != 0.0 {
+            let scale = (1.0 - self.zoom_speed).powf(self.scroll);
+            if *goal_height > 0.0 {
+                let new_height = (*goal_height * scale).clamp(self.min_height, self.max_height);
+                // 保持光标下的世界坐标不动
+                if let Some(cursor) = self.drag.cursor {
+                    let offset = (cursor - viewport * 0.5) * Vec2::new(1.0, -1.0);
+                    let shift = offset * (units_per_pixel - new_height / viewport.y);
+                    *goal_position += camera.right() * shift.x + camera.up() * shift.y;
+                }
+                *goal_height = new_height;
+            } else {
+                *goal_position +=
+                    camera.forward() * (1.0 - scale) * goal_position.length().max(1.0);
+            }
+            self.scroll = 0.0;
+        }
+
+        let t = smoothing_factor(self.smoothing, dt.as_secs_f32());
+        camera.position = camera.position.lerp(*goal_position, t);
+        if let Projection::Orthographic { height, .. } = &mut camera.projection {
+            *height += (*goal_height - *height) * t;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaw_pitch_round_trip() {
+        let mut camera = Camera::new(Vec3::ZERO, Projection::perspective(45.0, 0.1, 100.0), 4, 3);
+        // 与教程的 FPS 相机一致：偏航角 -90° 朝向 -Z
+        camera.set_yaw_pitch(-FRAC_PI_2, 0.0);
+        assert!(camera.forward().abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!(camera.up().abs_diff_eq(Vec3::Y, 1e-5));
+
+        camera.set_yaw_pitch(0.7, -0.3);
+        let (yaw, pitch) = camera.yaw_pitch();
+        assert!((yaw - 0.7).abs() < 1e-5 && (pitch + 0.3).abs() < 1e-5);
+
+        let camera = camera.looking_at(Vec3::new(3.0, 0.0, 0.0));
+        assert!(camera.forward().abs_diff_eq(Vec3::X, 1e-5));
+        let view = camera.view_matrix();
+        let expected = Mat4::look_at_rh(Vec3::ZERO, Vec3::X, Vec3::Y);
+        assert!(view.abs_diff_eq(expected, 1e-5));
+    }
+
+    #[test]
+    fn reverse_z_maps_near_to_one() {
+        let projection = Projection::infinite_reverse_z(60.0, 0.1);
+        let m = projection.matrix(1.0);
+        let near = m.project_point3(Vec3::new(0.0, 0.0, -0.1));
+        let far = m.project_point3(Vec3::new(0.0, 0.0, -1e6));
+        assert!((near.z - 1.0).abs() < 1e-5);
+        assert!(far.z < 1e-5);
+        assert_eq!(projection.depth_compare(), wgpu::CompareFunction::Greater);
+    }
+
+    #[test]
+    fn orbit_keeps_distance_and_smooths_toward_goal() {
+        let mut camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Projection::perspective(45.0, 0.1, 100.0),
+            800,
+            600,
+        );
+        let mut orbit = OrbitController::new(Vec3::ZERO).with_smoothing(0.1);
+        orbit.update(&mut camera, Duration::from_millis(16));
+        assert!(camera.position.abs_diff_eq(Vec3::new(0.0, 0.0, 5.0), 1e-4));
+
+        orbit.mouse_wheel(MouseScrollDelta::LineDelta(0.0, 1.0), TouchPhase::Moved);
+        orbit.update(&mut camera, Duration::from_millis(100));
+        // 一个半衰期后走完一半：5 -> 4.5 的一半
+        assert!((camera.position.length() - 4.75).abs() < 1e-3);
+        for _ in 0..100 {
+            orbit.update(&mut camera, Duration::from_millis(100));
+        }
+        assert!((camera.position.length() - 4.5).abs() < 1e-3);
+        assert!(
+            camera
+                .forward()
+                .abs_diff_eq(-camera.position.normalize(), 1e-4)
+        );
+    }
+
+    #[test]
+    fn arcball_rotation_keeps_target_centered() {
+        let mut camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Projection::perspective(45.0, 0.1, 100.0),
+            800,
+            800,
+        );
+        let mut arcball = ArcballController::new(Vec3::ZERO).with_smoothing(0.0);
+        arcball.resize(PhysicalSize::new(800, 800));
+        arcball.mouse_click(ElementState::Pressed, MouseButton::Left);
+        arcball.cursor_move(PhysicalPosition::new(400.0, 400.0));
+        arcball.cursor_move(PhysicalPosition::new(600.0, 400.0));
+        arcball.update(&mut camera, Duration::from_millis(16));
+
+        assert!((camera.position.length() - 5.0).abs() < 1e-4);
+        assert!(
+            camera
+                .forward()
+                .abs_diff_eq(-camera.position.normalize(), 1e-4)
+        );
+        // 向右拖动，物体向右转，相机移到左侧
+        assert!(camera.position.x < -1.0);
+    }
+
+    #[test]
+    fn pan_zoom_keeps_cursor_anchored_while_smoothing() {
+        let mut camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Projection::orthographic(10.0, 0.1, 100.0),
+            800,
+            800,
+        );
+        let mut pan_zoom = PanZoomController::new().with_smoothing(0.5);
+        pan_zoom.resize(PhysicalSize::new(800, 800));
+        pan_zoom.cursor_move(PhysicalPosition::new(600.0, 200.0));
+
+        // 光标在视口中的偏移，以像素为单位，y 轴向上
+        let offset = Vec2::new(200.0, 200.0);
+        let anchor = |goal: (Vec3, f32)| goal.0.truncate() + offset * goal.1 / 800.0;
+        pan_zoom.update(&mut camera, Duration::from_millis(16));
+        let expected = anchor(pan_zoom.goal.unwrap());
+
+        // 第一次缩放尚未平滑完成时再次缩放
+        for _ in 0..2 {
+            pan_zoom.mouse_wheel(MouseScrollDelta::LineDelta(0.0, 1.0), TouchPhase::Moved);
+            pan_zoom.update(&mut camera, Duration::from_millis(16));
+        }
+        let goal = pan_zoom.goal.unwrap();
+        assert!((goal.1 - 10.0 * 0.9 * 0.9).abs() < 1e-4);
+        assert!(anchor(goal).abs_diff_eq(expected, 1e-4));
+    }
+}
@@ -1,3 +1,13 @@
+//! 颜色打包、色彩空间转换、混合与渐变查找表
+//!
+//! 没有特别说明时，颜色均为 `[0, 1]` 范围内的浮点数：
+//! `Vec4` 为带 alpha 的 sRGB（gamma 编码，与十六进制颜色、取色器中的数值一致），
+//! 带 `linear` 字样的函数使用线性空间的 RGB，着色器中的光照计算与混合都应在线性空间进行。
+
+use crate::load_texture::AnyTexture;
+use crate::{Error, Result};
+use glam::{Vec3, Vec4};
+
 pub fn pack_rgba8_to_u32(rgba: &[u8]) -> u32 {
     ((rgba[0] as u32) << 24) | ((rgba[1] as u32) << 16) | ((rgba[2] as u32) << 8) | rgba[3] as u32
 }
@@ -30,3 +40,494 @@ pub fn unpack_u32_to_color(value: u32) -> wgpu::Color {
         a: rgba8[3] as f64 / 255.0,
     }
 }
+
+/// sRGB 编码的分量转换到线性空间
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// 线性空间的分量编码为 sRGB
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// alpha 保持不变
+pub fn srgba_to_linear(srgba: Vec4) -> Vec4 {
+    Vec3::from_array(srgba.truncate().to_array().map(srgb_to_linear)).extend(srgba.w)
+}
+
+/// alpha 保持不变
+pub fn linear_to_srgba(linear: Vec4) -> Vec4 {
+    Vec3::from_array(linear.truncate().to_array().map(linear_to_srgb)).extend(linear.w)
+}
+
+/// RGB 转 HSV，h 为角度 `[0, 360)`，s、v 为 `[0, 1]`
+pub fn rgb_to_hsv(rgb: Vec3) -> Vec3 {
+    let max = rgb.max_element();
+    let chroma = max - rgb.min_element();
+    let s = if max > 0.0 { chroma / max } else { 0.0 };
+    Vec3::new(hue(rgb, max, chroma), s, max)
+}
+
+pub fn hsv_to_rgb(hsv: Vec3) -> Vec3 {
+    let chroma = hsv.z * hsv.y;
+    from_hue_chroma(hsv.x, chroma, hsv.z - chroma)
+}
+
+/// RGB 转 HSL，h 为角度 `[0, 360)`，s、l 为 `[0, 1]`
+pub fn rgb_to_hsl(rgb: Vec3) -> Vec3 {
+    let max = rgb.max_element();
+    let min = rgb.min_element();
+    let chroma = max - min;
+    let l = (max + min) * 0.5;
+    let s = if l > 0.0 && l < 1.0 {
+        chroma / (1.0 - (2.0 * l - 1.0).abs())
+    } else {
+        0.0
+    };
+    Vec3::new(hue(rgb, max, chroma), s, l)
+}
+
+pub fn hsl_to_rgb(hsl: Vec3) -> Vec3 {
+    let chroma = (1.0 - (2.0 * hsl.z - 1.0).abs()) * hsl.y;
+    from_hue_chroma(hsl.x, chroma, hsl.z - chroma * 0.5)
+}
+
+fn hue(rgb: Vec3, max: f32, chroma: f32) -> f32 {
+    if chroma <= 0.0 {
+        return 0.0;
+    }
+    let h = if max == rgb.x {
+        ((rgb.y - rgb.z) / chroma).rem_euclid(6.0)
+    } else if max == rgb.y {
+        (rgb.z - rgb.x) / chroma + 2.0
+    } else {
+        (rgb.x - rgb.y) / chroma + 4.0
+    };
+    h * 60.0
+}
+
+fn from_hue_chroma(h: f32, chroma: f32, m: f32) -> Vec3 {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let rgb = match h as u32 {
+        0 => Vec3::new(chroma, x, 0.0),
+        1 => Vec3::new(x, chroma, 0.0),
+        2 => Vec3::new(0.0, chroma, x),
+        3 => Vec3::new(0.0, x, chroma),
+        4 => Vec3::new(x, 0.0, chroma),
+        _ => Vec3::new(chroma, 0.0, x),
+    };
+    rgb + m
+}
+
+/// 线性 RGB 转 OKLab（Björn Ottosson），L 为 `[0, 1]`
+#[allow(clippy::excessive_precision)]
+pub fn linear_to_oklab(rgb: Vec3) -> Vec3 {
+    let lms = Vec3::new(
+        0.4122214708 * rgb.x + 0.5363325363 * rgb.y + 0.0514459929 * rgb.z,
+        0.2119034982 * rgb.x + 0.6806995451 * rgb.y + 0.1073969566 * rgb.z,
+        0.0883024619 * rgb.x + 0.2817188376 * rgb.y + 0.6299787005 * rgb.z,
+    );
+    let [l, m, s] = lms.to_array().map(f32::cbrt);
+    Vec3::new(
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    )
+}
+
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_linear(lab: Vec3) -> Vec3 {
+    let l = lab.x + 0.3963377774 * lab.y + 0.2158037573 * lab.z;
+    let m = lab.x - 0.1055613458 * lab.y - 0.0638541728 * lab.z;
+    let s = lab.x - 0.0894841775 * lab.y - 1.2914855480 * lab.z;
+    let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+    Vec3::new(
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    )
+}
+
+/// OKLab 转 OKLCH，h 为角度 `[0, 360)`
+pub fn oklab_to_oklch(lab: Vec3) -> Vec3 {
+    let c = (lab.y * lab.y + lab.z * lab.z).sqrt();
+    let h = lab.z.atan2(lab.y).to_degrees().rem_euclid(360.0);
+    Vec3::new(lab.x, c, h)
+}
+
+pub fn oklch_to_oklab(lch: Vec3) -> Vec3 {
+    let (sin, cos) = lch.z.to_radians().sin_cos();
+    Vec3::new(lch.x, lch.y * cos, lch.y * sin)
+}
+
+/// 解析 `#rgb`、`#rgba`、`#rrggbb`、`#rrggbbaa` 形式的颜色，`#` 可省略
+pub fn parse_hex(hex: &str) -> Result<Vec4> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    let invalid = || Error::Decode(format!("无效的十六进制颜色: {hex}"));
+    if !digits.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+    let mut rgba = [255u8; 4];
+    match digits.len() {
+        3 | 4 => {
+            for (i, c) in rgba.iter_mut().zip(0..digits.len()) {
+                // 单个数字重复一次，例如 f -> ff
+                *i = channel(&digits[c..c + 1])? * 17;
+            }
+        }
+        6 | 8 => {
+            for (i, c) in rgba.iter_mut().zip((0..digits.len()).step_by(2)) {
+                *i = channel(&digits[c..c + 2])?;
+            }
+        }
+        _ => return Err(invalid()),
+    }
+    Ok(Vec4::from_array(rgba.map(|c| c as f32 / 255.0)))
+}
+
+/// 转换为 `#rrggbb`，alpha 小于 1 时为 `#rrggbbaa`
+pub fn to_hex(srgba: Vec4) -> String {
+    let [r, g, b, a] = srgba_to_rgba8(srgba);
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+pub fn srgba_to_rgba8(srgba: Vec4) -> [u8; 4] {
+    srgba
+        .clamp(Vec4::ZERO, Vec4::ONE)
+        .to_array()
+        .map(|c| (c * 255.0).round() as u8)
+}
+
+/// 颜色插值使用的色彩空间
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// 直接插值 sRGB 编码的数值，与 CSS、matplotlib 的色图一致
+    Srgb,
+    /// 线性空间插值，符合物理上的混合，但中间色偏亮
+    Linear,
+    /// OKLab 空间插值，感知上最均匀
+    #[default]
+    Oklab,
+}
+
+/// 在指定的色彩空间中插值两个 sRGB 颜色，alpha 总是线性插值
+pub fn mix(a: Vec4, b: Vec4, t: f32, interpolation: Interpolation) -> Vec4 {
+    let alpha = a.w + (b.w - a.w) * t;
+    let rgb = match interpolation {
+        Interpolation::Srgb => a.truncate().lerp(b.truncate(), t),
+        Interpolation::Linear => {
+            let mixed = srgba_to_linear(a).lerp(srgba_to_linear(b), t);
+            linear_to_srgba(mixed).truncate()
+        }
+        Interpolation::Oklab => {
+            let to_lab = |c: Vec4| linear_to_oklab(srgba_to_linear(c).truncate());
+            let lab = to_lab(a).lerp(to_lab(b), t);
+            linear_to_srgba(oklab_to_linear(lab).extend(1.0)).truncate()
+        }
+    };
+    rgb.clamp(Vec3::ZERO, Vec3::ONE).extend(alpha)
+}
+
+pub fn premultiply(c: Vec4) -> Vec4 {
+    (c.truncate() * c.w).extend(c.w)
+}
+
+/// alpha 为 0 时返回全 0
+pub fn unpremultiply(c: Vec4) -> Vec4 {
+    if c.w > 0.0 {
+        (c.truncate() / c.w).extend(c.w)
+    } else {
+        Vec4::ZERO
+    }
+}
+
+/// Porter-Duff source-over 混合，输入输出均为非预乘 alpha 的线性颜色
+pub fn blend_over(src: Vec4, dst: Vec4) -> Vec4 {
+    unpremultiply(premultiply(src) + premultiply(dst) * (1.0 - src.w))
+}
+
+/// 由若干色标组成的渐变，色标颜色为 sRGB
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Vec4)>,
+    pub interpolation: Interpolation,
+}
+
+#[allow(dead_code)]
+impl Gradient {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            stops: vec![],
+            interpolation,
+        }
+    }
+
+    /// 在 `position` 处添加色标，色标按位置排序
+    pub fn with_stop(mut self, position: f32, srgba: Vec4) -> Self {
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, srgba));
+        self
+    }
+
+    pub fn with_hex_stop(self, position: f32, hex: &str) -> Result<Self> {
+        Ok(self.with_stop(position, parse_hex(hex)?))
+    }
+
+    /// 十六进制颜色等距分布在 `[0, 1]` 上
+    pub fn from_hex(hex: &[&str], interpolation: Interpolation) -> Result<Self> {
+        let last = hex.len().saturating_sub(1).max(1) as f32;
+        hex.iter()
+            .enumerate()
+            .try_fold(Self::new(interpolation), |gradient, (i, hex)| {
+                gradient.with_hex_stop(i as f32 / last, hex)
+            })
+    }
+
+    pub fn stops(&self) -> &[(f32, Vec4)] {
+        &self.stops
+    }
+
+    /// 在 `t` 处取色，超出色标范围时取两端的颜色；没有色标时返回透明黑
+    pub fn sample(&self, t: f32) -> Vec4 {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Vec4::ZERO;
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let i = self.stops.partition_point(|(p, _)| *p <= t);
+        let (p0, c0) = self.stops[i - 1];
+        let (p1, c1) = self.stops[i];
+        let span = p1 - p0;
+        let local = if span > 0.0 { (t - p0) / span } else { 1.0 };
+        mix(c0, c1, local, self.interpolation)
+    }
+
+    /// 等距采样 `width` 个像素，返回 sRGB 编码的 RGBA8 数据
+    pub fn bake_rgba8(&self, width: u32) -> Vec<u8> {
+        let last = width.saturating_sub(1).max(1) as f32;
+        (0..width)
+            .flat_map(|i| srgba_to_rgba8(self.sample(i as f32 / last)))
+            .collect()
+    }
+
+    /// 烘焙为 `Rgba8UnormSrgb` 格式的 1D 纹理，着色器中采样得到线性颜色
+    ///
+    /// ```wgsl
+    /// @group(0) @binding(0) var lut: texture_1d<f32>;
+    /// @group(0) @binding(1) var lut_sampler: sampler;
+    /// let color = textureSample(lut, lut_sampler, saturate(value));
+    /// ```
+    /// 1D 纹理不支持 `textureSampleLevel`，在计算着色器中需改用 `textureLoad`；
+    /// WebGL 不支持 1D 纹理，此时可改用 [`to_texture_2d`](Self::to_texture_2d)。
+    /// 采样器应使用 `ClampToEdge` 与线性过滤。
+    #[track_caller]
+    pub fn to_texture(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        label: Option<&'static str>,
+    ) -> AnyTexture {
+        self.upload(device, queue, width, wgpu::TextureViewDimension::D1, label)
+    }
+
+    /// 烘焙为高度为 1 的 2D 纹理，以 `vec2f(value, 0.5)` 采样
+    #[track_caller]
+    pub fn to_texture_2d(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        label: Option<&'static str>,
+    ) -> AnyTexture {
+        self.upload(device, queue, width, wgpu::TextureViewDimension::D2, label)
+    }
+
+    #[track_caller]
+    fn upload(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        view_dimension: wgpu::TextureViewDimension,
+        label: Option<&'static str>,
+    ) -> AnyTexture {
+        let width = width.max(1);
+        let size = wgpu::Extent3d {
+            width,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let tex = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: match view_dimension {
+                wgpu::TextureViewDimension::D1 => wgpu::TextureDimension::D1,
+                _ => wgpu::TextureDimension::D2,
+            },
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            tex.as_image_copy(),
+            &self.bake_rgba8(width),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: None,
+            },
+            size,
+        );
+        let tex_view = tex.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(view_dimension),
+            ..Default::default()
+        });
+        AnyTexture::from_parts(tex, tex_view, view_dimension, label)
+    }
+}
+
+/// 常用的科学可视化色图，适合热力图与按高度着色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis,
+    Inferno,
+    Magma,
+    Plasma,
+}
+
+impl Colormap {
+    /// matplotlib 色图的 9 个等距采样点，在 sRGB 空间插值
+    fn hex(self) -> [&'static str; 9] {
+        match self {
+            Self::Viridis => [
+                "440154", "472d7b", "3b528b", "2c728e", "21918c", "28ae80", "5ec962", "addc30",
+                "fde725",
+            ],
+            Self::Inferno => [
+                "000004", "1f0c48", "550f6d", "88226a", "ba3655", "e35933", "f98e09", "f9cb35",
+                "fcffa4",
+            ],
+            Self::Magma => [
+                "000004", "1c1044", "4f127b", "812581", "b5367a", "e55064", "fb8761", "fec287",
+                "fcfdbf",
+            ],
+            Self::Plasma => [
+                "0d0887", "4c02a1", "7e03a8", "a92395", "cc4778", "e56b5d", "f89540", "fdc527",
+                "f0f921",
+            ],
+        }
+    }
+
+    pub fn gradient(self) -> Gradient {
+        Gradient::from_hex(&self.hex(), Interpolation::Srgb).expect("built-in colormap")
+    }
+
+    pub fn sample(self, t: f32) -> Vec4 {
+        self.gradient().sample(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_linear_round_trip() {
+        for i in 0..=255 {
+            let c = i as f32 / 255.0;
+            assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+        }
+        assert!((srgb_to_linear(0.5) - 0.21404).abs() < 1e-4);
+    }
+
+    #[test]
+    fn hsv_hsl_round_trip() {
+        let colors = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.2, 0.6, 0.4),
+            Vec3::new(0.9, 0.8, 0.1),
+            Vec3::new(0.3, 0.1, 0.7),
+            Vec3::splat(0.5),
+        ];
+        for c in colors {
+            assert!(hsv_to_rgb(rgb_to_hsv(c)).abs_diff_eq(c, 1e-5), "{c}");
+            assert!(hsl_to_rgb(rgb_to_hsl(c)).abs_diff_eq(c, 1e-5), "{c}");
+        }
+        assert!(rgb_to_hsv(Vec3::new(0.0, 1.0, 0.0)).abs_diff_eq(Vec3::new(120.0, 1.0, 1.0), 1e-5));
+        assert!(rgb_to_hsl(Vec3::new(0.0, 0.0, 1.0)).abs_diff_eq(Vec3::new(240.0, 1.0, 0.5), 1e-5));
+    }
+
+    #[test]
+    fn oklab_matches_reference() {
+        // Björn Ottosson 给出的参考值
+        let white = linear_to_oklab(Vec3::ONE);
+        assert!(white.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-3));
+        let red = linear_to_oklab(Vec3::X);
+        assert!(red.abs_diff_eq(Vec3::new(0.628, 0.225, 0.126), 1e-3));
+        let c = Vec3::new(0.1, 0.5, 0.8);
+        assert!(oklab_to_linear(linear_to_oklab(c)).abs_diff_eq(c, 1e-4));
+        let lch = oklab_to_oklch(red);
+        assert!(oklch_to_oklab(lch).abs_diff_eq(red, 1e-5));
+    }
+
+    #[test]
+    fn hex_parsing() {
+        assert_eq!(
+            parse_hex("#ff8000").unwrap(),
+            Vec4::new(1.0, 128.0 / 255.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            parse_hex("f80").unwrap(),
+            Vec4::new(1.0, 136.0 / 255.0, 0.0, 1.0)
+        );
+        assert_eq!(parse_hex("#00000080").unwrap().w, 128.0 / 255.0);
+        assert!(parse_hex("#12345").is_err());
+        assert!(parse_hex("#gg0000").is_err());
+        assert!(parse_hex("#é00").is_err());
+        assert_eq!(to_hex(parse_hex("#3b528b").unwrap()), "#3b528b");
+        assert_eq!(to_hex(Vec4::new(1.0, 0.0, 0.0, 0.5)), "#ff000080");
+    }
+
+    #[test]
+    fn gradient_sampling_and_blending() {
+        let gradient = Colormap::Viridis.gradient();
+        assert_eq!(to_hex(gradient.sample(-1.0)), "#440154");
+        assert_eq!(to_hex(gradient.sample(0.5)), "#21918c");
+        assert_eq!(to_hex(gradient.sample(2.0)), "#fde725");
+        let lut = gradient.bake_rgba8(9);
+        assert_eq!(&lut[4 * 4..4 * 5], &[0x21, 0x91, 0x8c, 0xff]);
+
+        let gray = mix(
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+            Vec4::ONE,
+            0.5,
+            Interpolation::Linear,
+        );
+        assert!((gray.x - linear_to_srgb(0.5)).abs() < 1e-5);
+        let over = blend_over(Vec4::new(1.0, 0.0, 0.0, 0.5), Vec4::new(0.0, 0.0, 1.0, 1.0));
+        assert!(over.abs_diff_eq(Vec4::new(0.5, 0.0, 0.5, 1.0), 1e-6));
+    }
+}
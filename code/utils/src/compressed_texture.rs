@@ -0,0 +1,796 @@
+//! KTX2 / DDS 容器中的纹理
+//!
+//! 容器中预先生成的 mip 链会被完整上传。BCn / ETC2 / ASTC 压缩数据在设备支持对应的
+//! `TEXTURE_COMPRESSION_*` 特性时直接上传，否则在 CPU 上解码：SNORM 格式解码为 `R8Snorm` /
+//! `Rg8Snorm`，BC6H 解码为 `Rgba16Float`，其余解码为 BGRA8。着色器读到的值与直接上传时一致，
+//! 同一份资源因此可以在所有平台上使用。
+
+use crate::load_texture::{AnyTexture, ColorSpace};
+use crate::{Error, Result, ValidationScope};
+use std::io::Read;
+use wgpu::util::DeviceExt;
+use wgpu::{AstcBlock, AstcChannel, Extent3d, TextureDimension, TextureFormat};
+
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+/// 数据是否为 KTX2 或 DDS 容器
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(&KTX2_MAGIC) || bytes.starts_with(&DDS_MAGIC)
+}
+
+/// 从容器中读出的纹理数据
+pub struct TextureContainer {
+    pub format: TextureFormat,
+    /// 数组纹理与立方体贴图的 `depth_or_array_layers` 为层数 × 面数
+    pub size: Extent3d,
+    pub dimension: TextureDimension,
+    pub view_dimension: wgpu::TextureViewDimension,
+    /// 每级 mip 的数据，同一级中的各层（或 3D 纹理的各深度切片）依次紧密排列
+    pub levels: Vec<Vec<u8>>,
+}
+
+#[allow(dead_code)]
+impl TextureContainer {
+    /// 根据文件头识别容器类型并解析
+    ///
+    /// `color_space` 只用于不带色彩空间信息的旧式 DDS 格式
+    pub fn parse(bytes: &[u8], color_space: ColorSpace) -> Result<Self> {
+        if bytes.starts_with(&KTX2_MAGIC) {
+            Self::from_ktx2(bytes)
+        } else if bytes.starts_with(&DDS_MAGIC) {
+            Self::from_dds(bytes, color_space)
+        } else {
+            Err(Error::UnsupportedFormat("不是 KTX2 或 DDS 文件".into()))
+        }
+    }
+
+    pub fn from_ktx2(bytes: &[u8]) -> Result<Self> {
+        let reader = ktx2::Reader::new(bytes).map_err(|e| Error::Decode(format!("KTX2: {e:?}")))?;
+        let header = reader.header();
+        let format = header
+            .format
+            .and_then(ktx2_format)
+            .ok_or_else(|| Error::UnsupportedFormat(format!("KTX2 格式 {:?}", header.format)))?;
+
+        let layers = header.layer_count.max(1) * header.face_count.max(1);
+        let (dimension, depth_or_array_layers) = if header.pixel_depth > 1 {
+            (TextureDimension::D3, header.pixel_depth)
+        } else {
+            (TextureDimension::D2, layers)
+        };
+        let view_dimension =
+            view_dimension(dimension, header.face_count == 6, header.layer_count > 0);
+
+        let levels = reader
+            .levels()
+            .map(|level| match header.supercompression_scheme {
+                None => Ok(level.data.to_vec()),
+                Some(ktx2::SupercompressionScheme::Zstandard) => {
+                    let mut data = Vec::with_capacity(level.uncompressed_byte_length as usize);
+                    let mut decoder = ruzstd::decoding::StreamingDecoder::new(level.data)
+                        .map_err(|e| Error::Decode(format!("KTX2 zstd: {e}")))?;
+                    decoder.read_to_end(&mut data)?;
+                    Ok(data)
+                }
+                Some(scheme) => Err(Error::UnsupportedFormat(format!(
+                    "KTX2 超压缩方式 {scheme:?}"
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            format,
+            size: Extent3d {
+                width: header.pixel_width,
+                height: header.pixel_height.max(1),
+                depth_or_array_layers,
+            },
+            dimension,
+            view_dimension,
+            levels,
+        })
+    }
+
+    pub fn from_dds(bytes: &[u8], color_space: ColorSpace) -> Result<Self> {
+        let dds = ddsfile::Dds::read(bytes).map_err(|e| Error::Decode(format!("DDS: {e}")))?;
+        let format = if let Some(dxgi_format) = dds.get_dxgi_format() {
+            dxgi_format_to_wgpu(dxgi_format)
+        } else {
+            dds.get_d3d_format()
+                .and_then(|d3d_format| d3d_format_to_wgpu(d3d_format, color_space))
+        }
+        .ok_or_else(|| Error::UnsupportedFormat("DDS 格式".into()))?;
+
+        let is_cube = dds.header.caps2.contains(ddsfile::Caps2::CUBEMAP)
+            || dds
+                .header10
+                .as_ref()
+                .is_some_and(|h| h.misc_flag.contains(ddsfile::MiscFlag::TEXTURECUBE));
+        let is_volume = dds.header.caps2.contains(ddsfile::Caps2::VOLUME) && dds.get_depth() > 1;
+        let mut layers = dds.get_num_array_layers().max(1);
+        if is_cube && dds.header10.is_some() {
+            // DX10 头中的数组大小是立方体贴图的个数
+            layers *= 6;
+        }
+        let (dimension, depth_or_array_layers) = if is_volume {
+            (TextureDimension::D3, dds.get_depth())
+        } else {
+            (TextureDimension::D2, layers)
+        };
+        let size = Extent3d {
+            width: dds.get_width(),
+            height: dds.get_height(),
+            depth_or_array_layers,
+        };
+        let level_count = dds.get_num_mipmap_levels().max(1);
+
+        // DDS 中的数据按层排列（每层包含完整的 mip 链），需重排为按 mip 级排列
+        let image_count = if is_volume { 1 } else { layers };
+        let image_sizes: Vec<usize> = (0..level_count)
+            .map(|level| level_byte_size(format, size, dimension, level) / image_count as usize)
+            .collect();
+        let layer_stride: usize = image_sizes.iter().sum();
+        if dds.data.len() < layer_stride * image_count as usize {
+            return Err(Error::Decode("DDS 数据长度不足".into()));
+        }
+        let levels = layer_major_to_mip_major(&dds.data, &image_sizes, image_count as usize);
+
+        Ok(Self {
+            format,
+            size,
+            dimension,
+            view_dimension: view_dimension(
+                dimension,
+                is_cube,
+                layers > if is_cube { 6 } else { 1 },
+            ),
+            levels,
+        })
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// 设备能否直接使用容器中的数据格式
+    pub fn is_supported(&self, device: &wgpu::Device) -> bool {
+        let (block_width, block_height) = self.format.block_dimensions();
+        device.features().contains(self.format.required_features())
+            // 压缩纹理的尺寸需是块尺寸的整数倍
+            && self.size.width.is_multiple_of(block_width)
+            && self.size.height.is_multiple_of(block_height)
+    }
+
+    /// 在 CPU 上解码压缩数据，格式见 [`decoded_format`]，非压缩格式原样返回
+    pub fn decompress(self) -> Result<Self> {
+        if !self.format.is_compressed() {
+            return Ok(self);
+        }
+        let format = decoded_format(self.format);
+        let levels = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(level, data)| {
+                let mip_size = self.size.mip_level_size(level as u32, self.dimension);
+                let (width, height) = (mip_size.width as usize, mip_size.height as usize);
+                let images = mip_size.depth_or_array_layers as usize;
+                let image_bytes = data.len() / images;
+                let mut pixels = Vec::new();
+                for src in data.chunks_exact(image_bytes) {
+                    pixels.extend(decode_image(self.format, src, width, height)?);
+                }
+                Ok(pixels)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            format,
+            levels,
+            ..self
+        })
+    }
+
+    /// 创建纹理并上传所有 mip 级，设备不支持时先在 CPU 上解码
+    #[track_caller]
+    pub fn upload(
+        self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        usage: wgpu::TextureUsages,
+        label: Option<&str>,
+    ) -> Result<AnyTexture> {
+        let container = if self.is_supported(device) {
+            self
+        } else {
+            log::info!(
+                "设备不支持 {:?}，在 CPU 上解码: {}",
+                self.format,
+                label.unwrap_or("unlabeled")
+            );
+            self.decompress()?
+        };
+        let data: Vec<u8> = container.levels.concat();
+        let scope = ValidationScope::push(device);
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label,
+                size: container.size,
+                mip_level_count: container.mip_level_count(),
+                sample_count: 1,
+                dimension: container.dimension,
+                format: container.format,
+                usage: usage | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::MipMajor,
+            &data,
+        );
+        let tex_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(container.view_dimension),
+            ..Default::default()
+        });
+        scope.pop()?;
+
+        Ok(AnyTexture::from_parts(
+            texture,
+            tex_view,
+            container.view_dimension,
+            label,
+        ))
+    }
+}
+
+fn view_dimension(
+    dimension: TextureDimension,
+    is_cube: bool,
+    is_array: bool,
+) -> wgpu::TextureViewDimension {
+    match (dimension, is_cube, is_array) {
+        (TextureDimension::D3, ..) => wgpu::TextureViewDimension::D3,
+        (_, true, true) => wgpu::TextureViewDimension::CubeArray,
+        (_, true, false) => wgpu::TextureViewDimension::Cube,
+        (_, false, true) => wgpu::TextureViewDimension::D2Array,
+        _ => wgpu::TextureViewDimension::D2,
+    }
+}
+
+// 一级 mip 中所有层的字节数
+fn level_byte_size(
+    format: TextureFormat,
+    size: Extent3d,
+    dimension: TextureDimension,
+    level: u32,
+) -> usize {
+    let mip_size = size.mip_level_size(level, dimension);
+    let (block_width, block_height) = format.block_dimensions();
+    let block_bytes = format.block_copy_size(None).unwrap();
+    (mip_size.width.div_ceil(block_width)
+        * mip_size.height.div_ceil(block_height)
+        * mip_size.depth_or_array_layers
+        * block_bytes) as usize
+}
+
+// 将按层排列（每层包含完整的 mip 链）的数据重排为按 mip 级排列，
+// `image_sizes` 是每级 mip 中单张图像的字节数
+fn layer_major_to_mip_major(
+    data: &[u8],
+    image_sizes: &[usize],
+    image_count: usize,
+) -> Vec<Vec<u8>> {
+    let layer_stride: usize = image_sizes.iter().sum();
+    let mut levels: Vec<Vec<u8>> = image_sizes
+        .iter()
+        .map(|size| Vec::with_capacity(size * image_count))
+        .collect();
+    for image in 0..image_count {
+        let mut offset = image * layer_stride;
+        for (level, image_size) in image_sizes.iter().enumerate() {
+            levels[level].extend_from_slice(&data[offset..offset + image_size]);
+            offset += image_size;
+        }
+    }
+    levels
+}
+
+/// 压缩格式在 CPU 上解码后的格式
+///
+/// SNORM 与 HDR 格式保留原有的取值范围，其余格式解码为 BGRA8
+pub fn decoded_format(format: TextureFormat) -> TextureFormat {
+    match format {
+        TextureFormat::Bc4RSnorm | TextureFormat::EacR11Snorm => TextureFormat::R8Snorm,
+        TextureFormat::Bc5RgSnorm | TextureFormat::EacRg11Snorm => TextureFormat::Rg8Snorm,
+        TextureFormat::Bc6hRgbUfloat | TextureFormat::Bc6hRgbFloat => TextureFormat::Rgba16Float,
+        _ if format.is_srgb() => TextureFormat::Bgra8UnormSrgb,
+        _ => TextureFormat::Bgra8Unorm,
+    }
+}
+
+// 解码一张 2D 图像，输出为 `decoded_format(format)` 格式的紧密排列的像素
+fn decode_image(
+    format: TextureFormat,
+    data: &[u8],
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>> {
+    match format {
+        TextureFormat::Bc4RSnorm => decode_by_block(data, width, height, 8, 1, |block, out| {
+            bcdec_rs::bc4(block, out, 4, true)
+        }),
+        TextureFormat::Bc5RgSnorm => decode_by_block(data, width, height, 16, 2, |block, out| {
+            bcdec_rs::bc5(block, out, 8, true)
+        }),
+        TextureFormat::Bc6hRgbUfloat | TextureFormat::Bc6hRgbFloat => {
+            let signed = format == TextureFormat::Bc6hRgbFloat;
+            decode_by_block(data, width, height, 16, 8, |block, out| {
+                let mut rgb = [0u16; 48];
+                bcdec_rs::bc6h_half(block, &mut rgb, 12, signed);
+                for (pixel, rgb) in out.chunks_exact_mut(8).zip(rgb.chunks_exact(3)) {
+                    // alpha 为 1.0
+                    let rgba = [rgb[0], rgb[1], rgb[2], half::f16::ONE.to_bits()];
+                    pixel.copy_from_slice(bytemuck::cast_slice(&rgba));
+                }
+            })
+        }
+        TextureFormat::EacR11Snorm => decode_by_block(data, width, height, 8, 1, |block, out| {
+            out.copy_from_slice(bytemuck::cast_slice(&decode_eac_snorm_block(block)))
+        }),
+        TextureFormat::EacRg11Snorm => decode_by_block(data, width, height, 16, 2, |block, out| {
+            let r = decode_eac_snorm_block(&block[..8]);
+            let g = decode_eac_snorm_block(&block[8..]);
+            for (i, pixel) in out.chunks_exact_mut(2).enumerate() {
+                pixel.copy_from_slice(bytemuck::cast_slice(&[r[i], g[i]]));
+            }
+        }),
+        _ => {
+            let mut pixels = vec![0u32; width * height];
+            decode_blocks(format, data, width, height, &mut pixels)?;
+            Ok(bytemuck::cast_slice(&pixels).to_vec())
+        }
+    }
+}
+
+// 逐块解码，`decode_block` 把一个块解码为 4×4 个 `pixel_bytes` 字节的像素，按行紧密排列
+fn decode_by_block(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    block_bytes: usize,
+    pixel_bytes: usize,
+    decode_block: impl Fn(&[u8], &mut [u8]),
+) -> Result<Vec<u8>> {
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    if data.len() < blocks_x * blocks_y * block_bytes {
+        return Err(Error::Decode("压缩数据不完整".into()));
+    }
+    let mut out = vec![0u8; width * height * pixel_bytes];
+    let mut block_pixels = vec![0u8; 16 * pixel_bytes];
+    for (i, block) in data
+        .chunks_exact(block_bytes)
+        .take(blocks_x * blocks_y)
+        .enumerate()
+    {
+        decode_block(block, &mut block_pixels);
+        // 图像边缘的块只复制图像内的部分
+        let (x, y) = (i % blocks_x * 4, i / blocks_x * 4);
+        let row_bytes = 4.min(width - x) * pixel_bytes;
+        for row in 0..4.min(height - y) {
+            let dst = ((y + row) * width + x) * pixel_bytes;
+            let src = row * 4 * pixel_bytes;
+            out[dst..dst + row_bytes].copy_from_slice(&block_pixels[src..src + row_bytes]);
+        }
+    }
+    Ok(out)
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+// 解码一个 EAC R11 SNORM 块，按行输出 16 个 8 位 SNORM 值
+//
+// 块为大端序：有符号的基准值、乘数与修正表索引各占一个字节的高低 4 位，之后是 16 个 3 位索引，
+// 像素按列排列。11 位的结果范围为 [-1023, 1023]
+fn decode_eac_snorm_block(block: &[u8]) -> [i8; 16] {
+    let base = (block[0] as i8).max(-127) as i32 * 8;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xF) as usize];
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let mut out = [0i8; 16];
+    for i in 0..16 {
+        let modifier = modifiers[((bits >> (45 - 3 * i)) & 7) as usize];
+        let value = if multiplier == 0 {
+            base + modifier
+        } else {
+            base + modifier * multiplier * 8
+        };
+        let value = value.clamp(-1023, 1023) as f32 / 1023.0;
+        let (x, y) = (i / 4, i % 4);
+        out[y * 4 + x] = (value * 127.0).round() as i8;
+    }
+    out
+}
+
+// 由 texture2ddecoder 解码一张 2D 图像，输出的像素按 BGRA 字节序排列
+fn decode_blocks(
+    format: TextureFormat,
+    data: &[u8],
+    width: usize,
+    height: usize,
+    out: &mut [u32],
+) -> Result<()> {
+    use texture2ddecoder as t2d;
+    let result = match format {
+        // 保留 1 位 alpha
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc1RgbaUnormSrgb => {
+            t2d::decode_bc1a(data, width, height, out)
+        }
+        TextureFormat::Bc2RgbaUnorm | TextureFormat::Bc2RgbaUnormSrgb => {
+            t2d::decode_bc2(data, width, height, out)
+        }
+        TextureFormat::Bc3RgbaUnorm | TextureFormat::Bc3RgbaUnormSrgb => {
+            t2d::decode_bc3(data, width, height, out)
+        }
+        TextureFormat::Bc4RUnorm => t2d::decode_bc4(data, width, height, out),
+        TextureFormat::Bc5RgUnorm => t2d::decode_bc5(data, width, height, out),
+        TextureFormat::Bc7RgbaUnorm | TextureFormat::Bc7RgbaUnormSrgb => {
+            t2d::decode_bc7(data, width, height, out)
+        }
+        TextureFormat::Etc2Rgb8Unorm | TextureFormat::Etc2Rgb8UnormSrgb => {
+            t2d::decode_etc2_rgb(data, width, height, out)
+        }
+        TextureFormat::Etc2Rgb8A1Unorm | TextureFormat::Etc2Rgb8A1UnormSrgb => {
+            t2d::decode_etc2_rgba1(data, width, height, out)
+        }
+        TextureFormat::Etc2Rgba8Unorm | TextureFormat::Etc2Rgba8UnormSrgb => {
+            t2d::decode_etc2_rgba8(data, width, height, out)
+        }
+        TextureFormat::EacR11Unorm => t2d::decode_eacr(data, width, height, out),
+        TextureFormat::EacRg11Unorm => t2d::decode_eacrg(data, width, height, out),
+        TextureFormat::Astc { channel, .. } if channel != AstcChannel::Hdr => {
+            let (block_width, block_height) = format.block_dimensions();
+            t2d::decode_astc(
+                data,
+                width,
+                height,
+                block_width as usize,
+                block_height as usize,
+                out,
+            )
+        }
+        _ => {
+            return Err(Error::UnsupportedFormat(format!(
+                "{format:?} 没有 CPU 解码器"
+            )));
+        }
+    };
+    result.map_err(|e| Error::Decode(format!("{format:?}: {e}")))
+}
+
+fn ktx2_format(format: ktx2::Format) -> Option<TextureFormat> {
+    use ktx2::Format as K;
+    let value = format.value();
+    // Vulkan 中 ASTC 的 14 种块尺寸按 UNORM / SRGB 交替排列，顺序与 AstcBlock 一致
+    if (K::ASTC_4x4_UNORM_BLOCK.value()..=K::ASTC_12x12_SRGB_BLOCK.value()).contains(&value) {
+        let index = value - K::ASTC_4x4_UNORM_BLOCK.value();
+        let channel = if index.is_multiple_of(2) {
+            AstcChannel::Unorm
+        } else {
+            AstcChannel::UnormSrgb
+        };
+        return Some(TextureFormat::Astc {
+            block: ASTC_BLOCKS[(index / 2) as usize],
+            channel,
+        });
+    }
+    if (K::ASTC_4x4_SFLOAT_BLOCK.value()..=K::ASTC_12x12_SFLOAT_BLOCK.value()).contains(&value) {
+        let index = value - K::ASTC_4x4_SFLOAT_BLOCK.value();
+        return Some(TextureFormat::Astc {
+            block: ASTC_BLOCKS[index as usize],
+            channel: AstcChannel::Hdr,
+        });
+    }
+
+    Some(match format {
+        K::R8_UNORM => TextureFormat::R8Unorm,
+        K::R8G8_UNORM => TextureFormat::Rg8Unorm,
+        K::R8G8B8A8_UNORM => TextureFormat::Rgba8Unorm,
+        K::R8G8B8A8_SRGB => TextureFormat::Rgba8UnormSrgb,
+        K::B8G8R8A8_UNORM => TextureFormat::Bgra8Unorm,
+        K::B8G8R8A8_SRGB => TextureFormat::Bgra8UnormSrgb,
+        K::R16G16B16A16_SFLOAT => TextureFormat::Rgba16Float,
+        K::R32G32B32A32_SFLOAT => TextureFormat::Rgba32Float,
+        K::BC1_RGB_UNORM_BLOCK | K::BC1_RGBA_UNORM_BLOCK => TextureFormat::Bc1RgbaUnorm,
+        K::BC1_RGB_SRGB_BLOCK | K::BC1_RGBA_SRGB_BLOCK => TextureFormat::Bc1RgbaUnormSrgb,
+        K::BC2_UNORM_BLOCK => TextureFormat::Bc2RgbaUnorm,
+        K::BC2_SRGB_BLOCK => TextureFormat::Bc2RgbaUnormSrgb,
+        K::BC3_UNORM_BLOCK => TextureFormat::Bc3RgbaUnorm,
+        K::BC3_SRGB_BLOCK => TextureFormat::Bc3RgbaUnormSrgb,
+        K::BC4_UNORM_BLOCK => TextureFormat::Bc4RUnorm,
+        K::BC4_SNORM_BLOCK => TextureFormat::Bc4RSnorm,
+        K::BC5_UNORM_BLOCK => TextureFormat::Bc5RgUnorm,
+        K::BC5_SNORM_BLOCK => TextureFormat::Bc5RgSnorm,
+        K::BC6H_UFLOAT_BLOCK => TextureFormat::Bc6hRgbUfloat,
+        K::BC6H_SFLOAT_BLOCK => TextureFormat::Bc6hRgbFloat,
+        K::BC7_UNORM_BLOCK => TextureFormat::Bc7RgbaUnorm,
+        K::BC7_SRGB_BLOCK => TextureFormat::Bc7RgbaUnormSrgb,
+        K::ETC2_R8G8B8_UNORM_BLOCK => TextureFormat::Etc2Rgb8Unorm,
+        K::ETC2_R8G8B8_SRGB_BLOCK => TextureFormat::Etc2Rgb8UnormSrgb,
+        K::ETC2_R8G8B8A1_UNORM_BLOCK => TextureFormat::Etc2Rgb8A1Unorm,
+        K::ETC2_R8G8B8A1_SRGB_BLOCK => TextureFormat::Etc2Rgb8A1UnormSrgb,
+        K::ETC2_R8G8B8A8_UNORM_BLOCK => TextureFormat::Etc2Rgba8Unorm,
+        K::ETC2_R8G8B8A8_SRGB_BLOCK => TextureFormat::Etc2Rgba8UnormSrgb,
+        K::EAC_R11_UNORM_BLOCK => TextureFormat::EacR11Unorm,
+        K::EAC_R11_SNORM_BLOCK => TextureFormat::EacR11Snorm,
+        K::EAC_R11G11_UNORM_BLOCK => TextureFormat::EacRg11Unorm,
+        K::EAC_R11G11_SNORM_BLOCK => TextureFormat::EacRg11Snorm,
+        _ => return None,
+    })
+}
+
+const ASTC_BLOCKS: [AstcBlock; 14] = [
+    AstcBlock::B4x4,
+    AstcBlock::B5x4,
+    AstcBlock::B5x5,
+    AstcBlock::B6x5,
+    AstcBlock::B6x6,
+    AstcBlock::B8x5,
+    AstcBlock::B8x6,
+    AstcBlock::B8x8,
+    AstcBlock::B10x5,
+    AstcBlock::B10x6,
+    AstcBlock::B10x8,
+    AstcBlock::B10x10,
+    AstcBlock::B12x10,
+    AstcBlock::B12x12,
+];
+
+fn dxgi_format_to_wgpu(format: ddsfile::DxgiFormat) -> Option<TextureFormat> {
+    use ddsfile::DxgiFormat as D;
+    Some(match format {
+        D::R8_UNorm => TextureFormat::R8Unorm,
+        D::R8G8_UNorm => TextureFormat::Rg8Unorm,
+        D::R8G8B8A8_UNorm => TextureFormat::Rgba8Unorm,
+        D::R8G8B8A8_UNorm_sRGB => TextureFormat::Rgba8UnormSrgb,
+        D::B8G8R8A8_UNorm => TextureFormat::Bgra8Unorm,
+        D::B8G8R8A8_UNorm_sRGB => TextureFormat::Bgra8UnormSrgb,
+        D::R16G16B16A16_Float => TextureFormat::Rgba16Float,
+        D::R32G32B32A32_Float => TextureFormat::Rgba32Float,
+        D::BC1_UNorm | D::BC1_Typeless => TextureFormat::Bc1RgbaUnorm,
+        D::BC1_UNorm_sRGB => TextureFormat::Bc1RgbaUnormSrgb,
+        D::BC2_UNorm | D::BC2_Typeless => TextureFormat::Bc2RgbaUnorm,
+        D::BC2_UNorm_sRGB => TextureFormat::Bc2RgbaUnormSrgb,
+        D::BC3_UNorm | D::BC3_Typeless => TextureFormat::Bc3RgbaUnorm,
+        D::BC3_UNorm_sRGB => TextureFormat::Bc3RgbaUnormSrgb,
+        D::BC4_UNorm | D::BC4_Typeless => TextureFormat::Bc4RUnorm,
+        D::BC4_SNorm => TextureFormat::Bc4RSnorm,
+        D::BC5_UNorm | D::BC5_Typeless => TextureFormat::Bc5RgUnorm,
+        D::BC5_SNorm => TextureFormat::Bc5RgSnorm,
+        D::BC6H_UF16 | D::BC6H_Typeless => TextureFormat::Bc6hRgbUfloat,
+        D::BC6H_SF16 => TextureFormat::Bc6hRgbFloat,
+        D::BC7_UNorm | D::BC7_Typeless => TextureFormat::Bc7RgbaUnorm,
+        D::BC7_UNorm_sRGB => TextureFormat::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn d3d_format_to_wgpu(
+    format: ddsfile::D3DFormat,
+    color_space: ColorSpace,
+) -> Option<TextureFormat> {
+    use ddsfile::D3DFormat as D;
+    let format = match format {
+        D::A8B8G8R8 => TextureFormat::Rgba8Unorm,
+        D::A8R8G8B8 => TextureFormat::Bgra8Unorm,
+        D::L8 => TextureFormat::R8Unorm,
+        D::DXT1 => TextureFormat::Bc1RgbaUnorm,
+        D::DXT3 => TextureFormat::Bc2RgbaUnorm,
+        D::DXT5 => TextureFormat::Bc3RgbaUnorm,
+        D::A16B16G16R16F => TextureFormat::Rgba16Float,
+        D::A32B32G32R32F => TextureFormat::Rgba32Float,
+        _ => return None,
+    };
+    // 旧式 DDS 格式不记录色彩空间，由调用方决定
+    Some(match color_space {
+        ColorSpace::Srgb => format.add_srgb_suffix(),
+        ColorSpace::Linear => format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_formats_map_to_wgpu() {
+        use ktx2::Format as K;
+        assert_eq!(
+            ktx2_format(K::BC1_RGB_SRGB_BLOCK),
+            Some(TextureFormat::Bc1RgbaUnormSrgb)
+        );
+        assert_eq!(
+            ktx2_format(K::BC5_SNORM_BLOCK),
+            Some(TextureFormat::Bc5RgSnorm)
+        );
+        assert_eq!(
+            ktx2_format(K::ASTC_6x5_SRGB_BLOCK),
+            Some(TextureFormat::Astc {
+                block: AstcBlock::B6x5,
+                channel: AstcChannel::UnormSrgb,
+            })
+        );
+        assert_eq!(
+            ktx2_format(K::ASTC_12x12_SFLOAT_BLOCK),
+            Some(TextureFormat::Astc {
+                block: AstcBlock::B12x12,
+                channel: AstcChannel::Hdr,
+            })
+        );
+        assert_eq!(ktx2_format(K::R64_SFLOAT), None);
+
+        use ddsfile::{D3DFormat, DxgiFormat};
+        assert_eq!(
+            dxgi_format_to_wgpu(DxgiFormat::BC7_UNorm_sRGB),
+            Some(TextureFormat::Bc7RgbaUnormSrgb)
+        );
+        assert_eq!(
+            d3d_format_to_wgpu(D3DFormat::DXT3, ColorSpace::Srgb),
+            Some(TextureFormat::Bc2RgbaUnormSrgb)
+        );
+        assert_eq!(
+            d3d_format_to_wgpu(D3DFormat::DXT1, ColorSpace::Linear),
+            Some(TextureFormat::Bc1RgbaUnorm)
+        );
+    }
+
+    #[test]
+    fn every_mapped_block_format_decodes_on_cpu() {
+        let formats = [
+            TextureFormat::Bc1RgbaUnorm,
+            TextureFormat::Bc2RgbaUnorm,
+            TextureFormat::Bc3RgbaUnorm,
+            TextureFormat::Bc4RUnorm,
+            TextureFormat::Bc4RSnorm,
+            TextureFormat::Bc5RgUnorm,
+            TextureFormat::Bc5RgSnorm,
+            TextureFormat::Bc6hRgbUfloat,
+            TextureFormat::Bc6hRgbFloat,
+            TextureFormat::Bc7RgbaUnorm,
+            TextureFormat::Etc2Rgb8Unorm,
+            TextureFormat::Etc2Rgb8A1Unorm,
+            TextureFormat::Etc2Rgba8Unorm,
+            TextureFormat::EacR11Unorm,
+            TextureFormat::EacR11Snorm,
+            TextureFormat::EacRg11Unorm,
+            TextureFormat::EacRg11Snorm,
+        ];
+        for format in formats {
+            let block_bytes = format.block_copy_size(None).unwrap() as usize;
+            // 6×5 像素，右侧与下方的块只有部分像素在图像内
+            let data = vec![0; block_bytes * 4];
+            let pixels =
+                decode_image(format, &data, 6, 5).unwrap_or_else(|e| panic!("{format:?}: {e}"));
+            let pixel_bytes = decoded_format(format).block_copy_size(None).unwrap() as usize;
+            assert_eq!(pixels.len(), 6 * 5 * pixel_bytes, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn snorm_keeps_signed_range() {
+        assert_eq!(
+            decoded_format(TextureFormat::Bc4RSnorm),
+            TextureFormat::R8Snorm
+        );
+        assert_eq!(
+            decoded_format(TextureFormat::EacRg11Snorm),
+            TextureFormat::Rg8Snorm
+        );
+        // 两个端点都是 0 的 BC4 SNORM 块解码为 0.0
+        assert_eq!(
+            decode_image(TextureFormat::Bc4RSnorm, &[0; 8], 4, 4).unwrap(),
+            [0; 16]
+        );
+
+        // 端点为 -127 与 127，索引 0 与 1 分别取两个端点
+        let mut block = [0x81, 0x7F, 0, 0, 0, 0, 0, 0];
+        block[2] = 0b0000_1000;
+        let pixels = decode_image(TextureFormat::Bc4RSnorm, &block, 4, 4).unwrap();
+        assert_eq!(pixels[0] as i8, -127);
+        assert_eq!(pixels[1] as i8, 127);
+
+        // EAC：基准值 -64，乘数为 0，修正值为 -3，即 (-512 - 3) / 1023
+        let block = [0xC0, 0x00, 0, 0, 0, 0, 0, 0];
+        let pixels = decode_image(TextureFormat::EacR11Snorm, &block, 4, 4).unwrap();
+        assert!(
+            pixels
+                .iter()
+                .all(|&p| p as i8 == (-515.0f32 / 1023.0 * 127.0).round() as i8)
+        );
+    }
+
+    #[test]
+    fn bc6h_keeps_hdr_values() {
+        assert_eq!(
+            decoded_format(TextureFormat::Bc6hRgbUfloat),
+            TextureFormat::Rgba16Float
+        );
+        // 模式 3（10 位端点，不做变换），两个端点都是最大值
+        let mut block = [0xFFu8; 16];
+        block[0] = 0b1110_0011;
+        let pixels = decode_image(TextureFormat::Bc6hRgbUfloat, &block, 4, 4).unwrap();
+        let rgba: Vec<half::f16> = bytemuck::pod_collect_to_vec::<u8, u16>(&pixels)
+            .into_iter()
+            .map(half::f16::from_bits)
+            .collect();
+        // 超过 1.0 的值不会被截断
+        assert!(rgba[0].to_f32() > 1.0, "{:?}", rgba[0]);
+        assert_eq!(rgba[3], half::f16::ONE);
+    }
+
+    #[test]
+    fn layers_are_reordered_by_mip_level() {
+        // 2 层，每层 2 级 mip，分别为 4 字节与 1 字节
+        let data = [0, 0, 0, 0, 1, 10, 10, 10, 10, 11];
+        let levels = layer_major_to_mip_major(&data, &[4, 1], 2);
+        assert_eq!(levels, [vec![0, 0, 0, 0, 10, 10, 10, 10], vec![1, 11]]);
+    }
+
+    #[test]
+    fn dds_cubemap_is_read_face_by_face_per_level() {
+        let mut dds = ddsfile::Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: ddsfile::DxgiFormat::R8G8B8A8_UNorm,
+            mipmap_levels: Some(3),
+            array_layers: Some(6),
+            caps2: Some(ddsfile::Caps2::CUBEMAP | ddsfile::Caps2::CUBEMAP_ALLFACES),
+            is_cubemap: true,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Unknown,
+        })
+        .unwrap();
+        // 每个面依次存放 4x4、2x2、1x1 三级 mip，字节值为 面 * 16 + 级
+        let level_bytes = [64, 16, 4];
+        let mut data = Vec::new();
+        for face in 0..6u8 {
+            for (level, bytes) in level_bytes.iter().enumerate() {
+                data.extend(std::iter::repeat_n(face * 16 + level as u8, *bytes));
+            }
+        }
+        dds.data = data;
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+
+        let container = TextureContainer::parse(&bytes, ColorSpace::Linear).unwrap();
+        assert_eq!(container.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(container.size.depth_or_array_layers, 6);
+        assert_eq!(container.view_dimension, wgpu::TextureViewDimension::Cube);
+        assert_eq!(container.mip_level_count(), 3);
+        for (level, bytes) in level_bytes.iter().enumerate() {
+            let expected: Vec<u8> = (0..6u8)
+                .flat_map(|face| std::iter::repeat_n(face * 16 + level as u8, *bytes))
+                .collect();
+            assert_eq!(container.levels[level], expected, "mip {level}");
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! 包围体与视锥剔除
+//!
+//! ```ignore
+//! let bounds = Aabb::from_points(mesh.vertices.iter().map(|v| v.pos.into()));
+//! let frustum = Frustum::from_view_projection(&camera.view_projection());
+//! // CPU：过滤实例列表后再写入实例缓冲区
+//! let visible = frustum.cull_instances(&instances, &bounds, |inst| inst.model_matrix());
+//! // GPU：计算着色器写入可见实例与间接绘制参数
+//! culler.cull(&queue, &mut encoder, &frustum, &bounds.bounding_sphere(), instances.len() as u32);
+//! rpass.set_vertex_buffer(1, culler.visible_instances().buffer.slice(..));
+//! rpass.draw_indexed_indirect(&culler.draw_args().buffer, 0);
+//! ```
+
+use crate::BufferObj;
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3, Vec4};
+
+/// 轴对齐包围盒
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+#[allow(dead_code)]
+impl Aabb {
+    /// 不包含任何点的空包围盒，与任意包围盒的并集为后者
+    pub const EMPTY: Self = Self {
+        min: Vec3::INFINITY,
+        max: Vec3::NEG_INFINITY,
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |aabb, p| Self {
+            min: aabb.min.min(p),
+            max: aabb.max.max(p),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+
+    /// 变换后的包围盒（Arvo 的方法），结果仍是轴对齐的，旋转时会比实际大
+    pub fn transformed(&self, m: &Mat4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let center = m.transform_point3(self.center());
+        let half = self.half_extents();
+        let extents = m.x_axis.truncate().abs() * half.x
+            + m.y_axis.truncate().abs() * half.y
+            + m.z_axis.truncate().abs() * half.z;
+        Self::from_center_half_extents(center, extents)
+    }
+
+    /// 外接球
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere {
+            center: self.center(),
+            radius: self.half_extents().length(),
+        }
+    }
+}
+
+/// 包围球
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+#[allow(dead_code)]
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Ritter 的近似算法，结果比最小包围球大 5% ~ 20%
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let Some(&first) = points.first() else {
+            return Self::new(Vec3::ZERO, 0.0);
+        };
+        // 以离任意点最远的点、以及离该点最远的点为初始直径
+        let farthest = |from: Vec3| {
+            points
+                .iter()
+                .copied()
+                .max_by(|a, b| {
+                    a.distance_squared(from)
+                        .total_cmp(&b.distance_squared(from))
+                })
+                .unwrap()
+        };
+        let a = farthest(first);
+        let b = farthest(a);
+        let mut sphere = Self::new((a + b) * 0.5, a.distance(b) * 0.5);
+        for &p in points {
+            let d = p.distance(sphere.center);
+            if d > sphere.radius {
+                let radius = (sphere.radius + d) * 0.5;
+                sphere.center += (p - sphere.center) * ((radius - sphere.radius) / d);
+                sphere.radius = radius;
+            }
+        }
+        sphere
+    }
+
+    /// 变换后的包围球，非均匀缩放时按最大的轴向缩放放大半径
+    pub fn transformed(&self, m: &Mat4) -> Self {
+        let scale = m
+            .x_axis
+            .truncate()
+            .length_squared()
+            .max(m.y_axis.truncate().length_squared())
+            .max(m.z_axis.truncate().length_squared())
+            .sqrt();
+        Self::new(m.transform_point3(self.center), self.radius * scale)
+    }
+}
+
+/// 视锥体的 6 个平面，法线朝内，`xyz · p + w >= 0` 的点在平面内侧
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// 依次为左、右、下、上、近、远
+    pub planes: [Vec4; 6],
+}
+
+#[allow(dead_code)]
+impl Frustum {
+    /// 从视图投影矩阵提取平面（Gribb-Hartmann），适用于 wgpu 的 [0, 1] 深度范围
+    ///
+    /// 同时适用于反向 Z：此时近、远平面互换；远平面在无穷远处时该平面对所有点都成立
+    pub fn from_view_projection(m: &Mat4) -> Self {
+        let [r0, r1, r2, r3] = [m.row(0), m.row(1), m.row(2), m.row(3)];
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let len = plane.truncate().length();
+            if len < 1e-6 {
+                // 无穷远处的平面
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            } else {
+                plane / len
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(p) + plane.w >= 0.0)
+    }
+
+    /// 保守测试：返回 false 时一定不可见，靠近视锥体角落的包围球可能误判为可见
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    /// 保守测试：对每个平面取包围盒上最靠内侧的角点
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let p = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(p) + plane.w >= 0.0
+        })
+    }
+
+    /// 过滤实例列表，`local_bounds` 为网格在模型空间中的包围盒，`model` 返回实例的模型矩阵
+    pub fn cull_instances<T: Copy>(
+        &self,
+        instances: &[T],
+        local_bounds: &Aabb,
+        model: impl Fn(&T) -> Mat4,
+    ) -> Vec<T> {
+        instances
+            .iter()
+            .filter(|inst| self.intersects_aabb(&local_bounds.transformed(&model(inst))))
+            .copied()
+            .collect()
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct CullParams {
+    planes: [[f32; 4]; 6],
+    sphere: [f32; 4],
+    instance_count: u32,
+    stride_words: u32,
+    _pad: [u32; 2],
+}
+
+/// 计算着色器视锥剔除
+///
+/// 把可见实例复制到 [`visible_instances`](Self::visible_instances)，
+/// 并把可见实例数写入 [`draw_args`](Self::draw_args) 中的间接绘制参数。
+/// 实例数据的开头须为列主序的模型矩阵（`[[f32; 4]; 4]`），其余字段按原样复制。
+pub struct GpuCuller {
+    pipeline: wgpu::ComputePipeline,
+    params: BufferObj,
+    visible: BufferObj,
+    draw_args: BufferObj,
+    bind_group: wgpu::BindGroup,
+    capacity: u32,
+    stride: u32,
+}
+
+#[allow(dead_code)]
+impl GpuCuller {
+    /// `instances` 须带有 `STORAGE` 用途，`instance_stride` 为每个实例的字节数，
+    /// `index_count` 为每次绘制的索引数
+    #[track_caller]
+    pub fn new(
+        device: &wgpu::Device,
+        instances: &BufferObj,
+        instance_stride: u32,
+        index_count: u32,
+    ) -> Self {
+        assert!(
+            instances
+                .buffer
+                .usage()
+                .contains(wgpu::BufferUsages::STORAGE),
+            "instance buffer must have STORAGE usage for GPU culling"
+        );
+        assert!(
+            instance_stride >= 64 && instance_stride.is_multiple_of(4),
+            "instance stride must be a multiple of 4 and hold a model matrix"
+        );
+        let capacity = (instances.size / instance_stride as u64) as u32;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("frustum cull shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader/frustum_cull.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("frustum cull pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let params = BufferObj::create_uniform_buffer(
+            device,
+            &CullParams::zeroed(),
+            Some("frustum cull params"),
+        );
+        let visible = BufferObj::create_empty_storage_buffer(
+            device,
+            capacity.max(1) as u64 * instance_stride as u64,
+            wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            Some("visible instances"),
+        );
+        let args = wgpu::util::DrawIndexedIndirectArgs {
+            index_count,
+            instance_count: 0,
+            first_index: 0,
+            base_vertex: 0,
+            first_instance: 0,
+        };
+        let draw_args =
+            BufferObj::create_indirect_buffer(device, args.as_bytes(), Some("culled draw args"));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("frustum cull bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: instances.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: visible.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: draw_args.buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            pipeline,
+            params,
+            visible,
+            draw_args,
+            bind_group,
+            capacity,
+            stride: instance_stride,
+        }
+    }
+
+    /// 记录剔除命令，`local_bounds` 为网格在模型空间中的包围球
+    pub fn cull(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        frustum: &Frustum,
+        local_bounds: &BoundingSphere,
+        instance_count: u32,
+    ) {
+        assert!(
+            instance_count <= self.capacity,
+            "instance count {instance_count} exceeds the instance buffer capacity {}",
+            self.capacity
+        );
+        let params = CullParams {
+            planes: frustum.planes.map(|plane| plane.to_array()),
+            sphere: local_bounds.center.extend(local_bounds.radius).into(),
+            instance_count,
+            stride_words: self.stride / 4,
+            _pad: [0; 2],
+        };
+        queue.write_buffer(&self.params.buffer, 0, bytemuck::bytes_of(&params));
+        // 只清零 instance_count，保留其余绘制参数
+        encoder.clear_buffer(&self.draw_args.buffer, 4, Some(4));
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("frustum cull pass"),
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &self.bind_group, &[]);
+        cpass.dispatch_workgroups(instance_count.div_ceil(64), 1, 1);
+    }
+
+    /// 可见实例，作为实例顶点缓冲区使用
+    pub fn visible_instances(&self) -> &BufferObj {
+        &self.visible
+    }
+
+    /// `DrawIndexedIndirectArgs`，用于 `draw_indexed_indirect`
+    pub fn draw_args(&self) -> &BufferObj {
+        &self.draw_args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frustum() -> Frustum {
+        let proj = Mat4::perspective_rh(60f32.to_radians(), 1.0, 0.1, 100.0);
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        Frustum::from_view_projection(&(proj * view))
+    }
+
+    #[test]
+    fn frustum_classifies_boxes_and_spheres() {
+        let frustum = frustum();
+        let unit = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::splat(0.5));
+        let at = |p: Vec3| unit.transformed(&Mat4::from_translation(p));
+
+        assert!(frustum.intersects_aabb(&at(Vec3::new(0.0, 0.0, -10.0))));
+        // 相机后方、远平面之外、左侧视野之外
+        assert!(!frustum.intersects_aabb(&at(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects_aabb(&at(Vec3::new(0.0, 0.0, -200.0))));
+        assert!(!frustum.intersects_aabb(&at(Vec3::new(-20.0, 0.0, -10.0))));
+        // 跨过左平面的包围盒可见
+        let edge = -10.0 * (30f32.to_radians()).tan();
+        assert!(frustum.intersects_aabb(&at(Vec3::new(edge, 0.0, -10.0))));
+
+        // 包围球跨过近平面时可见
+        let sphere = BoundingSphere::new(Vec3::new(0.0, 0.0, 1.0), 1.2);
+        assert!(frustum.intersects_sphere(&sphere));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, 2.0), 1.0)));
+    }
+
+    #[test]
+    fn infinite_reverse_z_has_no_far_plane() {
+        let proj = Mat4::perspective_infinite_reverse_rh(60f32.to_radians(), 1.0, 0.1);
+        let frustum = Frustum::from_view_projection(&proj);
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -1e7)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.05)));
+    }
+
+    #[test]
+    fn transformed_aabb_and_sphere_enclose_points() {
+        let points: Vec<Vec3> = (0..50)
+            .map(|i| {
+                let t = i as f32 * 0.37;
+                Vec3::new(t.sin() * 2.0, (t * 1.3).cos(), (t * 0.7).sin() * 3.0)
+            })
+            .collect();
+        let aabb = Aabb::from_points(points.iter().copied());
+        let sphere = BoundingSphere::from_points(&points);
+        let m = Mat4::from_scale_rotation_translation(
+            Vec3::new(2.0, 1.0, 0.5),
+            glam::Quat::from_rotation_y(0.8),
+            Vec3::new(5.0, -1.0, 3.0),
+        );
+        let (world_aabb, world_sphere) = (aabb.transformed(&m), sphere.transformed(&m));
+        for p in points {
+            assert!(aabb.contains_point(p));
+            assert!(p.distance(sphere.center) <= sphere.radius + 1e-4);
+            let q = m.transform_point3(p);
+            assert!(world_aabb.union(&Aabb::new(q, q)) == world_aabb);
+            assert!(q.distance(world_sphere.center) <= world_sphere.radius + 1e-4);
+        }
+        assert!(Aabb::EMPTY.is_empty());
+        assert_eq!(Aabb::EMPTY.union(&aabb), aabb);
+    }
+
+    #[test]
+    fn cull_instances_keeps_visible_ones() {
+        let frustum = frustum();
+        let bounds = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::splat(0.5));
+        let instances = [
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(1.0, 0.0, -20.0),
+        ];
+        let visible = frustum.cull_instances(&instances, &bounds, |p| Mat4::from_translation(*p));
+        assert_eq!(visible, [instances[0], instances[2]]);
+    }
+}
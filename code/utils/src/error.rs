@@ -0,0 +1,106 @@
+use core::fmt;
+
+/// utils 中加载器与节点构建器返回的错误
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// 图片或纹理容器的数据无法解码
+    Decode(String),
+    UnsupportedFormat(String),
+    Network(String),
+    /// wgpu 验证错误，例如着色器与绑定布局不匹配
+    Validation(String),
+    /// 从 GPU 回读数据失败，例如缓冲区映射失败或设备已丢失
+    Readback(String),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO 错误: {e}"),
+            Self::Decode(msg) => write!(f, "解码失败: {msg}"),
+            Self::UnsupportedFormat(msg) => write!(f, "不支持的格式: {msg}"),
+            Self::Network(msg) => write!(f, "网络错误: {msg}"),
+            Self::Validation(msg) => write!(f, "wgpu 验证错误: {msg}"),
+            Self::Readback(msg) => write!(f, "回读失败: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        match e {
+            image::ImageError::IoError(e) => Self::Io(e),
+            image::ImageError::Unsupported(e) => Self::UnsupportedFormat(e.to_string()),
+            e => Self::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<wgpu::Error> for Error {
+    fn from(e: wgpu::Error) -> Self {
+        Self::Validation(e.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Network(e.to_string())
+    }
+}
+
+/// 捕获 wgpu 验证错误的 error scope
+///
+/// ```ignore
+/// let scope = ValidationScope::push(device);
+/// let pipeline = device.create_render_pipeline(..);
+/// scope.pop()?;
+/// ```
+///
+/// # NOTE:
+/// web 端无法同步等待 error scope 的结果，`pop` 总是返回 `Ok`，捕获到的错误会在稍后输出到日志
+pub struct ValidationScope(wgpu::ErrorScopeGuard);
+
+impl ValidationScope {
+    pub fn push(device: &wgpu::Device) -> Self {
+        Self(device.push_error_scope(wgpu::ErrorFilter::Validation))
+    }
+
+    pub fn pop(self) -> Result<()> {
+        let error = self.0.pop();
+        std::cfg_select! {
+            target_arch = "wasm32" => {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Some(e) = error.await {
+                        log::error!("wgpu 验证错误: {e}");
+                    }
+                });
+                Ok(())
+            }
+            _ => {
+                match pollster::block_on(error) {
+                    Some(e) => Err(e.into()),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
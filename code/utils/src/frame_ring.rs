@@ -0,0 +1,266 @@
+use crate::BufferObj;
+use crate::resource_tracker::TrackedResource;
+use bytemuck::Pod;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 从环形缓冲区中分配出的一段数据
+#[derive(Clone, Copy, Debug)]
+pub struct RingSlice {
+    /// 可直接用作动态偏移
+    pub offset: wgpu::DynamicOffset,
+    pub size: wgpu::BufferAddress,
+}
+
+/// 逐帧上传数据的环形缓冲区
+///
+/// 一个大缓冲区被均分为 `frames_in_flight` 个帧区域，每帧的 uniform / storage 数据按对齐要求
+/// 依次分配在当前帧区域内，返回的偏移可作为 `ViewNode` / `ComputeNode` 的动态偏移使用。
+/// 帧区域只有在引用它的那次提交完成之后才会被再次使用。
+///
+/// 每帧的调用顺序：
+/// ```ignore
+/// ring.begin_frame(&device);
+/// let slice = ring.push_uniform(&queue, &camera_uniform);
+/// // ... 使用 slice.offset 编码绘制命令
+/// queue.submit(Some(encoder.finish()));
+/// ring.end_frame(&queue);
+/// ```
+///
+/// 作为动态偏移使用时，把 `ring.buffer` 放进 `BindGroupData` 的 `dynamic_uniforms` 或
+/// `storage_buffers`，再把 `slice.offset` 传给 `ViewNode::draw_rpass_by_dynamic_offsets` /
+/// `ComputeNode::dispatch_by_dynamic_offsets`。
+///
+/// # NOTE:
+/// 作为动态 uniform 使用时，`DynamicUniformBindGroup` 绑定的窗口大小为
+/// `min_uniform_buffer_offset_alignment`，单次分配的 uniform 数据不能超过这个大小；
+/// 作为动态存储缓冲区使用时，需先用 [`with_storage_window`](Self::with_storage_window) 设置绑定的窗口大小
+pub struct FrameRing {
+    pub buffer: BufferObj,
+    frame_size: wgpu::BufferAddress,
+    frames_in_flight: u32,
+    current_frame: u32,
+    // 当前帧区域内已分配的字节数
+    cursor: wgpu::BufferAddress,
+    uniform_alignment: wgpu::BufferAddress,
+    storage_alignment: wgpu::BufferAddress,
+    // 作为动态存储缓冲区绑定时的窗口大小
+    storage_window: wgpu::BufferAddress,
+    // 每个帧区域最近一次的提交是否已完成
+    frame_done: Vec<Arc<AtomicBool>>,
+}
+
+#[allow(dead_code)]
+impl FrameRing {
+    pub fn new(
+        device: &wgpu::Device,
+        frame_size: wgpu::BufferAddress,
+        frames_in_flight: u32,
+        label: Option<&'static str>,
+    ) -> Self {
+        assert!(frames_in_flight > 0);
+        let limits = device.limits();
+        let uniform_alignment = limits.min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let storage_alignment = limits.min_storage_buffer_offset_alignment as wgpu::BufferAddress;
+        // 帧区域的起始位置需同时满足两种对齐
+        let frame_size = frame_size.next_multiple_of(uniform_alignment.max(storage_alignment));
+        let size = frame_size * frames_in_flight as wgpu::BufferAddress;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            size,
+            usage: wgpu::BufferUsages::UNIFORM
+                | wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST,
+            label,
+            mapped_at_creation: false,
+        });
+        let mut buffer = BufferObj::create_by_buffer(buffer, size);
+        buffer.has_dynamic_offset = true;
+        buffer.tracked = TrackedResource::buffer(&buffer.buffer, label);
+
+        Self {
+            buffer,
+            frame_size,
+            frames_in_flight,
+            current_frame: 0,
+            cursor: 0,
+            uniform_alignment,
+            storage_alignment,
+            storage_window: 0,
+            frame_done: (0..frames_in_flight)
+                .map(|_| Arc::new(AtomicBool::new(true)))
+                .collect(),
+        }
+    }
+
+    /// 设置作为动态存储缓冲区绑定时的窗口大小，着色器在每个偏移处能访问到 `size` 字节
+    ///
+    /// 之后 `push_storage` 分配的每段数据之后都会留出完整的窗口，不会越过帧区域
+    pub fn with_storage_window(mut self, size: wgpu::BufferAddress) -> Self {
+        let size = size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        assert!(
+            size > 0 && size <= self.frame_size,
+            "storage window must fit in a frame"
+        );
+        self.storage_window = size;
+        self.buffer.min_binding_size = wgpu::BufferSize::new(size);
+        self
+    }
+
+    /// 切换到下一个帧区域
+    ///
+    /// 若该区域上一次的提交还未完成，原生平台上会阻塞等待；
+    /// web 端无法阻塞，需要设置足够的 `frames_in_flight`
+    pub fn begin_frame(&mut self, device: &wgpu::Device) {
+        self.current_frame = (self.current_frame + 1) % self.frames_in_flight;
+        self.cursor = 0;
+
+        let done = &self.frame_done[self.current_frame as usize];
+        if !done.load(Ordering::Acquire) {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let _ = device.poll(wgpu::PollType::wait_indefinitely());
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                let _ = device;
+                log::warn!("FrameRing: 帧区域 {} 仍在使用中", self.current_frame);
+            }
+        }
+    }
+
+    /// 在提交当前帧的命令之后调用，提交完成时回收当前帧区域
+    pub fn end_frame(&mut self, queue: &wgpu::Queue) {
+        let done = self.frame_done[self.current_frame as usize].clone();
+        done.store(false, Ordering::Release);
+        queue.on_submitted_work_done(move || {
+            done.store(true, Ordering::Release);
+        });
+    }
+
+    pub fn push_uniform<T: Pod>(&mut self, queue: &wgpu::Queue, data: &T) -> RingSlice {
+        // 动态 uniform 的窗口与对齐相同
+        self.push_bytes(
+            queue,
+            bytemuck::bytes_of(data),
+            self.uniform_alignment,
+            self.uniform_alignment,
+        )
+    }
+
+    pub fn push_storage<T: Pod>(&mut self, queue: &wgpu::Queue, data: &[T]) -> RingSlice {
+        self.push_bytes(
+            queue,
+            bytemuck::cast_slice(data),
+            self.storage_alignment,
+            self.storage_window,
+        )
+    }
+
+    /// 作为普通（非动态）绑定使用时的缓冲区范围
+    pub fn binding(&self, slice: RingSlice) -> wgpu::BufferBinding<'_> {
+        wgpu::BufferBinding {
+            buffer: &self.buffer.buffer,
+            offset: slice.offset as wgpu::BufferAddress,
+            size: wgpu::BufferSize::new(slice.size),
+        }
+    }
+
+    /// 当前帧区域内剩余的字节数
+    pub fn remaining(&self) -> wgpu::BufferAddress {
+        self.frame_size - self.cursor
+    }
+
+    /// `window` 是以动态偏移绑定时可见的字节数，分配的范围需能容纳它
+    fn push_bytes(
+        &mut self,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        alignment: wgpu::BufferAddress,
+        window: wgpu::BufferAddress,
+    ) -> RingSlice {
+        let start = self.cursor.next_multiple_of(alignment);
+        let size =
+            (bytes.len() as wgpu::BufferAddress).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        assert!(
+            start + size.max(window) <= self.frame_size,
+            "FrameRing overflow: frame_size {} is too small",
+            self.frame_size
+        );
+        self.cursor = start + size;
+
+        let offset = self.current_frame as wgpu::BufferAddress * self.frame_size + start;
+        if bytes.len() as wgpu::BufferAddress == size {
+            queue.write_buffer(&self.buffer.buffer, offset, bytes);
+        } else {
+            let mut padded = bytes.to_vec();
+            padded.resize(size as usize, 0);
+            queue.write_buffer(&self.buffer.buffer, offset, &padded);
+        }
+
+        RingSlice {
+            offset: offset as wgpu::DynamicOffset,
+            size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{BindGroupData, ComputeNode};
+
+    #[test]
+    fn slices_are_usable_as_node_dynamic_offsets() {
+        let Some((device, queue)) = crate::test_util::device() else {
+            return;
+        };
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(
+                r#"
+struct Params { add: vec4u }
+@group(0) @binding(0) var<storage, read> input: array<u32, 4>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+@group(1) @binding(0) var<uniform> params: Params;
+@compute @workgroup_size(1)
+fn cs_main() {
+    output[0] = input[3] + params.add.x;
+}
+"#
+                .into(),
+            ),
+        });
+        let mut ring = FrameRing::new(&device, 4096, 2, None).with_storage_window(16);
+        let output = BufferObj::create_empty_storage_buffer(
+            &device,
+            4,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            None,
+        );
+        let bg_data = BindGroupData {
+            workgroup_count: (1, 1, 1),
+            storage_buffers: vec![&ring.buffer, &output],
+            dynamic_uniforms: vec![&ring.buffer],
+            ..Default::default()
+        };
+        let node =
+            ComputeNode::new_with_dynamic_uniforms(&device, &bg_data, &shader_module).unwrap();
+
+        ring.begin_frame(&device);
+        ring.push_storage(&queue, &[1u32, 2, 3, 4]);
+        let input = ring.push_storage(&queue, &[10u32, 20, 30, 40]);
+        let params = ring.push_uniform(&queue, &[5u32, 0, 0, 0]);
+        assert_ne!(input.offset, 0);
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut cpass = encoder.begin_compute_pass(&Default::default());
+            node.dispatch_by_dynamic_offsets(&mut cpass, &[input.offset], &[params.offset]);
+        }
+        queue.submit(Some(encoder.finish()));
+        ring.end_frame(&queue);
+
+        let result = pollster::block_on(output.read::<u32>(&device, &queue)).unwrap();
+        assert_eq!(result, [45]);
+    }
+}
@@ -9,6 +9,7 @@ use winit::{
         WindowEvent,
     },
     event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
@@ -188,6 +189,16 @@ impl<A: WgpuAppAction + 'static> ApplicationHandler for WgpuAppHandler<A> {
         // 暂停事件
     }
 
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        crate::resource_tracker::log_memory_report();
+        // 先销毁 app，此时仍在登记表中的资源即为泄漏
+        self.app.lock().take();
+        let leaks = crate::resource_tracker::report_leaks();
+        if leaks > 0 {
+            log::warn!("退出时仍有 {leaks} 个 GPU 资源未释放");
+        }
+    }
+
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -225,6 +236,12 @@ impl<A: WgpuAppAction + 'static> ApplicationHandler for WgpuAppHandler<A> {
                 }
             }
             WindowEvent::KeyboardInput { event, .. } => {
+                // 按 F9 输出 GPU 资源的显存报告
+                if event.state == ElementState::Pressed
+                    && event.physical_key == PhysicalKey::Code(KeyCode::F9)
+                {
+                    crate::resource_tracker::log_memory_report();
+                }
                 // 键盘事件
                 let _ = app.keyboard_input(&event);
             }
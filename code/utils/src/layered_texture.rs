@@ -0,0 +1,348 @@
+//! 由多张图片组成的纹理：立方体贴图、2D 数组纹理与 3D 体积纹理
+//!
+//! 所有图片需尺寸相同，且解码后得到相同的纹理格式（例如不能混用 8 位与浮点图片）。
+//! 立方体贴图的面按 wgpu 的层序排列：+X, -X, +Y, -Y, +Z, -Z。
+
+use crate::load_texture::{AnyTexture, TextureLoadOptions};
+use crate::mipmap::{self, MipFilter};
+use crate::{Error, Result, ValidationScope};
+use image::{DynamicImage, GenericImageView};
+use wgpu::{Extent3d, TextureFormat, TextureViewDimension};
+
+/// 单张图片中立方体六个面的排列方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeLayout {
+    /// 4x3 的横向十字：
+    /// ```text
+    ///      +Y
+    /// -X   +Z   +X   -Z
+    ///      -Y
+    /// ```
+    HorizontalCross,
+    /// 1x6 的竖条，从上到下依次为 +X, -X, +Y, -Y, +Z, -Z
+    VerticalStrip,
+}
+
+impl CubeLayout {
+    /// 以面边长为单位的 (列数, 行数)
+    fn grid(self) -> (u32, u32) {
+        match self {
+            Self::HorizontalCross => (4, 3),
+            Self::VerticalStrip => (1, 6),
+        }
+    }
+
+    /// 每个面在网格中的 (列, 行)
+    fn face_cells(self) -> [(u32, u32); 6] {
+        match self {
+            Self::HorizontalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)],
+            Self::VerticalStrip => [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)],
+        }
+    }
+}
+
+/// 加载多张图片，通常与本模块的其它函数配合使用
+pub async fn load_images(paths: &[&str]) -> Result<Vec<DynamicImage>> {
+    let mut images = Vec::with_capacity(paths.len());
+    for path in paths {
+        let bytes = crate::assets::load(path).await?;
+        images.push(crate::load_texture::decode_image(&bytes, path)?);
+    }
+    Ok(images)
+}
+
+/// 由六张面图片创建立方体贴图
+#[track_caller]
+pub fn cube_from_faces(
+    app: &app_surface::AppSurface,
+    faces: [DynamicImage; 6],
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    from_layers(
+        app,
+        faces.into(),
+        TextureViewDimension::Cube,
+        usage,
+        options,
+        label,
+    )
+}
+
+/// 从横向十字或竖条排列的单张图片中切出六个面，创建立方体贴图
+#[track_caller]
+pub fn cube_from_image(
+    app: &app_surface::AppSurface,
+    img: DynamicImage,
+    layout: CubeLayout,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    let (width, height) = img.dimensions();
+    let (columns, rows) = layout.grid();
+    let face_size = width / columns;
+    if face_size == 0 || width != face_size * columns || height != face_size * rows {
+        return Err(Error::Decode(format!(
+            "{width}x{height} 的图片不符合 {layout:?} 的 {columns}:{rows} 排列"
+        )));
+    }
+    let faces = layout
+        .face_cells()
+        .iter()
+        .map(|&(column, row)| {
+            img.crop_imm(column * face_size, row * face_size, face_size, face_size)
+        })
+        .collect();
+    from_layers(
+        app,
+        faces,
+        TextureViewDimension::Cube,
+        usage,
+        options,
+        label,
+    )
+}
+
+/// 由一组图片创建 2D 数组纹理，第 i 张图片为第 i 层
+#[track_caller]
+pub fn array_from_images(
+    app: &app_surface::AppSurface,
+    images: Vec<DynamicImage>,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    from_layers(
+        app,
+        images,
+        TextureViewDimension::D2Array,
+        usage,
+        options,
+        label,
+    )
+}
+
+/// 由一组切片图片创建 3D 纹理，第 i 张图片为 z = i 的切片
+///
+/// 3D 纹理不生成 mip，`options.mipmaps` 会被忽略
+#[track_caller]
+pub fn volume_from_slices(
+    app: &app_surface::AppSurface,
+    slices: Vec<DynamicImage>,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    from_layers(app, slices, TextureViewDimension::D3, usage, options, label)
+}
+
+/// 由没有文件头的原始体素数据创建 3D 纹理
+///
+/// `data` 按 x、y、z 的顺序紧凑排列，长度须为 `width * height * depth * 像素字节数`
+#[track_caller]
+pub fn volume_from_raw(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[u8],
+    size: Extent3d,
+    format: TextureFormat,
+    usage: wgpu::TextureUsages,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    let pixel_bytes = format
+        .block_copy_size(None)
+        .filter(|_| !format.is_compressed())
+        .ok_or_else(|| Error::UnsupportedFormat(format!("{format:?} 不能用于原始体素数据")))?;
+    let expected = pixel_bytes as u64
+        * size.width as u64
+        * size.height as u64
+        * size.depth_or_array_layers as u64;
+    if data.len() as u64 != expected {
+        return Err(Error::Decode(format!(
+            "体素数据长度 {} 与 {}x{}x{} {format:?} 所需的 {expected} 字节不一致",
+            data.len(),
+            size.width,
+            size.height,
+            size.depth_or_array_layers
+        )));
+    }
+    upload(
+        device,
+        queue,
+        data,
+        size,
+        format,
+        pixel_bytes,
+        TextureViewDimension::D3,
+        usage,
+        None,
+        label,
+    )
+}
+
+/// 从 [`assets`](crate::assets) 加载原始体素文件，创建 3D 纹理
+pub async fn volume_from_raw_path(
+    app: &app_surface::AppSurface,
+    path: &str,
+    size: Extent3d,
+    format: TextureFormat,
+    usage: wgpu::TextureUsages,
+) -> Result<AnyTexture> {
+    let bytes = crate::assets::load(path).await?;
+    volume_from_raw(
+        &app.device,
+        &app.queue,
+        &bytes,
+        size,
+        format,
+        usage,
+        Some(path),
+    )
+}
+
+#[track_caller]
+fn from_layers(
+    app: &app_surface::AppSurface,
+    images: Vec<DynamicImage>,
+    view_dimension: TextureViewDimension,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    let Some(first) = images.first() else {
+        return Err(Error::Decode("至少需要一张图片".into()));
+    };
+    let (width, height) = first.dimensions();
+    if view_dimension == TextureViewDimension::Cube && (images.len() != 6 || width != height) {
+        return Err(Error::Decode(format!(
+            "立方体贴图需要 6 张正方形图片，实际为 {} 张 {width}x{height}",
+            images.len()
+        )));
+    }
+
+    let layer_count = images.len() as u32;
+    let mut texels = vec![];
+    let mut format = None;
+    for (index, img) in images.into_iter().enumerate() {
+        if img.dimensions() != (width, height) {
+            return Err(Error::Decode(format!(
+                "第 {index} 张图片的尺寸 {:?} 与第一张的 {width}x{height} 不一致",
+                img.dimensions()
+            )));
+        }
+        let (layer, _, layer_format) =
+            crate::load_texture::load_from_img(img, &options, app.device.features())?;
+        let previous = *format.get_or_insert(layer_format);
+        if previous != layer_format {
+            return Err(Error::UnsupportedFormat(format!(
+                "第 {index} 张图片解码为 {layer_format:?}，与之前的 {previous:?} 不一致"
+            )));
+        }
+        texels.extend_from_slice(&layer);
+    }
+    let mut format = format.unwrap();
+    if options.mipmaps == Some(MipFilter::NormalMap) {
+        format = format.remove_srgb_suffix();
+    }
+    let mipmaps = if view_dimension == TextureViewDimension::D3 {
+        if options.mipmaps.is_some() {
+            log::warn!("3D 纹理不生成 mip");
+        }
+        None
+    } else {
+        options.mipmaps
+    };
+
+    upload(
+        &app.device,
+        &app.queue,
+        &texels,
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: layer_count,
+        },
+        format,
+        texels.len() as u32 / (width * height * layer_count),
+        view_dimension,
+        usage,
+        mipmaps,
+        label,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+#[track_caller]
+fn upload(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texels: &[u8],
+    size: Extent3d,
+    format: TextureFormat,
+    pixel_bytes: u32,
+    view_dimension: TextureViewDimension,
+    usage: wgpu::TextureUsages,
+    mipmaps: Option<MipFilter>,
+    label: Option<&str>,
+) -> Result<AnyTexture> {
+    let mip_usage = mipmaps.and_then(|_| mipmap::required_usage(device, format));
+    if mipmaps.is_some() && mip_usage.is_none() {
+        log::warn!("格式 {format:?} 不支持生成 mip: {label:?}");
+    }
+    let mip_level_count = if mip_usage.is_some() {
+        mipmap::mip_level_count(size.width, size.height)
+    } else {
+        1
+    };
+    let dimension = if view_dimension == TextureViewDimension::D3 {
+        wgpu::TextureDimension::D3
+    } else {
+        wgpu::TextureDimension::D2
+    };
+
+    let scope = ValidationScope::push(device);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label,
+        size,
+        mip_level_count,
+        sample_count: 1,
+        dimension,
+        format,
+        usage: usage
+            | wgpu::TextureUsages::COPY_DST
+            | mip_usage.unwrap_or(wgpu::TextureUsages::empty()),
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        texels,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(pixel_bytes * size.width),
+            rows_per_image: Some(size.height),
+        },
+        size,
+    );
+    if let Some(filter) = mipmaps.filter(|_| mip_level_count > 1) {
+        mipmap::generate_mipmaps(device, queue, &texture, filter);
+    }
+    let tex_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(view_dimension),
+        ..Default::default()
+    });
+    scope.pop()?;
+
+    Ok(AnyTexture::from_parts(
+        texture,
+        tex_view,
+        view_dimension,
+        label,
+    ))
+}
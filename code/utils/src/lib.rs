@@ -1,3 +1,11 @@
+// 让 `#[derive(Vertex)]` 生成的 `::utils::...` 路径在本 crate 内也能解析
+extern crate self as utils;
+
+mod error;
+pub use error::{Error, Result, ValidationScope};
+
+pub mod assets;
+
 pub mod framework;
 pub use framework::{WgpuAppAction, run};
 
@@ -5,19 +13,54 @@ pub mod load_texture;
 pub use load_texture::{
     AnyTexture, bilinear_sampler, default_sampler, mirror_repeate_sampler, repeate_sampler,
 };
+pub mod compressed_texture;
+
+mod sampler;
+pub use sampler::{SamplerCache, SamplerDesc};
+pub mod layered_texture;
+
+mod atlas;
+pub use atlas::{Atlas, AtlasBuilder, AtlasRegion, SkylinePacker};
+
+pub mod mipmap;
 pub mod node;
+pub mod upload_queue;
 
 mod plane;
 pub use plane::Plane;
+pub mod mesh;
+pub mod shapes;
+
+pub mod camera;
+pub mod culling;
+pub mod picking;
 
 mod buffer;
 pub use buffer::BufferObj;
 
+mod typed_buffer;
+pub use typed_buffer::TypedBuffer;
+
+mod frame_ring;
+pub use frame_ring::{FrameRing, RingSlice};
+
+mod mesh_arena;
+pub use mesh_arena::{MeshAllocation, MeshArena, RangeAllocator};
+
+mod readback;
+
+#[cfg(test)]
+mod test_util;
+
+pub mod resource_tracker;
+
 pub mod matrix_helper;
 pub mod vertex;
 
-mod color;
-pub use color::*;
+pub mod color;
+pub use color::{
+    pack_rgba8_to_u32, unpack_u32_to_color, unpack_u32_to_rgba_f32, unpack_u32_to_rgba8,
+};
 
 pub fn get_current_surface_texture(
     surface: &wgpu::Surface<'_>,
@@ -51,57 +94,6 @@ pub struct SceneUniform {
     pub padding: [f32; 2],
 }
 
-#[cfg(target_arch = "wasm32")]
-pub(crate) fn application_root_dir() -> String {
-    let location = web_sys::window().unwrap().location();
-    let host = location.host().unwrap();
-    let href = location.href().unwrap();
-    if host.contains("localhost") || host.contains("127.0.0.1") {
-        String::from("http://")
-            + &host
-            + if href.contains("learn-wgpu-zh") {
-                "/learn-wgpu-zh/"
-            } else {
-                "/"
-            }
-    } else if host.contains("jinleili.github.io") {
-        String::from("https://jinleili.github.io/learn-wgpu-zh/")
-    } else {
-        String::from("https://cannot.access/")
-    }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn application_root_dir() -> String {
-    use std::env;
-    use std::fs;
-
-    match env::var("PROFILE") {
-        Ok(_) => String::from(env!("CARGO_MANIFEST_DIR")),
-        Err(_) => {
-            let mut path = env::current_exe().expect("Failed to find executable path.");
-            while let Ok(target) = fs::read_link(path.clone()) {
-                path = target;
-            }
-            if cfg!(any(
-                target_os = "macos",
-                target_os = "windows",
-                target_os = "linux"
-            )) {
-                path = path.join("../../../assets/").canonicalize().unwrap();
-            }
-
-            String::from(path.to_str().unwrap())
-        }
-    }
-}
-
-use std::path::PathBuf;
-#[allow(unused)]
-pub(crate) fn get_texture_file_path(name: &str) -> PathBuf {
-    PathBuf::from(application_root_dir()).join(name)
-}
-
 // 根据不同平台初始化日志。
 pub fn init_logger() {
     std::cfg_select! {
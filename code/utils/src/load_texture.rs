@@ -1,24 +1,170 @@
-#[cfg(not(target_arch = "wasm32"))]
-use std::path::PathBuf;
-
+use crate::compressed_texture::{self, TextureContainer};
+use crate::mipmap::{self, MipFilter};
+use crate::resource_tracker::TrackedResource;
+use crate::sampler::SamplerDesc;
+use crate::{Error, Result, ValidationScope};
 use image::{DynamicImage, GenericImageView};
 use wgpu::{Extent3d, Sampler, Texture, TextureFormat, TextureView};
 
+#[allow(dead_code)]
 pub struct AnyTexture {
     pub size: Extent3d,
     pub tex: Texture,
     pub tex_view: TextureView,
     pub format: TextureFormat,
     pub view_dimension: wgpu::TextureViewDimension,
+    // 在资源登记表中的登记，随 AnyTexture 一起释放
+    tracked: TrackedResource,
+}
+
+impl AnyTexture {
+    /// 由手动创建的纹理与视图构造，纹理会登记到资源登记表中
+    #[track_caller]
+    pub fn from_parts(
+        tex: Texture,
+        tex_view: TextureView,
+        view_dimension: wgpu::TextureViewDimension,
+        label: Option<&str>,
+    ) -> Self {
+        Self {
+            size: tex.size(),
+            format: tex.format(),
+            tracked: TrackedResource::texture(&tex, label),
+            tex,
+            tex_view,
+            view_dimension,
+        }
+    }
+
+    /// 回读第 0 级 mip 的所有像素，返回去除了行对齐填充的紧凑数据
+    ///
+    /// 纹理需带有 `COPY_SRC` 用途，且不能是压缩格式
+    pub async fn read_pixels(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<u8>> {
+        if !self.tex.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(Error::Validation("回读的纹理需带有 COPY_SRC 用途".into()));
+        }
+        let pixel_bytes = self
+            .format
+            .block_copy_size(None)
+            .filter(|_| !self.format.is_compressed())
+            .ok_or_else(|| Error::UnsupportedFormat(format!("无法回读 {:?} 纹理", self.format)))?;
+        let unpadded_bytes_per_row = pixel_bytes * self.size.width;
+        let padded_bytes_per_row = crate::readback::padded_bytes_per_row(unpadded_bytes_per_row);
+        let rows = self.size.height * self.size.depth_or_array_layers;
+
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("texture readback staging buffer"),
+            size: (padded_bytes_per_row * rows) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("texture readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.tex,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &staging,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(self.size.height),
+                },
+            },
+            self.size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let padded = crate::readback::map_staging_buffer(device, &staging).await?;
+        Ok(crate::readback::remove_row_padding(
+            &padded,
+            padded_bytes_per_row,
+            unpadded_bytes_per_row,
+        ))
+    }
+
+    /// 将纹理保存为 PNG 图片
+    ///
+    /// 支持 Rgba8、Bgra8 与 R8Unorm 格式，数组纹理只保存第一层
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn save_png<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<()> {
+        let mut pixels = self.read_pixels(device, queue).await?;
+        let (width, height) = (self.size.width, self.size.height);
+        let layer_bytes = (width * height) as usize
+            * if self.format == TextureFormat::R8Unorm {
+                1
+            } else {
+                4
+            };
+        pixels.truncate(layer_bytes);
+
+        let color_type = match self.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                image::ExtendedColorType::Rgba8
+            }
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                for pixel in pixels.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+                image::ExtendedColorType::Rgba8
+            }
+            TextureFormat::R8Unorm => image::ExtendedColorType::L8,
+            _ => {
+                return Err(Error::UnsupportedFormat(format!(
+                    "无法将 {:?} 纹理保存为 PNG",
+                    self.format
+                )));
+            }
+        };
+        image::save_buffer_with_format(
+            path,
+            &pixels,
+            width,
+            height,
+            color_type,
+            image::ImageFormat::Png,
+        )?;
+        Ok(())
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
-pub async fn get_web_img(img_name: &str) -> Result<Vec<u8>, reqwest::Error> {
-    let url =
-        reqwest::Url::parse(&format!("{}{}", super::application_root_dir(), img_name,)).unwrap();
-    let data = reqwest::get(url).await?.bytes().await?.to_vec();
+pub async fn get_web_img(img_name: &str) -> Result<Vec<u8>> {
+    crate::assets::load(img_name).await
+}
+
+/// 8 位颜色纹理的色彩空间
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// 颜色贴图，使用 `*Srgb` 格式，采样时由硬件转换到线性空间
+    #[default]
+    Srgb,
+    /// 法线、粗糙度等数据贴图，以及需要在着色器中读取原始颜色值的图片，按原值采样
+    Linear,
+}
 
-    Ok(data)
+/// 图片纹理的加载选项
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextureLoadOptions {
+    pub set_to_grayscale: bool,
+    /// 只影响 8 位颜色图片，16 位与浮点图片没有 sRGB 格式，总是按线性数据上传
+    pub color_space: ColorSpace,
+    /// 上传前将颜色预乘 alpha
+    pub premultiply_alpha: bool,
+    /// 在 GPU 上生成完整的 mip 链，`None` 时只有一级 mip
+    ///
+    /// 使用 `MipFilter::NormalMap` 时纹理会以线性格式创建
+    pub mipmaps: Option<MipFilter>,
 }
 
 #[allow(dead_code)]
@@ -27,49 +173,92 @@ pub async fn from_path(
     app: &app_surface::AppSurface,
     usage: wgpu::TextureUsages,
     set_to_grayscale: bool,
-) -> (AnyTexture, Sampler) {
-    #[cfg(target_arch = "wasm32")]
-    let img = {
-        let bytes = get_web_img(image_path).await;
-        let format = if image_path.to_lowercase().ends_with(".png") {
-            image::ImageFormat::Png
-        } else if image_path.to_lowercase().ends_with(".jpg")
-            || image_path.to_lowercase().ends_with(".jpeg")
-        {
-            image::ImageFormat::Jpeg
-        } else {
-            panic!("不支持的图片格式，仅支持 PNG 和 JPEG/JPG")
-        };
-        image::load_from_memory_with_format(&bytes.unwrap(), format).unwrap()
-    };
-    #[cfg(not(target_arch = "wasm32"))]
-    let img = {
-        let path = if image_path.split('/').count() > 1 {
-            // is already a full path
-            PathBuf::from(image_path)
+) -> Result<(AnyTexture, Sampler)> {
+    from_path_with_options(
+        image_path,
+        app,
+        usage,
+        TextureLoadOptions {
+            set_to_grayscale,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// 生成了 mip 链时返回的采样器会在 mip 级之间做线性插值
+#[allow(dead_code)]
+pub async fn from_path_with_options(
+    image_path: &str,
+    app: &app_surface::AppSurface,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+) -> Result<(AnyTexture, Sampler)> {
+    let bytes = crate::assets::load(image_path).await?;
+
+    from_bytes(&bytes, image_path, app, usage, options)
+}
+
+/// 从内存中的图片文件数据创建纹理，图片格式由文件头识别
+///
+/// KTX2 / DDS 容器由 [`TextureContainer`] 加载；
+/// `name` 用于资源登记，以及识别没有文件头标识的 TGA 图片
+#[allow(dead_code)]
+pub fn from_bytes(
+    bytes: &[u8],
+    name: &str,
+    app: &app_surface::AppSurface,
+    usage: wgpu::TextureUsages,
+    options: TextureLoadOptions,
+) -> Result<(AnyTexture, Sampler)> {
+    if compressed_texture::is_container(bytes) {
+        // 容器中自带 mip 链，不再生成
+        let any_tex = TextureContainer::parse(bytes, options.color_space)?.upload(
+            &app.device,
+            &app.queue,
+            usage,
+            Some(name),
+        )?;
+        let sampler = if any_tex.tex.mip_level_count() > 1 {
+            bilinear_sampler(&app.device)
         } else {
-            super::get_texture_file_path(image_path)
+            default_sampler(&app.device)
         };
-        log::info!("path: {:?}", path);
-        image::open(path.as_path()).unwrap()
-    };
+        return Ok((any_tex, sampler));
+    }
 
-    let (texels, texture_extent, format) = load_from_img(img, set_to_grayscale);
+    let img = decode_image(bytes, name)?;
+    let (texels, texture_extent, mut format) = load_from_img(img, &options, app.device.features())?;
+    if options.mipmaps == Some(MipFilter::NormalMap) {
+        format = format.remove_srgb_suffix();
+    }
     let pixel_bytes = single_pixel_bytes(format);
+    let mip_usage = options
+        .mipmaps
+        .and_then(|_| mipmap::required_usage(&app.device, format));
+    if options.mipmaps.is_some() && mip_usage.is_none() {
+        log::warn!("格式 {format:?} 不支持生成 mip: {name}");
+    }
+    let mip_level_count = if mip_usage.is_some() {
+        mipmap::mip_level_count(texture_extent.width, texture_extent.height)
+    } else {
+        1
+    };
+    let scope = ValidationScope::push(&app.device);
     let texture = app.device.create_texture(&wgpu::TextureDescriptor {
         size: texture_extent,
-        mip_level_count: 1,
+        mip_level_count,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
         format,
-        usage: usage | wgpu::TextureUsages::COPY_DST,
+        usage: usage
+            | wgpu::TextureUsages::COPY_DST
+            | mip_usage.unwrap_or(wgpu::TextureUsages::empty()),
         label: None,
         view_formats: &[format.remove_srgb_suffix()],
     });
-    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
-        format: Some(format.remove_srgb_suffix()),
-        ..Default::default()
-    });
+    // 视图使用纹理自身的格式，与其它加载器一致：`ColorSpace::Srgb` 的纹理采样时会转换到线性空间
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
     app.queue.write_texture(
         wgpu::TexelCopyTextureInfo {
             texture: &texture,
@@ -85,45 +274,129 @@ pub async fn from_path(
         },
         texture_extent,
     );
-    let any_tex = AnyTexture {
-        size: texture_extent,
-        tex: texture,
-        tex_view: texture_view,
-        view_dimension: wgpu::TextureViewDimension::D2,
-        format,
+    let sampler = match options.mipmaps {
+        Some(filter) if mip_level_count > 1 => {
+            mipmap::generate_mipmaps(&app.device, &app.queue, &texture, filter);
+            bilinear_sampler(&app.device)
+        }
+        _ => default_sampler(&app.device),
     };
+    scope.pop()?;
+    let any_tex = AnyTexture::from_parts(
+        texture,
+        texture_view,
+        wgpu::TextureViewDimension::D2,
+        Some(name),
+    );
+
+    Ok((any_tex, sampler))
+}
 
-    (any_tex, default_sampler(&app.device))
+/// 通过文件头识别图片格式后解码
+///
+/// TGA 没有文件头标识，只能根据扩展名识别
+pub(crate) fn decode_image(bytes: &[u8], name: &str) -> Result<DynamicImage> {
+    let format = image::guess_format(bytes)
+        .or_else(|_| image::ImageFormat::from_path(name))
+        .map_err(|_| Error::UnsupportedFormat(format!("无法识别的图片格式: {name}")))?;
+    Ok(image::load_from_memory_with_format(bytes, format)?)
 }
 
-fn load_from_img(
+pub(crate) fn load_from_img(
     img: DynamicImage,
-    set_to_grayscale: bool,
-) -> (Vec<u8>, wgpu::Extent3d, TextureFormat) {
+    options: &TextureLoadOptions,
+    device_features: wgpu::Features,
+) -> Result<(Vec<u8>, wgpu::Extent3d, TextureFormat)> {
     let (width, height) = img.dimensions();
     let texture_extent = wgpu::Extent3d {
         width,
         height,
         depth_or_array_layers: 1,
     };
+    let support_16bit_norm = device_features.contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM);
 
-    let (format, texels) = if set_to_grayscale {
+    let (format, texels) = if options.set_to_grayscale {
         // webgpu spec: R8 | R16 is not supported for storage use.
         // (TextureFormat::R8Unorm, DynamicImage::ImageLuma16(img.into_luma8()).into_bytes())
         (TextureFormat::R8Unorm, img.into_luma8().into_raw())
     } else {
         match img.color() {
             image::ColorType::L8 => (TextureFormat::R8Unorm, img.into_bytes()),
-            image::ColorType::Rgb8 | image::ColorType::Rgba8 => {
-                (TextureFormat::Rgba8UnormSrgb, img.into_rgba8().into_raw())
+            image::ColorType::L16 if support_16bit_norm => (
+                TextureFormat::R16Unorm,
+                bytemuck::cast_slice(&img.into_luma16().into_raw()).to_vec(),
+            ),
+            image::ColorType::La16 | image::ColorType::Rgb16 | image::ColorType::Rgba16
+                if support_16bit_norm =>
+            {
+                let mut pixels = img.into_rgba16().into_raw();
+                if options.premultiply_alpha {
+                    for pixel in pixels.chunks_exact_mut(4) {
+                        let alpha = pixel[3] as u32;
+                        for c in pixel[..3].iter_mut() {
+                            *c = (*c as u32 * alpha / 65535) as u16;
+                        }
+                    }
+                }
+                (
+                    TextureFormat::Rgba16Unorm,
+                    bytemuck::cast_slice(&pixels).to_vec(),
+                )
+            }
+            image::ColorType::Rgb32F | image::ColorType::Rgba32F => {
+                let mut pixels = img.into_rgba32f().into_raw();
+                if options.premultiply_alpha {
+                    for pixel in pixels.chunks_exact_mut(4) {
+                        let alpha = pixel[3];
+                        for c in pixel[..3].iter_mut() {
+                            *c *= alpha;
+                        }
+                    }
+                }
+                if device_features.contains(wgpu::Features::FLOAT32_FILTERABLE) {
+                    (
+                        TextureFormat::Rgba32Float,
+                        bytemuck::cast_slice(&pixels).to_vec(),
+                    )
+                } else {
+                    // Rgba32Float 默认不可过滤，转换为半精度
+                    let halfs: Vec<u16> = pixels
+                        .iter()
+                        .map(|v| half::f16::from_f32(*v).to_bits())
+                        .collect();
+                    (
+                        TextureFormat::Rgba16Float,
+                        bytemuck::cast_slice(&halfs).to_vec(),
+                    )
+                }
+            }
+            color => {
+                if color.bytes_per_pixel() > color.channel_count() {
+                    log::warn!("{color:?} 图片被转换为 8 位，因为设备不支持 16 位 norm 格式");
+                }
+                // La8 等其余格式统一转换为 Rgba8
+                let mut pixels = img.into_rgba8().into_raw();
+                if options.premultiply_alpha {
+                    for pixel in pixels.chunks_exact_mut(4) {
+                        let alpha = pixel[3] as u32;
+                        for c in pixel[..3].iter_mut() {
+                            *c = ((*c as u32 * alpha + 127) / 255) as u8;
+                        }
+                    }
+                }
+                let format = match options.color_space {
+                    ColorSpace::Srgb => TextureFormat::Rgba8UnormSrgb,
+                    ColorSpace::Linear => TextureFormat::Rgba8Unorm,
+                };
+                (format, pixels)
             }
-            _ => panic!("unsupported color type"),
         }
     };
 
-    (texels, texture_extent, format)
+    Ok((texels, texture_extent, format))
 }
 
+#[track_caller]
 pub fn empty(
     device: &wgpu::Device,
     format: TextureFormat,
@@ -161,89 +434,55 @@ pub fn empty(
         ..Default::default()
     });
 
-    AnyTexture {
-        size: extent,
-        tex: texture,
-        tex_view: texture_view,
-        view_dimension,
-        format,
-    }
+    AnyTexture::from_parts(texture, texture_view, view_dimension, label)
 }
 
 #[allow(dead_code)]
 pub fn default_sampler(device: &wgpu::Device) -> Sampler {
-    device.create_sampler(&wgpu::SamplerDescriptor {
-        label: None,
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Nearest,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
-        ..Default::default()
-    })
+    create_sampler(device, SamplerDesc::nearest())
 }
 
 #[allow(dead_code)]
 pub fn repeate_sampler(device: &wgpu::Device) -> Sampler {
-    device.create_sampler(&wgpu::SamplerDescriptor {
-        label: None,
-        address_mode_u: wgpu::AddressMode::Repeat,
-        address_mode_v: wgpu::AddressMode::Repeat,
-        address_mode_w: wgpu::AddressMode::Repeat,
-        mag_filter: wgpu::FilterMode::Nearest,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
-        ..Default::default()
-    })
+    create_sampler(
+        device,
+        SamplerDesc::nearest().address_mode(wgpu::AddressMode::Repeat),
+    )
 }
 
 #[allow(dead_code)]
 pub fn mirror_repeate_sampler(device: &wgpu::Device) -> Sampler {
-    device.create_sampler(&wgpu::SamplerDescriptor {
-        label: None,
-        address_mode_u: wgpu::AddressMode::MirrorRepeat,
-        address_mode_v: wgpu::AddressMode::MirrorRepeat,
-        address_mode_w: wgpu::AddressMode::MirrorRepeat,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
-        ..Default::default()
-    })
+    create_sampler(
+        device,
+        SamplerDesc::nearest()
+            .address_mode(wgpu::AddressMode::MirrorRepeat)
+            .filter(wgpu::FilterMode::Linear),
+    )
 }
 
 // 瓦片式平铺采样
 #[allow(dead_code)]
 pub fn tile_sampler(device: &wgpu::Device) -> Sampler {
-    device.create_sampler(&wgpu::SamplerDescriptor {
-        label: None,
-        address_mode_u: wgpu::AddressMode::Repeat,
-        address_mode_v: wgpu::AddressMode::Repeat,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Nearest,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::MipmapFilterMode::Nearest,
-        ..Default::default()
-    })
+    create_sampler(
+        device,
+        SamplerDesc::nearest().address_mode_uvw(
+            wgpu::AddressMode::Repeat,
+            wgpu::AddressMode::Repeat,
+            wgpu::AddressMode::ClampToEdge,
+        ),
+    )
 }
 
 // 双线性插值
 // https://vulkan-tutorial.com/Texture_mapping/Image_view_and_sampler
 #[allow(dead_code)]
 pub fn bilinear_sampler(device: &wgpu::Device) -> Sampler {
-    device.create_sampler(&wgpu::SamplerDescriptor {
-        label: None,
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::MipmapFilterMode::Linear,
-        // iOS 上设置了 compare 值会 crash
-        // compare: Some(wgpu::CompareFunction::LessEqual),
-        // compare: wgpu::CompareFunction::Undefined,
-        ..Default::default()
-    })
+    create_sampler(device, SamplerDesc::linear())
+}
+
+// 以上预设都是合法的组合，无需验证
+fn create_sampler(device: &wgpu::Device, desc: SamplerDesc) -> Sampler {
+    device.create_sampler(&desc.to_descriptor(None))
 }
 
 fn single_pixel_bytes(format: TextureFormat) -> u32 {
@@ -0,0 +1,619 @@
+//! CPU 上的网格处理：切线、法线、顶点焊接与索引顺序优化
+//!
+//! 函数只接收属性数组与 triangle list 索引，不依赖具体的顶点类型，
+//! 各教程中自定义的 `ModelVertex` 也可以直接使用。
+
+use glam::Vec3;
+use std::collections::HashMap;
+
+/// 生成与 MikkTSpace 一致的切线，w 分量为副切线的方向（±1）
+///
+/// 着色器中按 `bitangent = cross(normal, tangent.xyz) * tangent.w` 重建副切线，
+/// 即可与 Blender、Substance 等工具烘焙的法线贴图匹配。
+///
+/// MikkTSpace 按三角形的角生成切线，共享顶点在切线空间不连续处（如镜像 UV 的接缝）
+/// 会得到其中一个角的结果；需要精确结果时应先把接缝处的顶点拆开。
+/// 纹理坐标退化（面积为 0）的三角形不会产生 NaN，无法计算切线的顶点使用任意一个垂直于法线的方向。
+pub fn generate_tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    uvs: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    assert_eq!(positions.len(), normals.len());
+    assert_eq!(positions.len(), uvs.len());
+
+    struct Geometry<'a> {
+        positions: &'a [[f32; 3]],
+        normals: &'a [[f32; 3]],
+        uvs: &'a [[f32; 2]],
+        indices: &'a [u32],
+        tangents: Vec<[f32; 4]>,
+    }
+
+    impl Geometry<'_> {
+        fn index(&self, face: usize, vert: usize) -> usize {
+            self.indices[face * 3 + vert] as usize
+        }
+    }
+
+    impl bevy_mikktspace::Geometry for Geometry<'_> {
+        fn num_faces(&self) -> usize {
+            self.indices.len() / 3
+        }
+
+        fn num_vertices_of_face(&self, _face: usize) -> usize {
+            3
+        }
+
+        fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+            self.positions[self.index(face, vert)]
+        }
+
+        fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+            self.normals[self.index(face, vert)]
+        }
+
+        fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+            self.uvs[self.index(face, vert)]
+        }
+
+        fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+            let index = self.index(face, vert);
+            self.tangents[index] = tangent;
+        }
+    }
+
+    let mut geometry = Geometry {
+        positions,
+        normals,
+        uvs,
+        indices,
+        tangents: vec![[0.0; 4]; positions.len()],
+    };
+    if !indices.is_empty() && !bevy_mikktspace::generate_tangents(&mut geometry) {
+        log::warn!("MikkTSpace 无法为该网格生成切线");
+    }
+
+    let mut tangents = geometry.tangents;
+    for (tangent, normal) in tangents.iter_mut().zip(normals) {
+        let t = Vec3::from_slice(&tangent[..3]);
+        if !t.is_finite() || t.length_squared() < 1e-12 {
+            let normal = Vec3::from(*normal).normalize_or(Vec3::Y);
+            *tangent = normal.any_orthonormal_vector().extend(1.0).into();
+        }
+    }
+    tangents
+}
+
+/// 用 [`generate_tangents`] 为顶点写入切线与副切线，适用于各教程中带 `tangent`、`bitangent` 字段的 `ModelVertex`
+///
+/// `attributes` 返回顶点的位置、法线与纹理坐标，`write` 接收切线与副切线。
+/// 副切线按 `cross(normal, tangent) * -w` 重建，翻转后与 wgpu 纹理坐标系下的右手法线贴图匹配：
+/// ```ignore
+/// write_tangent_frames(
+///     &mut vertices,
+///     &indices,
+///     |v| (v.position, v.normal, v.tex_coords),
+///     |v, tangent, bitangent| {
+///         v.tangent = tangent;
+///         v.bitangent = bitangent;
+///     },
+/// );
+/// ```
+pub fn write_tangent_frames<V>(
+    vertices: &mut [V],
+    indices: &[u32],
+    attributes: impl Fn(&V) -> ([f32; 3], [f32; 3], [f32; 2]),
+    mut write: impl FnMut(&mut V, [f32; 3], [f32; 3]),
+) {
+    let (positions, (normals, uvs)): (Vec<_>, (Vec<_>, Vec<_>)) = vertices
+        .iter()
+        .map(|v| {
+            let (position, normal, uv) = attributes(v);
+            (position, (normal, uv))
+        })
+        .unzip();
+    let tangents = generate_tangents(&positions, &normals, &uvs, indices);
+    for ((v, t), normal) in vertices.iter_mut().zip(tangents).zip(normals) {
+        let tangent = Vec3::from_slice(&t[..3]);
+        let bitangent = Vec3::from(normal).cross(tangent) * -t[3];
+        write(v, tangent.into(), bitangent.into());
+    }
+}
+
+/// 平滑法线：按三角形在顶点处的夹角加权平均，结果与三角剖分的方式无关
+///
+/// 位置完全相同的顶点（如 UV 接缝两侧）视为同一个点，得到相同的法线
+pub fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    // 位置相同的顶点共享同一个累加槽
+    let mut slots = HashMap::new();
+    let slot_of: Vec<usize> = positions
+        .iter()
+        .map(|p| {
+            let key = p.map(f32::to_bits);
+            let next = slots.len();
+            *slots.entry(key).or_insert(next)
+        })
+        .collect();
+
+    let mut sums = vec![Vec3::ZERO; slots.len()];
+    for tri in indices.chunks_exact(3) {
+        let p = [tri[0], tri[1], tri[2]].map(|i| Vec3::from(positions[i as usize]));
+        let face_normal = (p[1] - p[0]).cross(p[2] - p[0]).normalize_or_zero();
+        for k in 0..3 {
+            let e1 = p[(k + 1) % 3] - p[k];
+            let e2 = p[(k + 2) % 3] - p[k];
+            let angle = e1.angle_between(e2);
+            if angle.is_finite() {
+                sums[slot_of[tri[k] as usize]] += face_normal * angle;
+            }
+        }
+    }
+    slot_of
+        .iter()
+        .map(|&slot| sums[slot].normalize_or(Vec3::Y).into())
+        .collect()
+}
+
+/// 把索引网格展开为每个三角形的角各占一个顶点，通常在计算平面法线之前使用
+pub fn unweld<V: Copy>(vertices: &[V], indices: &[u32]) -> Vec<V> {
+    indices.iter().map(|&i| vertices[i as usize]).collect()
+}
+
+/// 非索引 triangle list 的平面法线，同一三角形的三个顶点使用同一法线
+pub fn flat_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    positions
+        .chunks_exact(3)
+        .flat_map(|tri| {
+            let [p0, p1, p2] = [tri[0], tri[1], tri[2]].map(Vec3::from);
+            let normal: [f32; 3] = (p1 - p0).cross(p2 - p0).normalize_or(Vec3::Y).into();
+            [normal; 3]
+        })
+        .collect()
+}
+
+/// 合并所有属性都在容差范围内的顶点，返回新的顶点与索引
+///
+/// `attributes` 返回参与比较的分量，前三个分量须为位置，例如：
+/// ```ignore
+/// let (vertices, indices) = weld(&vertices, &indices, 1e-5, |v: &PosNormalUv| {
+///     [v.pos[0], v.pos[1], v.pos[2], v.normal[0], v.normal[1], v.normal[2], v.uv[0], v.uv[1]]
+/// });
+/// ```
+pub fn weld<V: Copy, const N: usize>(
+    vertices: &[V],
+    indices: &[u32],
+    tolerance: f32,
+    attributes: impl Fn(&V) -> [f32; N],
+) -> (Vec<V>, Vec<u32>) {
+    assert!(N >= 3, "attributes must start with the position");
+    let cell_size = tolerance.max(f32::EPSILON) * 2.0;
+    let cell_of = |a: &[f32; N]| [a[0], a[1], a[2]].map(|c| (c / cell_size).floor() as i64);
+
+    let mut welded: Vec<V> = vec![];
+    let mut welded_attributes: Vec<[f32; N]> = vec![];
+    let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let remap: Vec<u32> = vertices
+        .iter()
+        .map(|v| {
+            let a = attributes(v);
+            let cell = cell_of(&a);
+            // 容差小于格子边长，只需检查相邻的 27 个格子
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let key = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                        let Some(candidates) = grid.get(&key) else {
+                            continue;
+                        };
+                        for &candidate in candidates {
+                            let b = &welded_attributes[candidate as usize];
+                            if a.iter().zip(b).all(|(x, y)| (x - y).abs() <= tolerance) {
+                                return candidate;
+                            }
+                        }
+                    }
+                }
+            }
+            let index = welded.len() as u32;
+            welded.push(*v);
+            welded_attributes.push(a);
+            grid.entry(cell).or_default().push(index);
+            index
+        })
+        .collect();
+
+    let indices = indices.iter().map(|&i| remap[i as usize]).collect();
+    (welded, indices)
+}
+
+// Forsyth 算法使用的模拟缓存大小
+const CACHE_SIZE: usize = 32;
+
+fn vertex_score(cache_position: Option<usize>, remaining_valence: u32) -> f32 {
+    if remaining_valence == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        None => 0.0,
+        // 刚用过的三个顶点属于上一个三角形，给固定分数，避免总是选与上一个三角形共边的三角形
+        Some(p) if p < 3 => 0.75,
+        Some(p) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (p - 3) as f32 * scale).max(0.0).powf(1.5)
+        }
+    };
+    // 剩余三角形少的顶点优先处理，避免留下孤立的三角形
+    cache_score + 2.0 * (remaining_valence as f32).powf(-0.5)
+}
+
+/// 重排三角形顺序以提高 GPU 顶点缓存的命中率（Tom Forsyth 的线性时间算法）
+///
+/// 只改变三角形之间的顺序，每个三角形的顶点顺序（即朝向）保持不变
+pub fn optimize_vertex_cache(indices: &mut [u32], vertex_count: usize) {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return;
+    }
+
+    let mut vertex_triangles: Vec<Vec<u32>> = vec![vec![]; vertex_count];
+    for (t, tri) in indices.chunks_exact(3).enumerate() {
+        for &v in tri {
+            vertex_triangles[v as usize].push(t as u32);
+        }
+    }
+    let mut remaining: Vec<u32> = vertex_triangles.iter().map(|t| t.len() as u32).collect();
+    let mut vertex_scores: Vec<f32> = remaining.iter().map(|&r| vertex_score(None, r)).collect();
+    let mut triangle_scores: Vec<f32> = indices
+        .chunks_exact(3)
+        .map(|tri| tri.iter().map(|&v| vertex_scores[v as usize]).sum())
+        .collect();
+    let mut emitted = vec![false; triangle_count];
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut output = Vec::with_capacity(indices.len());
+    let mut scan_cursor = 0;
+    let mut best = None;
+
+    for _ in 0..triangle_count {
+        // 缓存中没有候选三角形时，按顺序找下一个未输出的三角形
+        let t = match best {
+            Some(t) => t,
+            None => {
+                while emitted[scan_cursor] {
+                    scan_cursor += 1;
+                }
+                scan_cursor
+            }
+        };
+        emitted[t] = true;
+        let tri = [indices[t * 3], indices[t * 3 + 1], indices[t * 3 + 2]];
+        output.extend_from_slice(&tri);
+
+        for &v in &tri {
+            let v = v as usize;
+            remaining[v] -= 1;
+            let list = &mut vertex_triangles[v];
+            if let Some(pos) = list.iter().position(|&x| x as usize == t) {
+                list.swap_remove(pos);
+            }
+        }
+        // 最近使用的顶点放到缓存最前面
+        let previous = std::mem::take(&mut cache);
+        cache.extend_from_slice(&tri);
+        cache.extend(previous.iter().filter(|v| !tri.contains(v)));
+        let evicted: Vec<u32> = if cache.len() > CACHE_SIZE {
+            cache.split_off(CACHE_SIZE)
+        } else {
+            vec![]
+        };
+
+        // 更新缓存内与刚被挤出缓存的顶点的分数，以及它们所在三角形的分数
+        best = None;
+        let mut best_score = f32::MIN;
+        let touched = cache
+            .iter()
+            .enumerate()
+            .map(|(p, &v)| (v, Some(p)))
+            .chain(evicted.iter().map(|&v| (v, None)));
+        for (v, position) in touched {
+            let v = v as usize;
+            let score = vertex_score(position, remaining[v]);
+            let delta = score - vertex_scores[v];
+            vertex_scores[v] = score;
+            for &tri in &vertex_triangles[v] {
+                triangle_scores[tri as usize] += delta;
+            }
+        }
+        for &v in &cache {
+            for &tri in &vertex_triangles[v as usize] {
+                let score = triangle_scores[tri as usize];
+                if score > best_score {
+                    best_score = score;
+                    best = Some(tri as usize);
+                }
+            }
+        }
+    }
+    indices[..output.len()].copy_from_slice(&output);
+}
+
+/// 重排三角形以减少像素的重复着色，应在 [`optimize_vertex_cache`] 之后调用
+///
+/// 按缓存未命中的位置把三角形切分为若干簇，朝向网格外侧的簇先绘制，
+/// 使其先写入深度、遮挡后绘制的簇。簇内部的顺序不变，顶点缓存命中率基本不受影响。
+pub fn optimize_overdraw(indices: &mut [u32], positions: &[[f32; 3]]) {
+    if indices.len() < 6 {
+        return;
+    }
+    // 三个顶点都不在缓存中的三角形开始一个新的簇
+    let mut clusters = vec![0];
+    let mut cache: Vec<u32> = vec![];
+    for (t, tri) in indices.chunks_exact(3).enumerate() {
+        let misses = tri.iter().filter(|v| !cache.contains(v)).count();
+        if misses == 3 && t > 0 {
+            clusters.push(t);
+        }
+        for &v in tri {
+            if !cache.contains(&v) {
+                cache.insert(0, v);
+            }
+        }
+        cache.truncate(16);
+    }
+    clusters.push(indices.len() / 3);
+
+    let point = |i: u32| Vec3::from(positions[i as usize]);
+    let mesh_centroid = indices.iter().map(|&i| point(i)).sum::<Vec3>() / indices.len() as f32;
+    let mut keyed: Vec<(f32, usize, usize)> = clusters
+        .windows(2)
+        .map(|w| {
+            let tris = &indices[w[0] * 3..w[1] * 3];
+            let mut normal = Vec3::ZERO;
+            let mut centroid = Vec3::ZERO;
+            for tri in tris.chunks_exact(3) {
+                let [p0, p1, p2] = [tri[0], tri[1], tri[2]].map(point);
+                normal += (p1 - p0).cross(p2 - p0);
+                centroid += p0 + p1 + p2;
+            }
+            centroid /= tris.len() as f32;
+            let key = (centroid - mesh_centroid).dot(normal.normalize_or_zero());
+            (key, w[0], w[1])
+        })
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let reordered: Vec<u32> = keyed
+        .iter()
+        .flat_map(|&(_, start, end)| indices[start * 3..end * 3].iter().copied())
+        .collect();
+    indices.copy_from_slice(&reordered);
+}
+
+/// 按首次被索引的顺序重排顶点，提高顶点读取的局部性，同时去掉没有被引用的顶点
+pub fn optimize_vertex_fetch<V: Copy>(vertices: &[V], indices: &mut [u32]) -> Vec<V> {
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut reordered = Vec::with_capacity(vertices.len());
+    for index in indices.iter_mut() {
+        let old = *index as usize;
+        if remap[old] == u32::MAX {
+            remap[old] = reordered.len() as u32;
+            reordered.push(vertices[old]);
+        }
+        *index = remap[old];
+    }
+    reordered
+}
+
+/// 平均每个三角形的缓存未命中次数（ACMR），使用 FIFO 缓存模拟，最优约为 0.5，最差为 3
+pub fn average_cache_miss_ratio(indices: &[u32], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return 0.0;
+    }
+    let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+    let mut misses = 0;
+    for &v in indices {
+        if !cache.contains(&v) {
+            misses += 1;
+            if cache.len() == cache_size {
+                cache.pop_front();
+            }
+            cache.push_back(v);
+        }
+    }
+    misses as f32 / triangle_count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{Cuboid, Grid};
+    use crate::vertex::PosNormalUv;
+
+    type Attributes = (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>);
+
+    fn attrs(vertices: &[PosNormalUv]) -> Attributes {
+        (
+            vertices.iter().map(|v| v.pos).collect(),
+            vertices.iter().map(|v| v.normal).collect(),
+            vertices.iter().map(|v| v.uv).collect(),
+        )
+    }
+
+    fn sorted_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+        // 旋转到最小的索引在前，保留朝向
+        let mut tris: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|t| {
+                let k = (0..3).min_by_key(|&k| t[k]).unwrap();
+                [t[k], t[(k + 1) % 3], t[(k + 2) % 3]]
+            })
+            .collect();
+        tris.sort();
+        tris
+    }
+
+    #[test]
+    fn tangents_follow_u_direction() {
+        // Grid 在 XZ 平面上，u 沿 +X，v 沿 +Z
+        let mesh = Grid::default().generate();
+        let (positions, normals, uvs) = attrs(&mesh.vertices);
+        let tangents = generate_tangents(&positions, &normals, &uvs, &mesh.indices);
+        for t in tangents {
+            assert!(
+                (Vec3::from_slice(&t[..3]) - Vec3::X).length() < 1e-4,
+                "{t:?}"
+            );
+            // 副切线 cross(N, T) = cross(Y, X) = -Z，与 v 的方向 +Z 相反
+            assert_eq!(t[3], -1.0);
+        }
+    }
+
+    #[test]
+    fn degenerate_uvs_give_finite_tangents() {
+        let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let normals = [[0.0, 0.0, 1.0]; 3];
+        let uvs = [[0.5, 0.5]; 3];
+        for t in generate_tangents(&positions, &normals, &uvs, &[0, 1, 2]) {
+            let t3 = Vec3::from_slice(&t[..3]);
+            assert!(t3.is_finite() && (t3.length() - 1.0).abs() < 1e-4);
+            assert!(t3.dot(Vec3::Z).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn tangent_frames_flip_bitangent() {
+        #[derive(Clone, Copy, Default)]
+        struct Vertex {
+            position: [f32; 3],
+            normal: [f32; 3],
+            uv: [f32; 2],
+            tangent: [f32; 3],
+            bitangent: [f32; 3],
+        }
+        let mesh = Grid::default().generate();
+        let mut vertices: Vec<Vertex> = mesh
+            .vertices
+            .iter()
+            .map(|v| Vertex {
+                position: v.pos,
+                normal: v.normal,
+                uv: v.uv,
+                ..Default::default()
+            })
+            .collect();
+        write_tangent_frames(
+            &mut vertices,
+            &mesh.indices,
+            |v| (v.position, v.normal, v.uv),
+            |v, tangent, bitangent| {
+                v.tangent = tangent;
+                v.bitangent = bitangent;
+            },
+        );
+        for v in vertices {
+            assert!((Vec3::from(v.tangent) - Vec3::X).length() < 1e-4);
+            // 与教程中手写的计算一致，副切线翻转为 v 的反方向 -Z
+            assert!((Vec3::from(v.bitangent) + Vec3::Z).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn smooth_normals_merge_split_corners() {
+        let mesh = Cuboid::default().generate();
+        let (positions, _, _) = attrs(&mesh.vertices);
+        let normals = smooth_normals(&positions, &mesh.indices);
+        for (p, n) in positions.iter().zip(&normals) {
+            // 角上的平滑法线沿对角线方向
+            let expected = Vec3::from(*p).normalize();
+            assert!((Vec3::from(*n) - expected).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn flat_normals_per_face() {
+        let mesh = Cuboid::default().generate();
+        let vertices = unweld(&mesh.vertices, &mesh.indices);
+        assert_eq!(vertices.len(), mesh.indices.len());
+        let (positions, expected, _) = attrs(&vertices);
+        for (n, e) in flat_normals(&positions).iter().zip(&expected) {
+            assert!((Vec3::from(*n) - Vec3::from(*e)).length() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn weld_respects_attributes_and_tolerance() {
+        let mesh = Cuboid::default().generate();
+        let by_position = |v: &PosNormalUv| v.pos;
+        let (welded, indices) = weld(&mesh.vertices, &mesh.indices, 1e-5, by_position);
+        assert_eq!(welded.len(), 8);
+        assert_eq!(indices.len(), mesh.indices.len());
+
+        let with_normal = |v: &PosNormalUv| {
+            let [x, y, z] = v.pos;
+            let [nx, ny, nz] = v.normal;
+            [x, y, z, nx, ny, nz]
+        };
+        let (welded, _) = weld(&mesh.vertices, &mesh.indices, 1e-5, with_normal);
+        assert_eq!(welded.len(), 24);
+
+        let mut jittered = mesh.vertices.clone();
+        for (i, v) in jittered.iter_mut().enumerate() {
+            v.pos[0] += if i % 2 == 0 { 4e-6 } else { -4e-6 };
+        }
+        let (welded, _) = weld(&jittered, &mesh.indices, 1e-5, by_position);
+        assert_eq!(welded.len(), 8);
+    }
+
+    #[test]
+    fn vertex_cache_optimization_lowers_acmr() {
+        let mesh = Grid {
+            width_segments: 40,
+            depth_segments: 40,
+            ..Default::default()
+        }
+        .generate();
+        // 用固定种子打乱三角形顺序
+        let mut tris: Vec<[u32; 3]> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect();
+        let mut seed = 12345u32;
+        for i in (1..tris.len()).rev() {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            tris.swap(i, (seed >> 8) as usize % (i + 1));
+        }
+        let mut indices: Vec<u32> = tris.into_iter().flatten().collect();
+        let before = average_cache_miss_ratio(&indices, 16);
+
+        optimize_vertex_cache(&mut indices, mesh.vertices.len());
+        let after = average_cache_miss_ratio(&indices, 16);
+        assert!(after < 0.8 && after < before * 0.5, "{before} -> {after}");
+        assert_eq!(sorted_triangles(&indices), sorted_triangles(&mesh.indices));
+
+        let (positions, _, _) = attrs(&mesh.vertices);
+        optimize_overdraw(&mut indices, &positions);
+        assert_eq!(sorted_triangles(&indices), sorted_triangles(&mesh.indices));
+    }
+
+    #[test]
+    fn overdraw_keeps_every_triangle() {
+        let mesh = Cuboid::default().generate();
+        let (positions, _, _) = attrs(&mesh.vertices);
+        let mut indices = mesh.indices.clone();
+        optimize_vertex_cache(&mut indices, positions.len());
+        optimize_overdraw(&mut indices, &positions);
+        assert_eq!(sorted_triangles(&indices), sorted_triangles(&mesh.indices));
+    }
+
+    #[test]
+    fn vertex_fetch_follows_first_use() {
+        let vertices = [10, 11, 12, 13, 14];
+        let mut indices = vec![3, 1, 4, 3, 4, 1];
+        let reordered = optimize_vertex_fetch(&vertices, &mut indices);
+        assert_eq!(reordered, [13, 11, 14]);
+        assert_eq!(indices, [0, 1, 2, 0, 2, 1]);
+    }
+}
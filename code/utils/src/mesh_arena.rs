@@ -0,0 +1,343 @@
+use crate::BufferObj;
+use crate::vertex::Vertex;
+use bytemuck::Pod;
+use core::marker::PhantomData;
+use core::ops::Range;
+
+/// 首次适配（first-fit）的区间分配器，释放时合并相邻的空闲区间
+pub struct RangeAllocator {
+    size: u64,
+    // 按起始位置排序、互不相邻的空闲区间
+    free_ranges: Vec<Range<u64>>,
+}
+
+impl RangeAllocator {
+    pub fn new(size: u64) -> Self {
+        let mut free_ranges = Vec::new();
+        if size > 0 {
+            free_ranges.push(0..size);
+        }
+        Self { size, free_ranges }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn allocate(&mut self, len: u64) -> Option<Range<u64>> {
+        if len == 0 {
+            return Some(0..0);
+        }
+        let index = self
+            .free_ranges
+            .iter()
+            .position(|range| range.end - range.start >= len)?;
+        let range = &mut self.free_ranges[index];
+        let allocated = range.start..range.start + len;
+        range.start += len;
+        if range.start == range.end {
+            self.free_ranges.remove(index);
+        }
+        Some(allocated)
+    }
+
+    pub fn free(&mut self, range: Range<u64>) {
+        if range.start == range.end {
+            return;
+        }
+        debug_assert!(range.end <= self.size);
+        let index = self
+            .free_ranges
+            .partition_point(|free| free.start < range.start);
+        debug_assert!(
+            index == 0 || self.free_ranges[index - 1].end <= range.start,
+            "double free"
+        );
+        self.free_ranges.insert(index, range);
+        // 与后一个区间合并
+        if index + 1 < self.free_ranges.len()
+            && self.free_ranges[index].end == self.free_ranges[index + 1].start
+        {
+            let next = self.free_ranges.remove(index + 1);
+            self.free_ranges[index].end = next.end;
+        }
+        // 与前一个区间合并
+        if index > 0 && self.free_ranges[index - 1].end == self.free_ranges[index].start {
+            let current = self.free_ranges.remove(index);
+            self.free_ranges[index - 1].end = current.end;
+        }
+    }
+
+    /// 扩大可分配的总大小，新增的空间追加在末尾
+    pub fn grow(&mut self, new_size: u64) {
+        if new_size <= self.size {
+            return;
+        }
+        let old_size = self.size;
+        self.size = new_size;
+        self.free(old_size..new_size);
+    }
+
+    pub fn free_space(&self) -> u64 {
+        self.free_ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+/// 网格在共享缓冲区中的位置
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MeshAllocation {
+    pub base_vertex: u32,
+    pub vertex_count: u32,
+    pub first_index: u32,
+    pub index_count: u32,
+}
+
+impl MeshAllocation {
+    /// 对应的间接绘制参数，可拼接后写入 `BufferObj::create_indirect_buffer`
+    pub fn draw_indexed_args(
+        &self,
+        instance_count: u32,
+        first_instance: u32,
+    ) -> wgpu::util::DrawIndexedIndirectArgs {
+        wgpu::util::DrawIndexedIndirectArgs {
+            index_count: self.index_count,
+            instance_count,
+            first_index: self.first_index,
+            base_vertex: self.base_vertex as i32,
+            first_instance,
+        }
+    }
+
+    fn vertex_range(&self) -> Range<u64> {
+        self.base_vertex as u64..(self.base_vertex + self.vertex_count) as u64
+    }
+
+    fn index_range(&self) -> Range<u64> {
+        self.first_index as u64..(self.first_index + self.index_count) as u64
+    }
+}
+
+/// 共享顶点/索引缓冲区的网格分配器
+///
+/// 许多网格被打包进一个大的顶点缓冲区与一个大的索引缓冲区，绘制时只需绑定一次，
+/// 每个网格通过 `base_vertex` / `first_index` 区分，便于批量绘制或合并为 multi-draw-indirect。
+///
+/// # NOTE:
+/// 空间不足时缓冲区会翻倍扩容并在 GPU 上复制已有数据，引用旧缓冲区的 bind group 需要重建
+pub struct MeshArena<V: Vertex + Pod> {
+    pub vertex_buf: BufferObj,
+    pub index_buf: BufferObj,
+    vertex_alloc: RangeAllocator,
+    index_alloc: RangeAllocator,
+    label: Option<&'static str>,
+    _marker: PhantomData<V>,
+}
+
+#[allow(dead_code)]
+impl<V: Vertex + Pod> MeshArena<V> {
+    #[track_caller]
+    pub fn new(
+        device: &wgpu::Device,
+        vertex_capacity: u64,
+        index_capacity: u64,
+        label: Option<&'static str>,
+    ) -> Self {
+        Self {
+            vertex_buf: Self::create_vertex_buf(device, vertex_capacity, label),
+            index_buf: Self::create_index_buf(device, index_capacity, label),
+            vertex_alloc: RangeAllocator::new(vertex_capacity),
+            index_alloc: RangeAllocator::new(index_capacity),
+            label,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 分配并上传一个网格，`indices` 是相对于网格自身顶点的索引
+    #[track_caller]
+    pub fn alloc(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[V],
+        indices: &[u32],
+    ) -> MeshAllocation {
+        let vertex_range = match self.vertex_alloc.allocate(vertices.len() as u64) {
+            Some(range) => range,
+            None => {
+                self.grow_vertices(device, queue, vertices.len() as u64);
+                self.vertex_alloc.allocate(vertices.len() as u64).unwrap()
+            }
+        };
+        let index_range = match self.index_alloc.allocate(indices.len() as u64) {
+            Some(range) => range,
+            None => {
+                self.grow_indices(device, queue, indices.len() as u64);
+                self.index_alloc.allocate(indices.len() as u64).unwrap()
+            }
+        };
+
+        if !vertices.is_empty() {
+            queue.write_buffer(
+                &self.vertex_buf.buffer,
+                vertex_range.start * Self::vertex_stride(),
+                bytemuck::cast_slice(vertices),
+            );
+        }
+        if !indices.is_empty() {
+            queue.write_buffer(
+                &self.index_buf.buffer,
+                index_range.start * 4,
+                bytemuck::cast_slice(indices),
+            );
+        }
+
+        MeshAllocation {
+            base_vertex: vertex_range.start as u32,
+            vertex_count: vertices.len() as u32,
+            first_index: index_range.start as u32,
+            index_count: indices.len() as u32,
+        }
+    }
+
+    pub fn free(&mut self, mesh: MeshAllocation) {
+        self.vertex_alloc.free(mesh.vertex_range());
+        self.index_alloc.free(mesh.index_range());
+    }
+
+    pub fn vertex_capacity(&self) -> u64 {
+        self.vertex_alloc.size()
+    }
+
+    pub fn index_capacity(&self) -> u64 {
+        self.index_alloc.size()
+    }
+
+    /// 绑定共享的顶点与索引缓冲区
+    pub fn set_buffers<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, vertex_slot: u32) {
+        rpass.set_vertex_buffer(vertex_slot, self.vertex_buf.buffer.slice(..));
+        rpass.set_index_buffer(self.index_buf.buffer.slice(..), wgpu::IndexFormat::Uint32);
+    }
+
+    /// 需先调用 `set_buffers`
+    pub fn draw(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        mesh: &MeshAllocation,
+        instances: Range<u32>,
+    ) {
+        rpass.draw_indexed(
+            mesh.first_index..mesh.first_index + mesh.index_count,
+            mesh.base_vertex as i32,
+            instances,
+        );
+    }
+
+    /// 将一批网格的绘制参数拼接为间接绘制缓冲区的内容
+    pub fn indirect_args(meshes: &[(MeshAllocation, u32)]) -> Vec<u8> {
+        meshes
+            .iter()
+            .flat_map(|(mesh, instance_count)| {
+                mesh.draw_indexed_args(*instance_count, 0)
+                    .as_bytes()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    fn grow_vertices(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, required: u64) {
+        let old_capacity = self.vertex_alloc.size();
+        let new_capacity = (old_capacity * 2).max(old_capacity + required);
+        let new_buf = Self::create_vertex_buf(device, new_capacity, self.label);
+        Self::copy_buffer(device, queue, &self.vertex_buf, &new_buf);
+        self.vertex_buf = new_buf;
+        self.vertex_alloc.grow(new_capacity);
+    }
+
+    fn grow_indices(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, required: u64) {
+        let old_capacity = self.index_alloc.size();
+        let new_capacity = (old_capacity * 2).max(old_capacity + required);
+        let new_buf = Self::create_index_buf(device, new_capacity, self.label);
+        Self::copy_buffer(device, queue, &self.index_buf, &new_buf);
+        self.index_buf = new_buf;
+        self.index_alloc.grow(new_capacity);
+    }
+
+    fn copy_buffer(device: &wgpu::Device, queue: &wgpu::Queue, src: &BufferObj, dst: &BufferObj) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("MeshArena grow"),
+        });
+        encoder.copy_buffer_to_buffer(&src.buffer, 0, &dst.buffer, 0, src.size);
+        queue.submit(Some(encoder.finish()));
+    }
+
+    fn vertex_stride() -> u64 {
+        core::mem::size_of::<V>() as u64
+    }
+
+    #[track_caller]
+    fn create_vertex_buf(
+        device: &wgpu::Device,
+        capacity: u64,
+        label: Option<&'static str>,
+    ) -> BufferObj {
+        let size =
+            (capacity.max(1) * Self::vertex_stride()).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        BufferObj::create_empty_storage_buffer(
+            device,
+            size,
+            wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            label,
+        )
+    }
+
+    #[track_caller]
+    fn create_index_buf(
+        device: &wgpu::Device,
+        capacity: u64,
+        label: Option<&'static str>,
+    ) -> BufferObj {
+        BufferObj::create_empty_storage_buffer(
+            device,
+            capacity.max(1) * 4,
+            wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            label,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeAllocator;
+
+    #[test]
+    fn allocate_and_coalesce() {
+        let mut alloc = RangeAllocator::new(100);
+        let a = alloc.allocate(10).unwrap();
+        let b = alloc.allocate(20).unwrap();
+        let c = alloc.allocate(30).unwrap();
+        assert_eq!((a.clone(), b.clone(), c.clone()), (0..10, 10..30, 30..60));
+
+        alloc.free(a);
+        alloc.free(c);
+        // 0..10 放不下 15 个，会分配在 c 释放后合并出的 30..100 中
+        assert_eq!(alloc.allocate(15), Some(30..45));
+        alloc.free(b);
+        assert_eq!(alloc.allocate(30), Some(0..30));
+        assert_eq!(alloc.free_space(), 100 - 45);
+    }
+
+    #[test]
+    fn grow_extends_tail() {
+        let mut alloc = RangeAllocator::new(8);
+        assert_eq!(alloc.allocate(6), Some(0..6));
+        assert_eq!(alloc.allocate(4), None);
+        alloc.grow(16);
+        assert_eq!(alloc.allocate(4), Some(6..10));
+        assert_eq!(alloc.free_space(), 6);
+    }
+}
@@ -0,0 +1,361 @@
+//! 在 GPU 上生成纹理的完整 mip 链
+//!
+//! 可渲染且可过滤的格式使用 blit 渲染通道逐级缩小，sRGB 格式在采样与写入时由硬件完成转换；
+//! 其余支持 storage 绑定的格式使用计算着色器对 2x2 像素取平均。
+//! 法线贴图在平均之后会重新归一化，且应使用线性（非 sRGB）格式。
+
+use std::collections::HashMap;
+use wgpu::TextureFormat;
+
+/// 完整 mip 链的级数
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// mip 的过滤方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MipFilter {
+    /// 普通颜色纹理，直接取平均
+    #[default]
+    Color,
+    /// 以 unorm 编码的切线空间法线贴图，取平均后重新归一化
+    NormalMap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MipMethod {
+    Blit,
+    Compute,
+}
+
+/// 生成 mip 链所需的额外纹理用途，格式不支持任何一种生成方式时返回 `None`
+///
+/// 创建需要生成 mip 的纹理时，应将返回的用途加到 `TextureDescriptor::usage` 上
+pub fn required_usage(device: &wgpu::Device, format: TextureFormat) -> Option<wgpu::TextureUsages> {
+    if is_blittable(device, format) {
+        Some(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+    } else if is_storage_capable(device, format) {
+        Some(wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING)
+    } else {
+        None
+    }
+}
+
+fn is_blittable(device: &wgpu::Device, format: TextureFormat) -> bool {
+    let features = format.guaranteed_format_features(device.features());
+    features
+        .allowed_usages
+        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        && features
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+}
+
+fn is_storage_capable(device: &wgpu::Device, format: TextureFormat) -> bool {
+    storage_format_name(format).is_some()
+        && format
+            .guaranteed_format_features(device.features())
+            .allowed_usages
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+}
+
+// 计算着色器中可作为 storage 纹理的浮点格式
+fn storage_format_name(format: TextureFormat) -> Option<&'static str> {
+    Some(match format {
+        TextureFormat::Rgba8Unorm => "rgba8unorm",
+        TextureFormat::Rgba8Snorm => "rgba8snorm",
+        TextureFormat::Bgra8Unorm => "bgra8unorm",
+        TextureFormat::Rgba16Float => "rgba16float",
+        TextureFormat::Rgba32Float => "rgba32float",
+        TextureFormat::R32Float => "r32float",
+        TextureFormat::Rg32Float => "rg32float",
+        TextureFormat::R16Float => "r16float",
+        TextureFormat::Rg16Float => "rg16float",
+        TextureFormat::R8Unorm => "r8unorm",
+        TextureFormat::Rg8Unorm => "rg8unorm",
+        TextureFormat::Rgb10a2Unorm => "rgb10a2unorm",
+        TextureFormat::Rg11b10Ufloat => "rg11b10ufloat",
+        _ => return None,
+    })
+}
+
+/// mip 生成器，按格式缓存所创建的管线
+pub struct MipmapGenerator {
+    sampler: wgpu::Sampler,
+    blit_shader: wgpu::ShaderModule,
+    blit_pipelines: HashMap<(TextureFormat, MipFilter), wgpu::RenderPipeline>,
+    compute_pipelines: HashMap<(TextureFormat, MipFilter), wgpu::ComputePipeline>,
+}
+
+#[allow(dead_code)]
+impl MipmapGenerator {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("mipmap sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let blit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("mipmap blit shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader/mipmap_blit.wgsl").into()),
+        });
+        Self {
+            sampler,
+            blit_shader,
+            blit_pipelines: HashMap::new(),
+            compute_pipelines: HashMap::new(),
+        }
+    }
+
+    /// 由第 0 级 mip 生成其余各级，并立即提交
+    pub fn generate_and_submit(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        filter: MipFilter,
+    ) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("mipmap encoder"),
+        });
+        self.generate(device, &mut encoder, texture, filter);
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// 将生成 mip 的命令编码到 `encoder` 中，数组纹理的每一层分别生成
+    ///
+    /// 纹理需带有 [`required_usage`] 所返回的用途；3D 纹理与多重采样纹理会被忽略
+    pub fn generate(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        filter: MipFilter,
+    ) {
+        let mip_count = texture.mip_level_count();
+        if mip_count < 2 {
+            return;
+        }
+        if texture.dimension() != wgpu::TextureDimension::D2 || texture.sample_count() > 1 {
+            log::warn!("MipmapGenerator: 只支持单采样的 2D / 2D 数组纹理");
+            return;
+        }
+        let format = texture.format();
+        if filter == MipFilter::NormalMap && format.is_srgb() {
+            log::warn!("MipmapGenerator: 法线贴图不应使用 sRGB 格式 {format:?}");
+        }
+        let Some(method) = Self::method(device, texture) else {
+            log::warn!(
+                "MipmapGenerator: 格式 {format:?} 与用途 {:?} 不支持生成 mip",
+                texture.usage()
+            );
+            return;
+        };
+
+        for layer in 0..texture.depth_or_array_layers() {
+            for level in 1..mip_count {
+                let src_view = Self::level_view(texture, level - 1, layer);
+                let dst_view = Self::level_view(texture, level, layer);
+                match method {
+                    MipMethod::Blit => {
+                        self.blit(device, encoder, format, filter, &src_view, &dst_view)
+                    }
+                    MipMethod::Compute => {
+                        let width = (texture.width() >> level).max(1);
+                        let height = (texture.height() >> level).max(1);
+                        self.dispatch(
+                            device,
+                            encoder,
+                            format,
+                            filter,
+                            &src_view,
+                            &dst_view,
+                            (width, height),
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    fn method(device: &wgpu::Device, texture: &wgpu::Texture) -> Option<MipMethod> {
+        let usage = texture.usage();
+        if !usage.contains(wgpu::TextureUsages::TEXTURE_BINDING) {
+            return None;
+        }
+        if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            && is_blittable(device, texture.format())
+        {
+            Some(MipMethod::Blit)
+        } else if usage.contains(wgpu::TextureUsages::STORAGE_BINDING)
+            && is_storage_capable(device, texture.format())
+        {
+            Some(MipMethod::Compute)
+        } else {
+            None
+        }
+    }
+
+    fn level_view(texture: &wgpu::Texture, level: u32, layer: u32) -> wgpu::TextureView {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("mipmap level view"),
+            format: Some(texture.format()),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: level,
+            mip_level_count: Some(1),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    }
+
+    fn blit(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        format: TextureFormat,
+        filter: MipFilter,
+        src_view: &wgpu::TextureView,
+        dst_view: &wgpu::TextureView,
+    ) {
+        let shader = &self.blit_shader;
+        let pipeline = self
+            .blit_pipelines
+            .entry((format, filter))
+            .or_insert_with(|| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("mipmap blit pipeline"),
+                    layout: None,
+                    vertex: wgpu::VertexState {
+                        module: shader,
+                        entry_point: Some("vs_main"),
+                        compilation_options: Default::default(),
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: shader,
+                        entry_point: Some("fs_main"),
+                        compilation_options: wgpu::PipelineCompilationOptions {
+                            constants: &[("NORMAL_MAP", normal_map_constant(filter))],
+                            ..Default::default()
+                        },
+                        targets: &[Some(format.into())],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview_mask: None,
+                    cache: None,
+                })
+            });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mipmap blit bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("mipmap blit rpass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: dst_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            ..Default::default()
+        });
+        rpass.set_pipeline(pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dispatch(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        format: TextureFormat,
+        filter: MipFilter,
+        src_view: &wgpu::TextureView,
+        dst_view: &wgpu::TextureView,
+        dst_size: (u32, u32),
+    ) {
+        let pipeline = self
+            .compute_pipelines
+            .entry((format, filter))
+            .or_insert_with(|| {
+                let source = include_str!("shader/mipmap_compute.wgsl")
+                    .replace("STORAGE_FORMAT", storage_format_name(format).unwrap());
+                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("mipmap compute shader"),
+                    source: wgpu::ShaderSource::Wgsl(source.into()),
+                });
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some("mipmap compute pipeline"),
+                    layout: None,
+                    module: &shader,
+                    entry_point: Some("cs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions {
+                        constants: &[("NORMAL_MAP", normal_map_constant(filter))],
+                        ..Default::default()
+                    },
+                    cache: None,
+                })
+            });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mipmap compute bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(dst_view),
+                },
+            ],
+        });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("mipmap compute pass"),
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(dst_size.0.div_ceil(8), dst_size.1.div_ceil(8), 1);
+    }
+}
+
+fn normal_map_constant(filter: MipFilter) -> f64 {
+    if filter == MipFilter::NormalMap {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// 使用临时的生成器为纹理生成 mip 链
+///
+/// 需要处理多张纹理时，应复用同一个 [`MipmapGenerator`] 以避免重复创建管线
+pub fn generate_mipmaps(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    filter: MipFilter,
+) {
+    MipmapGenerator::new(device).generate_and_submit(device, queue, texture, filter);
+}
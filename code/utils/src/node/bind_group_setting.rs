@@ -2,15 +2,19 @@ use std::vec::Vec;
 use wgpu::{BindGroupLayout, TextureFormat};
 
 use super::BindGroupData;
+use crate::BufferObj;
+use crate::resource_tracker::{ResourceCategory, TrackedResource};
 
 #[allow(dead_code)]
 pub struct BindGroupSetting {
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub bind_group: wgpu::BindGroup,
+    tracked: TrackedResource,
 }
 
 #[allow(dead_code)]
 impl BindGroupSetting {
+    #[track_caller]
     pub fn new(device: &wgpu::Device, bg_data: &BindGroupData) -> Self {
         let mut layouts: Vec<wgpu::BindGroupLayoutEntry> = vec![];
         let mut entries: Vec<wgpu::BindGroupEntry> = vec![];
@@ -25,7 +29,8 @@ impl BindGroupSetting {
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
-                    min_binding_size: wgpu::BufferSize::new(0),
+                    // 只有 `TypedBuffer` 知道元素类型，与着色器中变量的大小不一致时创建管线就会报错
+                    min_binding_size: buffer_obj.uniform_binding_size,
                 },
                 count: None,
             });
@@ -51,7 +56,7 @@ impl BindGroupSetting {
             });
             entries.push(wgpu::BindGroupEntry {
                 binding: b_index,
-                resource: buffer_obj.buffer.as_entire_binding(),
+                resource: storage_binding(buffer_obj),
             });
             b_index += 1;
         }
@@ -113,6 +118,7 @@ impl BindGroupSetting {
         Self {
             bind_group_layout,
             bind_group,
+            tracked: TrackedResource::new(ResourceCategory::BindGroup, None, 0),
         }
     }
 
@@ -121,6 +127,26 @@ impl BindGroupSetting {
     }
 }
 
+/// 存储缓冲区的绑定范围
+///
+/// 带动态偏移的存储缓冲区（比如 `FrameRing`）只绑定一个窗口，窗口大小即 `min_binding_size`，
+/// 否则任何非零的动态偏移都会越界
+pub(crate) fn storage_binding(buffer_obj: &BufferObj) -> wgpu::BindingResource<'_> {
+    if buffer_obj.has_dynamic_offset {
+        debug_assert!(
+            buffer_obj.min_binding_size.is_some(),
+            "storage buffer with dynamic offsets needs a min_binding_size as its window"
+        );
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &buffer_obj.buffer,
+            offset: 0,
+            size: buffer_obj.min_binding_size,
+        })
+    } else {
+        buffer_obj.buffer.as_entire_binding()
+    }
+}
+
 fn texture_sample_filterable(format: TextureFormat) -> bool {
     match format {
         // on iOS: texture binding 1 expects sample type = Float { filterable: true }, but given a view with format = R32Float
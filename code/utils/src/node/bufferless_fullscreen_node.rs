@@ -1,16 +1,20 @@
-use crate::DEPTH_FORMAT;
+use crate::resource_tracker::{ResourceCategory, TrackedResource};
+use crate::{DEPTH_FORMAT, Result, ValidationScope};
 use wgpu::{PrimitiveTopology, ShaderModule, TextureFormat};
 
 use super::BindGroupData;
 
+#[allow(dead_code)]
 pub struct BufferlessFullscreenNode {
     bind_group: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
+    tracked: [TrackedResource; 2],
 }
 
 #[allow(dead_code)]
 impl BufferlessFullscreenNode {
     #[allow(clippy::too_many_arguments)]
+    #[track_caller]
     pub fn new(
         device: &wgpu::Device,
         format: TextureFormat,
@@ -18,7 +22,8 @@ impl BufferlessFullscreenNode {
         shader_module: &ShaderModule,
         color_blend_state: Option<wgpu::BlendState>,
         sample_count: u32,
-    ) -> Self {
+    ) -> Result<Self> {
+        let scope = ValidationScope::push(device);
         let use_depth_stencil = true;
         let pipeline_vertex_buffers = [];
         let blend_state = if color_blend_state.is_some() {
@@ -74,10 +79,20 @@ impl BufferlessFullscreenNode {
 
         let bind_group = create_bind_group(device, bg_data, &pipeline.get_bind_group_layout(0));
 
-        Self {
+        scope.pop()?;
+
+        Ok(Self {
             bind_group,
             pipeline,
-        }
+            tracked: [
+                TrackedResource::new(
+                    ResourceCategory::BindGroup,
+                    Some("bufferless fullscreen"),
+                    0,
+                ),
+                TrackedResource::new(ResourceCategory::Pipeline, Some("bufferless fullscreen"), 0),
+            ],
+        })
     }
 
     pub fn draw(
@@ -127,7 +142,7 @@ pub fn create_bind_group(
     for storage_buf in bg_data.storage_buffers.iter() {
         entries.push(wgpu::BindGroupEntry {
             binding: b_index,
-            resource: storage_buf.buffer.as_entire_binding(),
+            resource: super::bind_group_setting::storage_binding(storage_buf),
         });
         b_index += 1;
     }
@@ -1,5 +1,7 @@
-use super::{BindGroupSetting, DynamicUniformBindGroup};
-use crate::BufferObj;
+use super::{BindGroupSetting, DynamicUniformBindGroup, ImmediateData};
+use crate::resource_tracker::{ResourceCategory, TrackedResource};
+use crate::{BufferObj, Result, ValidationScope};
+use bytemuck::Pod;
 use std::vec::Vec;
 use wgpu::ShaderModule;
 
@@ -7,57 +9,62 @@ use wgpu::ShaderModule;
 pub struct ComputeNode {
     pub bg_setting: BindGroupSetting,
     pub dy_uniform_bg: Option<DynamicUniformBindGroup>,
+    pub immediate_data: Option<ImmediateData>,
     pub pipeline_layout: wgpu::PipelineLayout,
     pub pipeline: wgpu::ComputePipeline,
     pub workgroup_count: (u32, u32, u32),
+    tracked: TrackedResource,
 }
 
 #[allow(dead_code)]
 impl ComputeNode {
+    #[track_caller]
     pub fn new(
         device: &wgpu::Device,
         bg_data: &super::BindGroupData,
         shader_module: &ShaderModule,
-    ) -> Self {
-        let mut visibilitys: Vec<wgpu::ShaderStages> = vec![];
-        for _ in
-            0..(bg_data.uniforms.len() + bg_data.storage_buffers.len() + bg_data.inout_tv.len())
-        {
-            visibilitys.push(wgpu::ShaderStages::COMPUTE);
-        }
-        let mut bg_data = bg_data.clone();
-        bg_data.visibilitys = visibilitys;
-        let bg_setting = BindGroupSetting::new(device, &bg_data);
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[Some(&bg_setting.bind_group_layout)],
-            immediate_size: 0,
-        });
-        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            module: shader_module,
-            entry_point: Some("cs_main"),
-            compilation_options: Default::default(),
-            cache: None,
-        });
-
-        ComputeNode {
-            bg_setting,
-            dy_uniform_bg: None,
-            pipeline_layout,
-            pipeline,
-            workgroup_count: bg_data.workgroup_count,
-        }
+    ) -> Result<Self> {
+        Self::create(device, bg_data, shader_module, false, None)
     }
 
     #[allow(dead_code)]
+    #[track_caller]
     pub fn new_with_dynamic_uniforms(
         device: &wgpu::Device,
         bg_data: &super::BindGroupData,
         shader_module: &ShaderModule,
-    ) -> Self {
+    ) -> Result<Self> {
+        Self::create(device, bg_data, shader_module, true, None)
+    }
+
+    /// 使用 `immediate_size` 字节的 immediate data，设备不支持时自动回退为 uniform 缓冲区
+    #[track_caller]
+    pub fn new_with_immediates(
+        device: &wgpu::Device,
+        bg_data: &super::BindGroupData,
+        shader_module: &ShaderModule,
+        immediate_size: u32,
+    ) -> Result<Self> {
+        let use_dynamic_uniforms = !bg_data.dynamic_uniforms.is_empty();
+        Self::create(
+            device,
+            bg_data,
+            shader_module,
+            use_dynamic_uniforms,
+            Some(immediate_size),
+        )
+    }
+
+    #[track_caller]
+    fn create(
+        device: &wgpu::Device,
+        bg_data: &super::BindGroupData,
+        shader_module: &ShaderModule,
+        use_dynamic_uniforms: bool,
+        immediate_size: Option<u32>,
+    ) -> Result<Self> {
+        // 捕获着色器与绑定布局不匹配等验证错误
+        let scope = ValidationScope::push(device);
         let mut visibilitys: Vec<wgpu::ShaderStages> = vec![];
         for _ in
             0..(bg_data.uniforms.len() + bg_data.storage_buffers.len() + bg_data.inout_tv.len())
@@ -68,19 +75,34 @@ impl ComputeNode {
         bg_data.visibilitys = visibilitys;
         let bg_setting = BindGroupSetting::new(device, &bg_data);
 
-        let mut dy_uniforms: Vec<(&BufferObj, wgpu::ShaderStages)> = vec![];
-        for obj in bg_data.dynamic_uniforms.clone() {
-            dy_uniforms.push((obj, wgpu::ShaderStages::COMPUTE));
-        }
-        let dy_uniform_bg = DynamicUniformBindGroup::new(device, dy_uniforms);
+        let dy_uniform_bg = if use_dynamic_uniforms {
+            let mut dy_uniforms: Vec<(&BufferObj, wgpu::ShaderStages)> = vec![];
+            for obj in bg_data.dynamic_uniforms.clone() {
+                dy_uniforms.push((obj, wgpu::ShaderStages::COMPUTE));
+            }
+            Some(DynamicUniformBindGroup::new(device, dy_uniforms))
+        } else {
+            None
+        };
+        let immediate_data = immediate_size
+            .map(|size| ImmediateData::new(device, size, wgpu::ShaderStages::COMPUTE))
+            .transpose()?;
 
+        let mut bind_group_layouts = vec![Some(&bg_setting.bind_group_layout)];
+        if let Some(dy_bg) = dy_uniform_bg.as_ref() {
+            bind_group_layouts.push(Some(&dy_bg.bind_group_layout));
+        }
+        let mut immediate_size = 0;
+        if let Some(immediate) = immediate_data.as_ref() {
+            if let Some(layout) = immediate.fallback_bind_group_layout() {
+                bind_group_layouts.push(Some(layout));
+            }
+            immediate_size = immediate.pipeline_immediate_size();
+        }
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[
-                Some(&bg_setting.bind_group_layout),
-                Some(&dy_uniform_bg.bind_group_layout),
-            ],
-            immediate_size: 0,
+            bind_group_layouts: &bind_group_layouts,
+            immediate_size,
         });
         let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: None,
@@ -91,13 +113,17 @@ impl ComputeNode {
             cache: None,
         });
 
-        ComputeNode {
+        scope.pop()?;
+
+        Ok(ComputeNode {
             bg_setting,
-            dy_uniform_bg: Some(dy_uniform_bg),
+            dy_uniform_bg,
+            immediate_data,
             pipeline_layout,
             pipeline,
             workgroup_count: bg_data.workgroup_count,
-        }
+            tracked: TrackedResource::new(ResourceCategory::Pipeline, Some("compute"), 0),
+        })
     }
 
     pub fn compute(&self, encoder: &mut wgpu::CommandEncoder) {
@@ -141,4 +167,133 @@ impl ComputeNode {
             );
         }
     }
+
+    /// 使用任意的动态偏移派发，比如 `FrameRing` 分配出的偏移
+    ///
+    /// `storage_offsets` 对应 bind group 0 中带动态偏移的存储缓冲区，
+    /// `uniform_offsets` 对应动态 uniform，都按绑定顺序排列
+    pub fn dispatch_by_dynamic_offsets<'a, 'b: 'a>(
+        &'b self,
+        cpass: &mut wgpu::ComputePass<'a>,
+        storage_offsets: &[wgpu::DynamicOffset],
+        uniform_offsets: &[wgpu::DynamicOffset],
+    ) {
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &self.bg_setting.bind_group, storage_offsets);
+        if let Some(node) = &self.dy_uniform_bg {
+            cpass.set_bind_group(1, &node.bind_group, uniform_offsets);
+        }
+        cpass.dispatch_workgroups(
+            self.workgroup_count.0,
+            self.workgroup_count.1,
+            self.workgroup_count.2,
+        );
+    }
+
+    pub fn compute_with_immediates<I: Pod>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        immediates: &I,
+        offset_index: u32,
+    ) {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        self.dispatch_with_immediates(&mut cpass, queue, immediates, offset_index);
+    }
+
+    /// 携带 immediate data 派发
+    ///
+    /// 回退为 uniform 缓冲区时需要通过 `queue` 写入数据；
+    /// 节点有动态 uniform 时，`offset_index` 是它们的偏移索引
+    pub fn dispatch_with_immediates<'a, 'b: 'a, I: Pod>(
+        &'b self,
+        cpass: &mut wgpu::ComputePass<'a>,
+        queue: &wgpu::Queue,
+        immediates: &I,
+        offset_index: u32,
+    ) {
+        let immediate = self
+            .immediate_data
+            .as_ref()
+            .expect("ComputeNode is built without immediate data");
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &self.bg_setting.bind_group, &[]);
+        if let Some(node) = &self.dy_uniform_bg {
+            cpass.set_bind_group(
+                1,
+                &node.bind_group,
+                &[256 * offset_index as wgpu::DynamicOffset],
+            );
+        }
+        // 回退模式下的 bind group 排在动态 uniform 之后
+        let group_index = if self.dy_uniform_bg.is_some() { 2 } else { 1 };
+        immediate.set_on_cpass(cpass, queue, group_index, immediates);
+        cpass.dispatch_workgroups(
+            self.workgroup_count.0,
+            self.workgroup_count.1,
+            self.workgroup_count.2,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::BindGroupData;
+
+    #[test]
+    fn immediates_with_dynamic_uniforms() {
+        let Some((device, queue)) = crate::test_util::device() else {
+            return;
+        };
+        // 超过 256 字节，回退模式下需要完整的窗口
+        const IMMEDIATE_SIZE: u32 = 272;
+        let shader = format!(
+            r#"
+struct Dynamic {{ value: vec4u }}
+struct Immediates {{ values: array<vec4u, 17> }}
+@group(0) @binding(0) var<storage, read_write> output: array<u32>;
+@group(1) @binding(0) var<uniform> dynamic: Dynamic;
+{}
+@compute @workgroup_size(1)
+fn cs_main() {{
+    output[0] = dynamic.value.x + immediates.values[16].w;
+}}
+"#,
+            ImmediateData::wgsl_declaration(&device, IMMEDIATE_SIZE, 2, "immediates", "Immediates")
+        );
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+        let output = BufferObj::create_empty_storage_buffer(
+            &device,
+            4,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            None,
+        );
+        let dynamic = BufferObj::create_empty_uniform_buffer(&device, 512, 16, true, None);
+        queue.write_buffer(&dynamic.buffer, 256, bytemuck::bytes_of(&[10u32, 0, 0, 0]));
+
+        let bg_data = BindGroupData {
+            workgroup_count: (1, 1, 1),
+            storage_buffers: vec![&output],
+            dynamic_uniforms: vec![&dynamic],
+            ..Default::default()
+        };
+        let node =
+            ComputeNode::new_with_immediates(&device, &bg_data, &shader_module, IMMEDIATE_SIZE)
+                .unwrap();
+
+        let mut immediates = [0u32; IMMEDIATE_SIZE as usize / 4];
+        immediates[67] = 7;
+        let scope = ValidationScope::push(&device);
+        let mut encoder = device.create_command_encoder(&Default::default());
+        node.compute_with_immediates(&mut encoder, &queue, &immediates, 1);
+        queue.submit(Some(encoder.finish()));
+        scope.pop().unwrap();
+
+        let result = pollster::block_on(output.read::<u32>(&device, &queue)).unwrap();
+        assert_eq!(result, [17]);
+    }
 }
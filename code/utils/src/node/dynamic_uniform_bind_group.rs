@@ -1,14 +1,28 @@
 use crate::BufferObj;
+use crate::resource_tracker::{ResourceCategory, TrackedResource};
 use std::vec::Vec;
 
 #[allow(dead_code)]
 pub struct DynamicUniformBindGroup {
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub bind_group: wgpu::BindGroup,
+    tracked: TrackedResource,
 }
 
 impl DynamicUniformBindGroup {
+    #[track_caller]
     pub fn new(device: &wgpu::Device, uniforms: Vec<(&BufferObj, wgpu::ShaderStages)>) -> Self {
+        let window = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        Self::with_window_size(device, uniforms, window)
+    }
+
+    /// 每个动态偏移处可见的窗口为 `window` 字节，数据超过偏移对齐（通常为 256 字节）时使用
+    #[track_caller]
+    pub fn with_window_size(
+        device: &wgpu::Device,
+        uniforms: Vec<(&BufferObj, wgpu::ShaderStages)>,
+        window: wgpu::BufferAddress,
+    ) -> Self {
         let mut layouts: Vec<wgpu::BindGroupLayoutEntry> = vec![];
         let mut entries: Vec<wgpu::BindGroupEntry> = vec![];
 
@@ -34,9 +48,7 @@ impl DynamicUniformBindGroup {
                     buffer: &buffer_obj.buffer,
                     offset: 0,
                     // size: buffer_obj.0.min_binding_size,
-                    size: wgpu::BufferSize::new(
-                        device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress,
-                    ),
+                    size: wgpu::BufferSize::new(window),
                 }),
             });
         }
@@ -54,6 +66,7 @@ impl DynamicUniformBindGroup {
         DynamicUniformBindGroup {
             bind_group_layout,
             bind_group,
+            tracked: TrackedResource::new(ResourceCategory::BindGroup, Some("dynamic uniform"), 0),
         }
     }
 }
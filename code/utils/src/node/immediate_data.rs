@@ -0,0 +1,167 @@
+use super::DynamicUniformBindGroup;
+use crate::{BufferObj, Error, Result};
+use bytemuck::Pod;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// 回退模式下 uniform 缓冲区的坑位数
+///
+/// 一次提交中，每个坑位只能被写入一次，否则之前的绘制也会读到后写入的数据
+const FALLBACK_SLOT_COUNT: u32 = 256;
+
+/// 节点的 immediate data（即 push constants）
+///
+/// 设备支持 `Features::IMMEDIATES` 时，数据通过 `set_immediates` 直接写入命令流；
+/// 否则自动回退为动态 uniform 缓冲区，每次设置数据都会占用缓冲区中的下一个坑位。
+///
+/// # NOTE:
+/// 两种模式下着色器里的声明不同，需使用 [`ImmediateData::wgsl_declaration`] 来生成对应的 WGSL 代码
+pub struct ImmediateData {
+    pub size: u32,
+    pub visibility: wgpu::ShaderStages,
+    fallback: Option<ImmediateFallback>,
+}
+
+struct ImmediateFallback {
+    buffer: BufferObj,
+    bind_group: DynamicUniformBindGroup,
+    slot_stride: u32,
+    cursor: AtomicU32,
+}
+
+#[allow(dead_code)]
+impl ImmediateData {
+    /// `size` 必须是 4 的非零倍数
+    pub fn new(device: &wgpu::Device, size: u32, visibility: wgpu::ShaderStages) -> Result<Self> {
+        if size == 0 || !size.is_multiple_of(4) {
+            return Err(Error::Validation(format!(
+                "immediate data 的大小必须是 4 的非零倍数，实际为 {size}"
+            )));
+        }
+        let fallback = if Self::is_supported(device, size) {
+            None
+        } else {
+            // 坑位需要满足动态偏移的对齐，同时能容纳完整的数据
+            let alignment = device.limits().min_uniform_buffer_offset_alignment;
+            let slot_stride = size.next_multiple_of(alignment);
+            let buffer = BufferObj::create_empty_uniform_buffer(
+                device,
+                (slot_stride * FALLBACK_SLOT_COUNT) as wgpu::BufferAddress,
+                size as u64,
+                true,
+                Some("immediate data fallback"),
+            );
+            // 窗口覆盖整个坑位，超过 256 字节的数据在着色器中也是完整的
+            let bind_group = DynamicUniformBindGroup::with_window_size(
+                device,
+                vec![(&buffer, visibility)],
+                slot_stride as wgpu::BufferAddress,
+            );
+            Some(ImmediateFallback {
+                buffer,
+                bind_group,
+                slot_stride,
+                cursor: AtomicU32::new(0),
+            })
+        };
+
+        Ok(Self {
+            size,
+            visibility,
+            fallback,
+        })
+    }
+
+    /// 设备是否能以原生 immediate data 的方式提供 `size` 字节的数据
+    pub fn is_supported(device: &wgpu::Device, size: u32) -> bool {
+        device.features().contains(wgpu::Features::IMMEDIATES)
+            && device.limits().max_immediate_size >= size
+    }
+
+    /// 生成着色器中的变量声明
+    ///
+    /// `fallback_group` 是回退模式下 uniform 缓冲区所在的 bind group 索引，
+    /// 它总是节点最后一个 bind group 的下一个索引
+    pub fn wgsl_declaration(
+        device: &wgpu::Device,
+        size: u32,
+        fallback_group: u32,
+        var_name: &str,
+        type_name: &str,
+    ) -> String {
+        if Self::is_supported(device, size) {
+            format!("var<immediate> {var_name}: {type_name};")
+        } else {
+            format!("@group({fallback_group}) @binding(0) var<uniform> {var_name}: {type_name};")
+        }
+    }
+
+    pub fn is_fallback(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    /// 原生模式下需要设置到 `PipelineLayoutDescriptor` 上的大小
+    pub(crate) fn pipeline_immediate_size(&self) -> u32 {
+        if self.fallback.is_some() {
+            0
+        } else {
+            self.size
+        }
+    }
+
+    /// 回退模式下需要追加到管线布局里的 bind group layout
+    pub(crate) fn fallback_bind_group_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.fallback
+            .as_ref()
+            .map(|fb| &fb.bind_group.bind_group_layout)
+    }
+
+    /// 写入当前坑位的数据，返回回退模式下的动态偏移
+    fn write_fallback<I: Pod>(fallback: &ImmediateFallback, queue: &wgpu::Queue, data: &I) -> u32 {
+        let slot = fallback.cursor.fetch_add(1, Ordering::Relaxed) % FALLBACK_SLOT_COUNT;
+        let offset = slot * fallback.slot_stride;
+        queue.write_buffer(
+            &fallback.buffer.buffer,
+            offset as wgpu::BufferAddress,
+            bytemuck::bytes_of(data),
+        );
+        offset
+    }
+
+    pub fn set_on_rpass<I: Pod>(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        queue: &wgpu::Queue,
+        group_index: u32,
+        data: &I,
+    ) {
+        assert!(
+            core::mem::size_of::<I>() as u32 <= self.size,
+            "immediate data is larger than the size the node was built with"
+        );
+        if let Some(fallback) = &self.fallback {
+            let offset = Self::write_fallback(fallback, queue, data);
+            rpass.set_bind_group(group_index, &fallback.bind_group.bind_group, &[offset]);
+        } else {
+            rpass.set_immediates(0, bytemuck::bytes_of(data));
+        }
+    }
+
+    pub fn set_on_cpass<I: Pod>(
+        &self,
+        cpass: &mut wgpu::ComputePass<'_>,
+        queue: &wgpu::Queue,
+        group_index: u32,
+        data: &I,
+    ) {
+        assert!(
+            core::mem::size_of::<I>() as u32 <= self.size,
+            "immediate data is larger than the size the node was built with"
+        );
+        if let Some(fallback) = &self.fallback {
+            let offset = Self::write_fallback(fallback, queue, data);
+            cpass.set_bind_group(group_index, &fallback.bind_group.bind_group, &[offset]);
+        } else {
+            cpass.set_immediates(0, bytemuck::bytes_of(data));
+        }
+    }
+}
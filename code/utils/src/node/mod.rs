@@ -7,8 +7,13 @@ pub use bind_group_setting::BindGroupSetting;
 mod dynamic_uniform_bind_group;
 pub use dynamic_uniform_bind_group::DynamicUniformBindGroup;
 
+mod immediate_data;
+pub use immediate_data::ImmediateData;
+
 mod view_node;
-pub use view_node::{ViewNode, ViewNodeBuilder};
+pub use view_node::{InstanceBufferData, ViewNode, ViewNodeBuilder};
+mod view_node_bundle;
+pub use view_node_bundle::{BundleItem, ViewNodeBundle};
 mod bufferless_fullscreen_node;
 pub use bufferless_fullscreen_node::BufferlessFullscreenNode;
 
@@ -1,17 +1,28 @@
-use super::{BindGroupData, BindGroupSetting};
+use super::{BindGroupData, BindGroupSetting, ImmediateData};
 use crate::BufferObj;
-use crate::DEPTH_FORMAT;
+use crate::resource_tracker::{ResourceCategory, TrackedResource};
 use crate::vertex::Vertex;
+use crate::{DEPTH_FORMAT, Result, ValidationScope};
 use bytemuck::Pod;
 use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU64, Ordering};
 use glam::{Vec2 as Size, Vec4 as Rect};
 use wgpu::util::DeviceExt;
 
+/// 逐实例（`VertexStepMode::Instance`）的顶点缓冲区数据
+pub struct InstanceBufferData {
+    pub data: Vec<u8>,
+    pub array_stride: wgpu::BufferAddress,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+    pub count: u32,
+}
+
 #[allow(dead_code)]
 pub struct NodeAttributes<'a, T: Vertex + Pod> {
     pub view_size: Size,
     pub vertices_and_indices: Option<(Vec<T>, Vec<u32>)>,
     pub vertex_buffer_layouts: Option<Vec<wgpu::VertexBufferLayout<'a>>>,
+    pub instance_buffers: Vec<InstanceBufferData>,
     pub bg_data: BindGroupData<'a>,
 
     pub tex_rect: Option<Rect>,
@@ -22,6 +33,8 @@ pub struct NodeAttributes<'a, T: Vertex + Pod> {
     pub cull_mode: Option<wgpu::Face>,
     pub use_depth_stencil: bool,
     pub shader_module: &'a wgpu::ShaderModule,
+    // immediate data 的字节数及可见的着色器阶段
+    pub immediate_data: Option<(u32, wgpu::ShaderStages)>,
 }
 
 pub struct ViewNodeBuilder<'a, T: Vertex + Pod> {
@@ -49,6 +62,7 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
                 view_size: (1.0, 1.0).into(),
                 vertices_and_indices: None,
                 vertex_buffer_layouts: None,
+                instance_buffers: vec![],
                 bg_data,
                 tex_rect: None,
                 corlor_format: None,
@@ -58,6 +72,7 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
                 cull_mode: Some(wgpu::Face::Back),
                 use_depth_stencil: true,
                 shader_module,
+                immediate_data: None,
             },
         }
     }
@@ -82,6 +97,7 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
         self
     }
 
+    /// 替换默认的逐顶点布局，[`with_instances`](Self::with_instances) 添加的实例布局仍会追加在其后
     pub fn with_vertex_buffer_layouts(
         mut self,
         layouts: Vec<wgpu::VertexBufferLayout<'a>>,
@@ -90,6 +106,24 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
         self
     }
 
+    /// 添加一个逐实例步进的顶点缓冲区
+    ///
+    /// 实例属性的 `shader_location` 从 `shader_location` 开始递增，
+    /// 缓冲区按添加顺序依次绑定在逐顶点缓冲区之后的槽位上
+    pub fn with_instances<I: Vertex + Pod>(
+        mut self,
+        instances: &[I],
+        shader_location: u32,
+    ) -> Self {
+        self.instance_buffers.push(InstanceBufferData {
+            data: bytemuck::cast_slice(instances).to_vec(),
+            array_stride: core::mem::size_of::<I>() as wgpu::BufferAddress,
+            attributes: I::vertex_attributes(shader_location),
+            count: instances.len() as u32,
+        });
+        self
+    }
+
     pub fn with_view_size(mut self, size: Size) -> Self {
         self.view_size = size;
         self
@@ -115,7 +149,14 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
         self
     }
 
-    pub fn build(self, device: &wgpu::Device) -> ViewNode {
+    pub fn with_immediate_data(mut self, size: u32, visibility: wgpu::ShaderStages) -> Self {
+        self.immediate_data = Some((size, visibility));
+        self
+    }
+
+    /// 着色器与顶点布局、绑定布局不匹配等 wgpu 验证错误会以 `Error::Validation` 返回
+    #[track_caller]
+    pub fn build(self, device: &wgpu::Device) -> Result<ViewNode> {
         debug_assert!(
             self.bg_data.visibilitys.len()
                 >= self.bg_data.uniforms.len()
@@ -124,7 +165,10 @@ impl<'a, T: Vertex + Pod> ViewNodeBuilder<'a, T> {
                     + self.bg_data.inout_tv.len(),
             "visibilitys count less than binding resource count"
         );
-        ViewNode::frome_attributes::<T>(self.attributes, device)
+        let scope = ValidationScope::push(device);
+        let node = ViewNode::frome_attributes::<T>(self.attributes, device)?;
+        scope.pop()?;
+        Ok(node)
     }
 }
 
@@ -134,9 +178,21 @@ pub struct ViewNode {
     pub vertex_count: usize,
     pub index_buf: wgpu::Buffer,
     pub index_count: usize,
+    pub instance_bufs: Vec<BufferObj>,
+    // 使用实例缓冲区时默认绘制的实例数
+    pub instance_count: u32,
+    // 第一个实例缓冲区的绑定槽位
+    instance_slot: u32,
     pub bg_setting: BindGroupSetting,
     pub dy_uniform_bg: Option<super::DynamicUniformBindGroup>,
+    pub immediate_data: Option<ImmediateData>,
     pub pipeline: wgpu::RenderPipeline,
+    color_format: wgpu::TextureFormat,
+    use_depth_stencil: bool,
+    // 几何数据或绑定每发生一次变化就会获得一个新的版本号，用于判断 RenderBundle 是否需要重新录制
+    version: u64,
+    index_tracked: TrackedResource,
+    pipeline_tracked: TrackedResource,
     view_width: f32,
     view_height: f32,
     pub clear_color: wgpu::Color,
@@ -144,10 +200,11 @@ pub struct ViewNode {
 
 #[allow(dead_code)]
 impl ViewNode {
+    #[track_caller]
     fn frome_attributes<T: Vertex + Pod>(
         attributes: NodeAttributes<T>,
         device: &wgpu::Device,
-    ) -> Self {
+    ) -> Result<Self> {
         let corlor_format = if let Some(format) = attributes.corlor_format {
             format
         } else {
@@ -173,26 +230,55 @@ impl ViewNode {
         let index_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("index buffer"),
             contents: bytemuck::cast_slice(&vi.1),
-            usage: wgpu::BufferUsages::INDEX,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
         });
 
+        let instance_bufs: Vec<BufferObj> = attributes
+            .instance_buffers
+            .iter()
+            .map(|instance| {
+                BufferObj::create_buffer(
+                    device,
+                    Some(&instance.data),
+                    None,
+                    wgpu::BufferUsages::VERTEX,
+                    Some("Instance buffer"),
+                )
+            })
+            .collect();
+        let instance_count = attributes
+            .instance_buffers
+            .first()
+            .map_or(1, |instance| instance.count);
+
         let default_layout_attributes = T::vertex_attributes(0);
-        let vertex_buffer_layouts = if let Some(layouts) = attributes.vertex_buffer_layouts {
+        let mut vertex_buffer_layouts = if let Some(layouts) = attributes.vertex_buffer_layouts {
             layouts
-        } else if core::mem::size_of::<T>() > 0 {
-            vec![wgpu::VertexBufferLayout {
-                array_stride: core::mem::size_of::<T>() as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: &default_layout_attributes,
-            }]
         } else {
-            vec![]
+            let mut layouts = vec![];
+            if core::mem::size_of::<T>() > 0 {
+                layouts.push(wgpu::VertexBufferLayout {
+                    array_stride: core::mem::size_of::<T>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &default_layout_attributes,
+                });
+            }
+            layouts
         };
+        // 实例缓冲区绑定在所有逐顶点布局之后的槽位上
+        let instance_slot = vertex_buffer_layouts.len() as u32;
+        for instance in attributes.instance_buffers.iter() {
+            vertex_buffer_layouts.push(wgpu::VertexBufferLayout {
+                array_stride: instance.array_stride,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &instance.attributes,
+            });
+        }
         let vertex_buffer_layouts = vertex_buffer_layouts
             .into_iter()
             .map(Some)
             .collect::<Vec<_>>();
-        let (dy_uniform_bg, pipeline_layout) = if !attributes.bg_data.dynamic_uniforms.is_empty() {
+        let dy_uniform_bg = if !attributes.bg_data.dynamic_uniforms.is_empty() {
             let uniforms = attributes
                 .bg_data
                 .dynamic_uniforms
@@ -200,24 +286,31 @@ impl ViewNode {
                 .zip(attributes.bg_data.dynamic_uniform_visibilitys)
                 .map(|(uniform, visi)| (*uniform, visi))
                 .collect();
-            let dy_bg = super::DynamicUniformBindGroup::new(device, uniforms);
-            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
-                bind_group_layouts: &[
-                    Some(&bg_setting.bind_group_layout),
-                    Some(&dy_bg.bind_group_layout),
-                ],
-                immediate_size: 0,
-            });
-            (Some(dy_bg), pipeline_layout)
+            Some(super::DynamicUniformBindGroup::new(device, uniforms))
         } else {
-            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
-                bind_group_layouts: &[Some(&bg_setting.bind_group_layout)],
-                immediate_size: 0,
-            });
-            (None, pipeline_layout)
+            None
         };
+        let immediate_data = attributes
+            .immediate_data
+            .map(|(size, visibility)| ImmediateData::new(device, size, visibility))
+            .transpose()?;
+
+        let mut bind_group_layouts = vec![Some(&bg_setting.bind_group_layout)];
+        if let Some(dy_bg) = dy_uniform_bg.as_ref() {
+            bind_group_layouts.push(Some(&dy_bg.bind_group_layout));
+        }
+        let mut immediate_size = 0;
+        if let Some(immediate) = immediate_data.as_ref() {
+            if let Some(layout) = immediate.fallback_bind_group_layout() {
+                bind_group_layouts.push(Some(layout));
+            }
+            immediate_size = immediate.pipeline_immediate_size();
+        }
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &bind_group_layouts,
+            immediate_size,
+        });
 
         // Create the render pipeline
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -262,18 +355,28 @@ impl ViewNode {
             cache: None,
         });
 
-        ViewNode {
+        let index_tracked = TrackedResource::buffer(&index_buf, Some("index buffer"));
+        Ok(ViewNode {
             view_width: attributes.view_size.x,
             view_height: attributes.view_size.y,
             vertex_buf,
             vertex_count,
             index_buf,
             index_count: vi.1.len(),
+            instance_bufs,
+            instance_count,
+            instance_slot,
             bg_setting,
             dy_uniform_bg,
+            immediate_data,
             pipeline,
+            color_format: corlor_format,
+            use_depth_stencil: attributes.use_depth_stencil,
+            version: next_version(),
+            index_tracked,
+            pipeline_tracked: TrackedResource::new(ResourceCategory::Pipeline, Some("view"), 0),
             clear_color: wgpu::Color::BLACK,
-        }
+        })
     }
 
     pub fn draw(
@@ -286,7 +389,7 @@ impl ViewNode {
     }
 
     pub fn draw_by_pass<'a, 'b: 'a>(&'b self, rpass: &mut wgpu::RenderPass<'b>) {
-        self.draw_rpass_by_offset(rpass, 0, 1);
+        self.draw_rpass_by_offset(rpass, 0, self.instance_count);
     }
 
     pub fn draw_by_instance_count<'a, 'b: 'a>(
@@ -318,7 +421,7 @@ impl ViewNode {
             ..Default::default()
         });
         self.set_rpass(&mut rpass);
-        self.draw_rpass_by_offset(&mut rpass, offset_index, 1);
+        self.draw_rpass_by_offset(&mut rpass, offset_index, self.instance_count);
     }
 
     pub fn draw_rpass_by_offset<'a, 'b: 'a>(
@@ -328,6 +431,223 @@ impl ViewNode {
         instance_count: u32,
     ) {
         self.set_rpass(rpass);
+        self.set_dynamic_offset(rpass, offset_index);
+        self.draw_elements(rpass, instance_count);
+    }
+
+    /// 使用任意的动态偏移绘制，比如 `FrameRing` 分配出的偏移
+    ///
+    /// `storage_offsets` 对应 bind group 0 中带动态偏移的存储缓冲区，
+    /// `uniform_offsets` 对应动态 uniform，都按绑定顺序排列
+    pub fn draw_rpass_by_dynamic_offsets<'a, 'b: 'a>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'b>,
+        storage_offsets: &[wgpu::DynamicOffset],
+        uniform_offsets: &[wgpu::DynamicOffset],
+        instance_count: u32,
+    ) {
+        self.set_rpass_with_offsets(rpass, storage_offsets);
+        if let Some(node) = &self.dy_uniform_bg {
+            rpass.set_bind_group(1, &node.bind_group, uniform_offsets);
+        }
+        self.draw_elements(rpass, instance_count);
+    }
+
+    /// 携带 immediate data 绘制
+    ///
+    /// 回退为 uniform 缓冲区时需要通过 `queue` 写入数据
+    pub fn draw_rpass_with_immediates<'a, 'b: 'a, I: Pod>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'b>,
+        queue: &wgpu::Queue,
+        immediates: &I,
+        offset_index: u32,
+        instance_count: u32,
+    ) {
+        self.set_rpass(rpass);
+        self.set_dynamic_offset(rpass, offset_index);
+        self.set_immediates(rpass, queue, immediates);
+        self.draw_elements(rpass, instance_count);
+    }
+
+    pub fn set_immediates<I: Pod>(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        queue: &wgpu::Queue,
+        immediates: &I,
+    ) {
+        let immediate = self
+            .immediate_data
+            .as_ref()
+            .expect("ViewNode is built without immediate data");
+        // 回退模式下的 bind group 排在动态 uniform 之后
+        let group_index = if self.dy_uniform_bg.is_some() { 2 } else { 1 };
+        immediate.set_on_rpass(rpass, queue, group_index, immediates);
+    }
+
+    /// 从 `indirect_buf` 读取绘制参数
+    ///
+    /// 有索引时参数布局为 `DrawIndexedIndirectArgs`，否则为 `DrawIndirectArgs`，
+    /// 实例数可以由计算着色器写入，无需回读到 CPU
+    pub fn draw_indirect<'a, 'b: 'a>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'b>,
+        indirect_buf: &'b BufferObj,
+        indirect_offset: wgpu::BufferAddress,
+    ) {
+        self.set_rpass(rpass);
+        self.set_dynamic_offset(rpass, 0);
+        if self.index_count > 0 {
+            rpass.draw_indexed_indirect(&indirect_buf.buffer, indirect_offset);
+        } else {
+            rpass.draw_indirect(&indirect_buf.buffer, indirect_offset);
+        }
+    }
+
+    /// 连续执行 `count` 组间接绘制参数
+    pub fn multi_draw_indirect<'a, 'b: 'a>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'b>,
+        indirect_buf: &'b BufferObj,
+        indirect_offset: wgpu::BufferAddress,
+        count: u32,
+    ) {
+        self.set_rpass(rpass);
+        self.set_dynamic_offset(rpass, 0);
+        if self.index_count > 0 {
+            rpass.multi_draw_indexed_indirect(&indirect_buf.buffer, indirect_offset, count);
+        } else {
+            rpass.multi_draw_indirect(&indirect_buf.buffer, indirect_offset, count);
+        }
+    }
+
+    /// 更新节点的几何数据
+    ///
+    /// 数据能放进现有缓冲区时原地写入，否则按增长策略重新分配缓冲区。
+    /// 重新分配后旧缓冲区里的数据不会保留，所以总是需要传入完整的顶点与索引
+    #[track_caller]
+    pub fn update_geometry<T: Vertex + Pod>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[T],
+        indices: &[u32],
+    ) {
+        let vertex_data: &[u8] = bytemuck::cast_slice(vertices);
+        let vertex_bytes = vertex_data.len() as wgpu::BufferAddress;
+        if vertex_bytes > 0 {
+            let capacity = self.vertex_buf.as_ref().map_or(0, |buf| buf.size);
+            if vertex_bytes > capacity {
+                let mut buf = BufferObj::create_empty_storage_buffer(
+                    device,
+                    grown_capacity(capacity, vertex_bytes),
+                    wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    Some("Vertex buffer"),
+                );
+                buf.min_binding_size =
+                    wgpu::BufferSize::new(core::mem::size_of::<T>() as wgpu::BufferAddress);
+                self.vertex_buf = Some(buf);
+            }
+            queue.write_buffer(&self.vertex_buf.as_ref().unwrap().buffer, 0, vertex_data);
+        }
+        self.vertex_count = vertices.len();
+
+        let index_data: &[u8] = bytemuck::cast_slice(indices);
+        let index_bytes = index_data.len() as wgpu::BufferAddress;
+        if index_bytes > self.index_buf.size() {
+            self.index_buf = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("index buffer"),
+                size: grown_capacity(self.index_buf.size(), index_bytes),
+                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.index_tracked = TrackedResource::buffer(&self.index_buf, Some("index buffer"));
+        }
+        if index_bytes > 0 {
+            queue.write_buffer(&self.index_buf, 0, index_data);
+        }
+        self.index_count = indices.len();
+        self.version = next_version();
+    }
+
+    /// 更新第 `index` 个实例缓冲区，新数据不能超出缓冲区大小
+    pub fn update_instances<I: Pod>(&mut self, queue: &wgpu::Queue, index: usize, instances: &[I]) {
+        let data: &[u8] = bytemuck::cast_slice(instances);
+        let instance_buf = &self.instance_bufs[index];
+        assert!(
+            data.len() as wgpu::BufferAddress <= instance_buf.size,
+            "instance data exceeds the instance buffer"
+        );
+        queue.write_buffer(&instance_buf.buffer, 0, data);
+        if index == 0 {
+            self.instance_count = instances.len() as u32;
+        }
+        self.version = next_version();
+    }
+
+    /// 使用新的绑定资源重建 bind group
+    ///
+    /// `bg_data` 中资源的种类与数量需与创建节点时保持一致
+    pub fn rebind(&mut self, device: &wgpu::Device, bg_data: &BindGroupData) {
+        self.bg_setting.bind_group = super::bufferless_fullscreen_node::create_bind_group(
+            device,
+            bg_data,
+            &self.bg_setting.bind_group_layout,
+        );
+        self.version = next_version();
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn color_format(&self) -> wgpu::TextureFormat {
+        self.color_format
+    }
+
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        if self.use_depth_stencil {
+            Some(DEPTH_FORMAT)
+        } else {
+            None
+        }
+    }
+
+    /// 将绘制命令录制到 RenderBundle 编码器中
+    pub fn record_bundle<'a>(
+        &'a self,
+        encoder: &mut wgpu::RenderBundleEncoder<'a>,
+        offset_index: u32,
+        instance_count: u32,
+    ) {
+        encoder.set_pipeline(&self.pipeline);
+        encoder.set_bind_group(0, &self.bg_setting.bind_group, &[]);
+        if let Some(node) = &self.dy_uniform_bg {
+            encoder.set_bind_group(
+                1,
+                &node.bind_group,
+                &[256 * offset_index as wgpu::DynamicOffset],
+            );
+        }
+        encoder.set_index_buffer(self.index_buf.slice(..), wgpu::IndexFormat::Uint32);
+        if let Some(vertex_buf) = self.vertex_buf.as_ref() {
+            encoder.set_vertex_buffer(0, vertex_buf.buffer.slice(..));
+        }
+        for (i, instance_buf) in self.instance_bufs.iter().enumerate() {
+            encoder.set_vertex_buffer(self.instance_slot + i as u32, instance_buf.buffer.slice(..));
+        }
+        if self.index_count > 0 {
+            encoder.draw_indexed(0..self.index_count as u32, 0, 0..instance_count);
+        } else {
+            encoder.draw(0..self.vertex_count as u32, 0..instance_count)
+        }
+    }
+
+    fn set_dynamic_offset<'a, 'b: 'a>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'b>,
+        offset_index: u32,
+    ) {
         if let Some(node) = &self.dy_uniform_bg {
             rpass.set_bind_group(
                 1,
@@ -335,6 +655,9 @@ impl ViewNode {
                 &[256 * offset_index as wgpu::DynamicOffset],
             );
         }
+    }
+
+    fn draw_elements(&self, rpass: &mut wgpu::RenderPass<'_>, instance_count: u32) {
         if self.index_count > 0 {
             rpass.draw_indexed(0..self.index_count as u32, 0, 0..instance_count);
         } else {
@@ -343,11 +666,37 @@ impl ViewNode {
     }
 
     pub fn set_rpass<'a, 'b: 'a>(&'b self, rpass: &mut wgpu::RenderPass<'a>) {
+        self.set_rpass_with_offsets(rpass, &[]);
+    }
+
+    /// 与 `set_rpass` 相同，`storage_offsets` 为 bind group 0 中带动态偏移的存储缓冲区的偏移
+    pub fn set_rpass_with_offsets<'a, 'b: 'a>(
+        &'b self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        storage_offsets: &[wgpu::DynamicOffset],
+    ) {
         rpass.set_pipeline(&self.pipeline);
-        rpass.set_bind_group(0, &self.bg_setting.bind_group, &[]);
+        rpass.set_bind_group(0, &self.bg_setting.bind_group, storage_offsets);
         rpass.set_index_buffer(self.index_buf.slice(..), wgpu::IndexFormat::Uint32);
         if let Some(vertex_buf) = self.vertex_buf.as_ref() {
             rpass.set_vertex_buffer(0, vertex_buf.buffer.slice(..));
         }
+        for (i, instance_buf) in self.instance_bufs.iter().enumerate() {
+            rpass.set_vertex_buffer(self.instance_slot + i as u32, instance_buf.buffer.slice(..));
+        }
     }
 }
+
+fn next_version() -> u64 {
+    static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// 缓冲区的增长策略：至少翻倍，以减少几何数据持续增长时的重新分配次数
+fn grown_capacity(
+    current: wgpu::BufferAddress,
+    required: wgpu::BufferAddress,
+) -> wgpu::BufferAddress {
+    let capacity = required.max(current * 2);
+    capacity.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+}
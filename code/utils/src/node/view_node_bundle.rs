@@ -0,0 +1,171 @@
+use super::ViewNode;
+use crate::{Error, Result};
+
+/// RenderBundle 中的一次绘制
+#[derive(Clone, Copy)]
+pub struct BundleItem<'a> {
+    pub node: &'a ViewNode,
+    pub offset_index: u32,
+    pub instance_count: u32,
+}
+
+impl<'a> BundleItem<'a> {
+    pub fn new(node: &'a ViewNode) -> Self {
+        Self {
+            node,
+            offset_index: 0,
+            instance_count: node.instance_count,
+        }
+    }
+}
+
+/// 将一组静态的 ViewNode 录制为 RenderBundle
+///
+/// 录制一次之后，每帧只需调用 `execute` 回放，不用再重复编码相同的管线、绑定与绘制命令。
+/// 每次 `prepare` 都会比对节点的版本号及绘制参数，节点的几何数据或绑定发生变化时自动重新录制。
+///
+/// # NOTE:
+/// 带有 immediate data 的节点每次绘制都需要新的数据，不能录制到 RenderBundle 中
+pub struct ViewNodeBundle {
+    label: Option<&'static str>,
+    // 录制时每个节点的（版本号, 动态偏移索引, 实例数）
+    recorded: Vec<(u64, u32, u32)>,
+    bundle: Option<wgpu::RenderBundle>,
+}
+
+#[allow(dead_code)]
+impl ViewNodeBundle {
+    pub fn new(label: Option<&'static str>) -> Self {
+        Self {
+            label,
+            recorded: vec![],
+            bundle: None,
+        }
+    }
+
+    /// 强制下一次 `prepare` 重新录制
+    pub fn invalidate(&mut self) {
+        self.bundle = None;
+    }
+
+    pub fn is_valid(&self, items: &[BundleItem]) -> bool {
+        self.bundle.is_some()
+            && self.recorded.len() == items.len()
+            && self
+                .recorded
+                .iter()
+                .zip(items)
+                .all(|(recorded, item)| *recorded == Self::item_key(item))
+    }
+
+    /// 必要时重新录制，返回是否发生了录制
+    ///
+    /// 包含带有 immediate data 的节点时返回 [`Error::Validation`]
+    pub fn prepare(&mut self, device: &wgpu::Device, items: &[BundleItem]) -> Result<bool> {
+        if items.iter().any(|item| item.node.immediate_data.is_some()) {
+            return Err(Error::Validation(
+                "带有 immediate data 的 ViewNode 不能录制到 RenderBundle 中".into(),
+            ));
+        }
+        if self.is_valid(items) {
+            return Ok(false);
+        }
+        let Some(first) = items.first() else {
+            self.recorded.clear();
+            self.bundle = None;
+            return Ok(false);
+        };
+        let color_format = first.node.color_format();
+        let depth_format = first.node.depth_format();
+        debug_assert!(
+            items
+                .iter()
+                .all(|item| item.node.color_format() == color_format
+                    && item.node.depth_format() == depth_format),
+            "all nodes in a render bundle must share the same attachment formats"
+        );
+
+        let mut encoder =
+            device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: self.label,
+                color_formats: &[Some(color_format)],
+                depth_stencil: depth_format.map(|format| wgpu::RenderBundleDepthStencil {
+                    format,
+                    depth_read_only: false,
+                    stencil_read_only: true,
+                }),
+                sample_count: 1,
+                multiview: None,
+            });
+        for item in items {
+            item.node
+                .record_bundle(&mut encoder, item.offset_index, item.instance_count);
+        }
+        self.bundle = Some(encoder.finish(&wgpu::RenderBundleDescriptor { label: self.label }));
+        self.recorded = items.iter().map(Self::item_key).collect();
+
+        Ok(true)
+    }
+
+    pub fn execute<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        if let Some(bundle) = self.bundle.as_ref() {
+            rpass.execute_bundles(core::iter::once(bundle));
+        }
+    }
+
+    fn item_key(item: &BundleItem) -> (u64, u32, u32) {
+        (item.node.version(), item.offset_index, item.instance_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{BindGroupData, ViewNodeBuilder};
+    use crate::vertex::PosOnly;
+
+    #[test]
+    fn nodes_with_immediates_are_rejected() {
+        let Some((device, _queue)) = crate::test_util::device() else {
+            return;
+        };
+        let shader = format!(
+            r#"
+struct Immediates {{ color: vec4f }}
+{}
+@vertex
+fn vs_main(@location(0) pos: vec3f) -> @builtin(position) vec4f {{
+    return vec4f(pos, 1.0);
+}}
+@fragment
+fn fs_main() -> @location(0) vec4f {{
+    return immediates.color;
+}}
+"#,
+            crate::node::ImmediateData::wgsl_declaration(
+                &device,
+                16,
+                1,
+                "immediates",
+                "Immediates"
+            )
+        );
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+        let geometry = (vec![PosOnly { pos: [0.0; 3] }; 3], vec![0, 1, 2]);
+        let node = ViewNodeBuilder::<PosOnly>::new(BindGroupData::default(), &shader_module)
+            .with_vertices_and_indices(geometry)
+            .with_use_depth_stencil(false)
+            .with_immediate_data(16, wgpu::ShaderStages::FRAGMENT)
+            .build(&device)
+            .unwrap();
+
+        let mut bundle = ViewNodeBundle::new(None);
+        assert!(matches!(
+            bundle.prepare(&device, &[BundleItem::new(&node)]),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,674 @@
+//! 拾取光标下的物体
+//!
+//! 两种方式都返回 [`PickHit`]：
+//! - CPU 射线检测：由光标位置与相机矩阵构造 [`Ray`]，与每个物体的 [`Bvh`] 求交，
+//!   适合物体数量不多、网格数据保留在 CPU 端的场景；
+//! - GPU ID 缓冲区：用 [`GpuPicker`] 的渲染目标把物体 ID 与法线画出来，再回读光标处的像素，
+//!   结果与屏幕上看到的完全一致（包括顶点着色器中的变形）。
+//!
+//! ```ignore
+//! let ray = Ray::from_camera(cursor, viewport, &camera);
+//! if let Some(hit) = scene.pick(&ray) {
+//!     log::info!("{} at {}", hit.object_id, hit.position);
+//! }
+//! ```
+
+use crate::Result;
+use crate::camera::{Camera, Projection};
+use crate::culling::Aabb;
+use crate::load_texture::AnyTexture;
+use glam::{Mat4, Vec2, Vec3};
+use std::sync::Arc;
+
+/// 拾取结果，位置与法线都在世界空间中，法线朝向观察者一侧
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickHit {
+    pub object_id: u32,
+    pub position: Vec3,
+    pub normal: Vec3,
+}
+
+/// 光标的像素坐标转换为 NDC，像素坐标原点在左上角
+pub fn cursor_to_ndc(cursor: Vec2, viewport: Vec2) -> Vec2 {
+    Vec2::new(
+        cursor.x / viewport.x * 2.0 - 1.0,
+        1.0 - cursor.y / viewport.y * 2.0,
+    )
+}
+
+/// 射线，`direction` 为单位向量
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+#[allow(dead_code)]
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// 由光标位置与视图投影矩阵构造射线，起点在近平面上
+    ///
+    /// 适用于深度范围为 [0, 1] 的常规投影，例如 `matrix_helper` 生成的矩阵
+    pub fn from_screen(cursor: Vec2, viewport: Vec2, view_proj: &Mat4) -> Self {
+        Self::unproject(
+            cursor_to_ndc(cursor, viewport),
+            &view_proj.inverse(),
+            0.0,
+            1.0,
+        )
+    }
+
+    /// 由光标位置与相机构造射线，支持反向 Z 的无穷远投影
+    pub fn from_camera(cursor: Vec2, viewport: Vec2, camera: &Camera) -> Self {
+        let ndc = cursor_to_ndc(cursor, viewport);
+        let inv = camera.view_projection().inverse();
+        match camera.projection {
+            // 深度 0 在无穷远处，取近平面与中间的一点确定方向
+            Projection::InfiniteReverseZ { .. } => Self::unproject(ndc, &inv, 1.0, 0.5),
+            _ => Self::unproject(ndc, &inv, 0.0, 1.0),
+        }
+    }
+
+    fn unproject(ndc: Vec2, inv_view_proj: &Mat4, near_depth: f32, far_depth: f32) -> Self {
+        let near = inv_view_proj.project_point3(ndc.extend(near_depth));
+        let far = inv_view_proj.project_point3(ndc.extend(far_depth));
+        Self::new(near, far - near)
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// 经矩阵变换后的射线，方向不重新归一化，因此参数 t 在变换前后一致
+    pub fn transformed(&self, m: &Mat4) -> Self {
+        Self {
+            origin: m.transform_point3(self.origin),
+            direction: m.transform_vector3(self.direction),
+        }
+    }
+
+    /// 与包围盒的进入、离开距离（slab 方法），不相交时返回 None
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<(f32, f32)> {
+        let inv = self.direction.recip();
+        let t0 = (aabb.min - self.origin) * inv;
+        let t1 = (aabb.max - self.origin) * inv;
+        let t_enter = t0.min(t1).max_element().max(0.0);
+        let t_exit = t0.max(t1).min_element();
+        (t_enter <= t_exit).then_some((t_enter, t_exit))
+    }
+
+    /// 与三角形求交（Möller–Trumbore），两面都可命中，返回距离与重心坐标 (u, v)
+    pub fn intersect_triangle(&self, [a, b, c]: [Vec3; 3]) -> Option<(f32, Vec2)> {
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.direction.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv_det;
+        (t >= 0.0).then_some((t, Vec2::new(u, v)))
+    }
+}
+
+/// 射线与单个网格的交点，均在网格的局部空间中
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshHit {
+    pub t: f32,
+    /// 原始索引缓冲区中的三角形序号
+    pub triangle: u32,
+    pub barycentric: Vec2,
+    /// 三角形的几何法线，朝向射线起点一侧
+    pub normal: Vec3,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    bounds: Aabb,
+    // 叶节点：三角形的起始位置与数量；内部节点：count 为 0，first 为左子节点，右子节点紧随其后
+    first: u32,
+    count: u32,
+}
+
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// 单个网格的三角形层次包围盒
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<[Vec3; 3]>,
+    triangle_ids: Vec<u32>,
+}
+
+#[allow(dead_code)]
+impl Bvh {
+    /// 由顶点位置与 triangle list 索引构建，按质心包围盒的最长轴取中位数划分
+    pub fn new(positions: &[[f32; 3]], indices: &[u32]) -> Self {
+        let triangles: Vec<[Vec3; 3]> = indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|i| Vec3::from(positions[i as usize])))
+            .collect();
+        let mut bvh = Self {
+            nodes: vec![],
+            triangle_ids: (0..triangles.len() as u32).collect(),
+            triangles,
+        };
+        if bvh.triangles.is_empty() {
+            return bvh;
+        }
+        let centroids: Vec<Vec3> = bvh
+            .triangles
+            .iter()
+            .map(|[a, b, c]| (*a + *b + *c) / 3.0)
+            .collect();
+        bvh.nodes.push(BvhNode {
+            bounds: Aabb::EMPTY,
+            first: 0,
+            count: bvh.triangles.len() as u32,
+        });
+        bvh.subdivide(0, &centroids);
+
+        // 按叶节点的顺序重排三角形
+        bvh.triangles = bvh
+            .triangle_ids
+            .iter()
+            .map(|&id| bvh.triangles[id as usize])
+            .collect();
+        bvh
+    }
+
+    fn subdivide(&mut self, node: usize, centroids: &[Vec3]) {
+        let BvhNode { first, count, .. } = self.nodes[node];
+        let range = first as usize..(first + count) as usize;
+        let ids = &mut self.triangle_ids[range.clone()];
+        let triangles = &self.triangles;
+        self.nodes[node].bounds =
+            Aabb::from_points(ids.iter().flat_map(|&id| triangles[id as usize]));
+        if ids.len() <= MAX_LEAF_TRIANGLES {
+            return;
+        }
+        let centroid_bounds = Aabb::from_points(ids.iter().map(|&id| centroids[id as usize]));
+        let extent = centroid_bounds.max - centroid_bounds.min;
+        let axis = extent.max_position();
+        if extent[axis] <= 0.0 {
+            return;
+        }
+        let mid = ids.len() / 2;
+        ids.select_nth_unstable_by(mid, |&a, &b| {
+            centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis])
+        });
+
+        let left = self.nodes.len();
+        for (first, count) in [
+            (first, mid as u32),
+            (first + mid as u32, count - mid as u32),
+        ] {
+            self.nodes.push(BvhNode {
+                bounds: Aabb::EMPTY,
+                first,
+                count,
+            });
+        }
+        self.nodes[node].first = left as u32;
+        self.nodes[node].count = 0;
+        self.subdivide(left, centroids);
+        self.subdivide(left + 1, centroids);
+    }
+
+    /// 整个网格的包围盒
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |node| node.bounds)
+    }
+
+    /// 最近的交点
+    pub fn raycast(&self, ray: &Ray) -> Option<MeshHit> {
+        let root = self.nodes.first()?;
+        ray.intersect_aabb(&root.bounds)?;
+        let mut best: Option<MeshHit> = None;
+        let mut stack = vec![0u32];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index as usize];
+            let best_t = best.map_or(f32::INFINITY, |hit| hit.t);
+            match ray.intersect_aabb(&node.bounds) {
+                Some((t_enter, _)) if t_enter <= best_t => {}
+                _ => continue,
+            }
+            if node.count > 0 {
+                for i in node.first..node.first + node.count {
+                    let tri = self.triangles[i as usize];
+                    if let Some((t, barycentric)) = ray.intersect_triangle(tri)
+                        && t < best.map_or(f32::INFINITY, |hit| hit.t)
+                    {
+                        let normal = (tri[1] - tri[0]).cross(tri[2] - tri[0]).normalize_or_zero();
+                        best = Some(MeshHit {
+                            t,
+                            triangle: self.triangle_ids[i as usize],
+                            barycentric,
+                            normal: if normal.dot(ray.direction) > 0.0 {
+                                -normal
+                            } else {
+                                normal
+                            },
+                        });
+                    }
+                }
+            } else {
+                // 先处理较近的子节点：后入栈先出栈
+                let (left, right) = (node.first, node.first + 1);
+                let enter = |i: u32| {
+                    ray.intersect_aabb(&self.nodes[i as usize].bounds)
+                        .map_or(f32::INFINITY, |(t, _)| t)
+                };
+                if enter(left) <= enter(right) {
+                    stack.extend([right, left]);
+                } else {
+                    stack.extend([left, right]);
+                }
+            }
+        }
+        best
+    }
+}
+
+struct PickObject {
+    id: u32,
+    bvh: Arc<Bvh>,
+    inv_model: Mat4,
+    world_bounds: Aabb,
+}
+
+/// CPU 射线拾取的场景：若干带有模型矩阵的网格，同一个 [`Bvh`] 可被多个实例共享
+#[derive(Default)]
+pub struct PickScene {
+    objects: Vec<PickObject>,
+}
+
+#[allow(dead_code)]
+impl PickScene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, object_id: u32, bvh: Arc<Bvh>, model: Mat4) {
+        let world_bounds = bvh.bounds().transformed(&model);
+        self.objects.push(PickObject {
+            id: object_id,
+            bvh,
+            inv_model: model.inverse(),
+            world_bounds,
+        });
+    }
+
+    /// 更新物体的模型矩阵，物体不存在时返回 false
+    pub fn set_transform(&mut self, object_id: u32, model: Mat4) -> bool {
+        let Some(object) = self.objects.iter_mut().find(|o| o.id == object_id) else {
+            return false;
+        };
+        object.inv_model = model.inverse();
+        object.world_bounds = object.bvh.bounds().transformed(&model);
+        true
+    }
+
+    pub fn remove(&mut self, object_id: u32) {
+        self.objects.retain(|o| o.id != object_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// 射线命中的最近物体
+    pub fn pick(&self, ray: &Ray) -> Option<PickHit> {
+        let mut best: Option<(f32, PickHit)> = None;
+        for object in &self.objects {
+            let best_t = best.map_or(f32::INFINITY, |(t, _)| t);
+            match ray.intersect_aabb(&object.world_bounds) {
+                Some((t_enter, _)) if t_enter <= best_t => {}
+                _ => continue,
+            }
+            // 射线变换到局部空间，方向不归一化，t 与世界空间一致
+            let local_ray = ray.transformed(&object.inv_model);
+            let Some(hit) = object.bvh.raycast(&local_ray) else {
+                continue;
+            };
+            if hit.t < best_t {
+                let normal = object
+                    .inv_model
+                    .transpose()
+                    .transform_vector3(hit.normal)
+                    .normalize_or_zero();
+                best = Some((
+                    hit.t,
+                    PickHit {
+                        object_id: object.id,
+                        position: ray.at(hit.t),
+                        normal,
+                    },
+                ));
+            }
+        }
+        best.map(|(_, hit)| hit)
+    }
+}
+
+/// GPU ID 缓冲区拾取
+///
+/// 用 [`color_targets`](Self::color_targets) 与 [`depth_stencil`](Self::depth_stencil) 创建拾取管线，
+/// 在 [`begin_pass`](Self::begin_pass) 返回的通道中绘制物体，两者都需传入相机的投影，
+/// 使深度测试与清除值和投影方式（包括反向 Z）一致。片元着色器输出：
+/// ```wgsl
+/// struct PickOutput {
+///     // 0 保留为“没有物体”
+///     @location(0) object_id: u32,
+///     // xyz 为世界空间法线
+///     @location(1) normal: vec4f,
+/// }
+/// ```
+/// 之后调用 [`pick`](Self::pick) 回读光标处的像素。
+pub struct GpuPicker {
+    id: AnyTexture,
+    normal: AnyTexture,
+    depth: AnyTexture,
+}
+
+#[allow(dead_code)]
+impl GpuPicker {
+    pub const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+    pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    /// ID 缓冲区的清除值，表示该像素没有物体
+    pub const NO_OBJECT: u32 = 0;
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let create = |format, label| {
+            let tex = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: width.max(1),
+                    height: height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let view = tex.create_view(&wgpu::TextureViewDescriptor::default());
+            AnyTexture::from_parts(tex, view, wgpu::TextureViewDimension::D2, Some(label))
+        };
+        Self {
+            id: create(Self::ID_FORMAT, "picking id"),
+            normal: create(Self::NORMAL_FORMAT, "picking normal"),
+            depth: create(Self::DEPTH_FORMAT, "picking depth"),
+        }
+    }
+
+    /// 窗口大小变化时重建渲染目标
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.id.size.width != width.max(1) || self.id.size.height != height.max(1) {
+            *self = Self::new(device, width, height);
+        }
+    }
+
+    /// 拾取管线的颜色目标
+    pub fn color_targets() -> [Option<wgpu::ColorTargetState>; 2] {
+        [Self::ID_FORMAT, Self::NORMAL_FORMAT].map(|format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+    }
+
+    /// 拾取管线的深度状态，比较函数由投影决定
+    pub fn depth_stencil(projection: &Projection) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: Self::DEPTH_FORMAT,
+            depth_write_enabled: Some(true),
+            depth_compare: Some(projection.depth_compare()),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    /// 清除拾取目标并开始渲染通道，深度按投影的清除值清除
+    pub fn begin_pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        projection: &Projection,
+    ) -> wgpu::RenderPass<'e> {
+        let attachment = |view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("picking pass"),
+            color_attachments: &[
+                attachment(&self.id.tex_view),
+                attachment(&self.normal.tex_view),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth.tex_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(projection.depth_clear_value()),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        })
+    }
+
+    /// 回读光标处的像素，`view_proj` 须与绘制拾取通道时使用的矩阵一致，用于由深度重建世界坐标
+    pub async fn pick(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cursor: Vec2,
+        view_proj: &Mat4,
+    ) -> Result<Option<PickHit>> {
+        let (width, height) = (self.id.size.width, self.id.size.height);
+        if cursor.x < 0.0 || cursor.y < 0.0 {
+            return Ok(None);
+        }
+        let (x, y) = (cursor.x as u32, cursor.y as u32);
+        if x >= width || y >= height {
+            return Ok(None);
+        }
+
+        // 三张纹理各复制一个像素，按复制对齐要求间隔存放
+        let stride = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64;
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("picking staging buffer"),
+            size: stride * 3,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("picking readback encoder"),
+        });
+        let sources = [
+            (&self.id.tex, wgpu::TextureAspect::All),
+            (&self.normal.tex, wgpu::TextureAspect::All),
+            (&self.depth.tex, wgpu::TextureAspect::DepthOnly),
+        ];
+        for (i, (texture, aspect)) in sources.into_iter().enumerate() {
+            encoder.copy_texture_to_buffer(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect,
+                },
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &staging,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset: stride * i as u64,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        queue.submit(Some(encoder.finish()));
+        let data = crate::readback::map_staging_buffer(device, &staging).await?;
+
+        let stride = stride as usize;
+        let object_id = u32::from_ne_bytes(data[..4].try_into().unwrap());
+        if object_id == Self::NO_OBJECT {
+            return Ok(None);
+        }
+        let normal = Vec3::from_array(std::array::from_fn(|i| {
+            let offset = stride + i * 2;
+            half::f16::from_ne_bytes([data[offset], data[offset + 1]]).to_f32()
+        }));
+        let depth = f32::from_ne_bytes(data[stride * 2..stride * 2 + 4].try_into().unwrap());
+
+        // 取像素中心重建世界坐标
+        let viewport = Vec2::new(width as f32, height as f32);
+        let ndc = cursor_to_ndc(Vec2::new(x as f32, y as f32) + 0.5, viewport);
+        let position = view_proj.inverse().project_point3(ndc.extend(depth));
+        Ok(Some(PickHit {
+            object_id,
+            position,
+            normal: normal.normalize_or_zero(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{Cuboid, UvSphere};
+
+    fn bvh_of(mesh: &crate::shapes::ShapeMesh) -> Bvh {
+        let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.pos).collect();
+        Bvh::new(&positions, &mesh.indices)
+    }
+
+    #[test]
+    fn screen_center_ray_looks_forward() {
+        let proj = Mat4::perspective_rh(45f32.to_radians(), 2.0, 0.1, 100.0);
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let viewport = Vec2::new(800.0, 400.0);
+        let ray = Ray::from_screen(viewport * 0.5, viewport, &(proj * view));
+        assert!(ray.direction.abs_diff_eq(Vec3::NEG_Z, 1e-4));
+        assert!((ray.origin.z - 4.9).abs() < 1e-3);
+
+        // 反向 Z 的无穷远投影
+        let camera = Camera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Projection::infinite_reverse_z(45.0, 0.1),
+            800,
+            400,
+        )
+        .looking_at(Vec3::ZERO);
+        let ray = Ray::from_camera(Vec2::new(800.0, 200.0), viewport, &camera);
+        assert!(ray.direction.z < 0.0 && ray.direction.x > 0.0);
+        assert!((ray.origin.z - 4.9).abs() < 1e-2);
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mesh = UvSphere::default().generate();
+        let bvh = bvh_of(&mesh);
+        let triangles: Vec<[Vec3; 3]> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| Vec3::from(mesh.vertices[i as usize].pos)))
+            .collect();
+        for i in 0..64 {
+            let a = i as f32 * 0.61;
+            let origin = Vec3::new(a.cos() * 2.0, (a * 0.7).sin(), a.sin() * 2.0);
+            let target = Vec3::new((a * 1.3).sin() * 0.3, (a * 2.1).cos() * 0.3, 0.1);
+            let ray = Ray::new(origin, target - origin);
+            let expected = triangles
+                .iter()
+                .enumerate()
+                .filter_map(|(i, tri)| ray.intersect_triangle(*tri).map(|(t, _)| (t, i as u32)))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            let hit = bvh.raycast(&ray);
+            assert_eq!(hit.map(|h| h.triangle), expected.map(|e| e.1));
+            if let Some(hit) = hit {
+                assert!(hit.normal.dot(ray.direction) <= 0.0);
+                assert!((ray.at(hit.t).length() - 0.5).abs() < 0.02);
+            }
+        }
+    }
+
+    #[test]
+    fn scene_picks_nearest_transformed_object() {
+        let cube = Arc::new(bvh_of(&Cuboid::default().generate()));
+        let mut scene = PickScene::new();
+        scene.add(
+            1,
+            cube.clone(),
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -5.0)),
+        );
+        scene.add(
+            2,
+            cube,
+            Mat4::from_scale_rotation_translation(
+                Vec3::splat(2.0),
+                glam::Quat::from_rotation_y(0.3),
+                Vec3::new(0.0, 0.0, -10.0),
+            ),
+        );
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::NEG_Z);
+        let hit = scene.pick(&ray).unwrap();
+        assert_eq!(hit.object_id, 1);
+        assert!(hit.position.abs_diff_eq(Vec3::new(0.0, 0.0, -4.5), 1e-4));
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-4));
+
+        scene.remove(1);
+        let hit = scene.pick(&ray).unwrap();
+        assert_eq!(hit.object_id, 2);
+        let expected_normal = glam::Quat::from_rotation_y(0.3) * Vec3::Z;
+        assert!(hit.normal.abs_diff_eq(expected_normal, 1e-4));
+
+        assert!(scene.set_transform(2, Mat4::from_translation(Vec3::X * 10.0)));
+        assert!(scene.pick(&ray).is_none());
+    }
+}
@@ -3,8 +3,8 @@ use crate::vertex::PosTex;
 pub struct Plane {
     width: f32,
     height: f32,
-    x_offset: f32,
-    y_offset: f32,
+    /// 左下角的坐标，为 None 时以原点为中心
+    offset: Option<glam::Vec2>,
     h_segments: u32,
     v_segments: u32,
 }
@@ -14,13 +14,25 @@ impl Plane {
         Plane {
             width: 2.0,
             height: 2.0,
-            x_offset: 0.0,
-            y_offset: 0.0,
+            offset: None,
             h_segments,
             v_segments,
         }
     }
 
+    /// 默认为 2x2，与裁剪空间一致
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// 左下角的坐标，默认以原点为中心
+    pub fn with_offset(mut self, x_offset: f32, y_offset: f32) -> Self {
+        self.offset = Some(glam::Vec2::new(x_offset, y_offset));
+        self
+    }
+
     // 支持指定纹理区域
     pub fn generate_vertices(&self) -> (Vec<PosTex>, Vec<u32>) {
         // z，w 表示宽高
@@ -51,19 +63,11 @@ impl Plane {
 
     //  最左边的 x 坐标
     fn most_left_x(&self) -> f32 {
-        if self.x_offset != 0.0 {
-            self.x_offset
-        } else {
-            -self.half_width()
-        }
+        self.offset.map_or(-self.half_width(), |offset| offset.x)
     }
     // 最下边的 y 坐标
     fn most_bottom_y(&self) -> f32 {
-        if self.y_offset != 0.0 {
-            self.y_offset
-        } else {
-            -self.half_height()
-        }
+        self.offset.map_or(-self.half_height(), |offset| offset.y)
     }
 
     // 返回的是 triangle list，而不是 triangle strip
@@ -95,3 +99,28 @@ impl Plane {
         self.height / 2.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Plane;
+
+    fn corners(plane: &Plane) -> ([f32; 3], [f32; 3]) {
+        let (vertices, _) = plane.generate_vertices();
+        (vertices[0].pos, vertices[vertices.len() - 1].pos)
+    }
+
+    #[test]
+    fn default_plane_is_centered() {
+        let plane = Plane::new(2, 2).with_size(4.0, 2.0);
+        assert_eq!(corners(&plane), ([-2.0, -1.0, 0.0], [2.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn zero_offset_puts_corner_at_origin() {
+        let plane = Plane::new(2, 2).with_size(4.0, 2.0).with_offset(0.0, 0.0);
+        assert_eq!(corners(&plane), ([0.0, 0.0, 0.0], [4.0, 2.0, 0.0]));
+
+        let plane = Plane::new(1, 1).with_offset(0.0, 3.0);
+        assert_eq!(corners(&plane), ([0.0, 3.0, 0.0], [2.0, 5.0, 0.0]));
+    }
+}
@@ -0,0 +1,95 @@
+//! 从 GPU 回读数据
+//!
+//! 数据先被复制到一个 MAP_READ 的暂存缓冲区，映射之后再拷贝到 CPU 内存。
+//! 原生平台需要调用 `device.poll` 来驱动映射回调，web 端由浏览器的事件循环驱动。
+use crate::{Error, Result};
+
+/// 映射暂存缓冲区并返回其中的全部字节
+pub(crate) async fn map_staging_buffer(
+    device: &wgpu::Device,
+    staging: &wgpu::Buffer,
+) -> Result<Vec<u8>> {
+    let buffer_slice = staging.slice(..);
+
+    // 注意：必须在 await future 之前先创建映射，然后再调用 device.poll()。
+    // 否则，应用程序将停止响应。
+    let (tx, rx) = flume::bounded(1);
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    // web 端的 poll 是空操作
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .map_err(|e| Error::Readback(e.to_string()))?;
+
+    match rx.recv_async().await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return Err(Error::Readback(e.to_string())),
+        // 回调没有被调用就被丢弃了，通常是因为设备已丢失
+        Err(_) => return Err(Error::Readback("设备已丢失".into())),
+    }
+    let data = buffer_slice
+        .get_mapped_range()
+        .map_err(|e| Error::Readback(e.to_string()))?
+        .to_vec();
+    // 须确保在解除缓冲区映射之前已删除所有已映射的视图
+    staging.unmap();
+
+    Ok(data)
+}
+
+/// 纹理复制到缓冲区时，每行字节数需按 `COPY_BYTES_PER_ROW_ALIGNMENT` 对齐
+pub(crate) fn padded_bytes_per_row(unpadded_bytes_per_row: u32) -> u32 {
+    unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// 去掉每行末尾用于对齐的填充字节
+pub(crate) fn remove_row_padding(
+    padded: &[u8],
+    padded_bytes_per_row: u32,
+    unpadded_bytes_per_row: u32,
+) -> Vec<u8> {
+    if padded_bytes_per_row == unpadded_bytes_per_row {
+        return padded.to_vec();
+    }
+    padded
+        .chunks(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..unpadded_bytes_per_row as usize])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_padded_to_copy_alignment() {
+        assert_eq!(padded_bytes_per_row(256), 256);
+        assert_eq!(padded_bytes_per_row(4), 256);
+        assert_eq!(padded_bytes_per_row(257), 512);
+    }
+
+    #[test]
+    fn row_padding_is_removed() {
+        // 2 行，每行 3 个有效字节，填充到 5 字节
+        let padded = [1, 2, 3, 0, 0, 4, 5, 6, 0, 0];
+        assert_eq!(remove_row_padding(&padded, 5, 3), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(remove_row_padding(&padded, 5, 5), padded);
+    }
+
+    #[test]
+    fn buffers_from_fixed_usage_constructors_can_be_read() {
+        let Some((device, queue)) = crate::test_util::device() else {
+            return;
+        };
+        let uniform = crate::BufferObj::create_uniform_buffer(&device, &[1u32, 2, 3, 4], None);
+        let storage = crate::BufferObj::create_storage_buffer(&device, &[5.0f32, 6.0], None);
+        let empty = crate::BufferObj::create_empty_uniform_buffer(&device, 16, 16, false, None);
+        let read = |buf: &crate::BufferObj| pollster::block_on(buf.read::<u32>(&device, &queue));
+        assert_eq!(read(&uniform).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(read(&empty).unwrap(), [0; 4]);
+        let storage = pollster::block_on(storage.read::<f32>(&device, &queue)).unwrap();
+        assert_eq!(storage, [5.0, 6.0]);
+    }
+}
@@ -0,0 +1,213 @@
+//! GPU 资源登记与显存统计
+//!
+//! utils 创建的缓冲区、纹理、bind group 与管线都会登记在全局登记表中，
+//! 资源对象被 drop 时自动注销。可以随时输出按类别汇总的显存报告，
+//! 也可以在 app 销毁之后检查仍未注销的资源以发现泄漏。
+
+use core::fmt;
+use core::panic::Location;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceCategory {
+    Vertex,
+    Index,
+    Uniform,
+    Storage,
+    Indirect,
+    Staging,
+    Texture,
+    RenderTarget,
+    BindGroup,
+    Pipeline,
+    Other,
+}
+
+impl ResourceCategory {
+    /// 根据缓冲区用途推断类别
+    pub fn from_buffer_usage(usage: wgpu::BufferUsages) -> Self {
+        if usage.contains(wgpu::BufferUsages::VERTEX) {
+            Self::Vertex
+        } else if usage.contains(wgpu::BufferUsages::INDEX) {
+            Self::Index
+        } else if usage.contains(wgpu::BufferUsages::INDIRECT) {
+            Self::Indirect
+        } else if usage.contains(wgpu::BufferUsages::UNIFORM) {
+            Self::Uniform
+        } else if usage.contains(wgpu::BufferUsages::STORAGE) {
+            Self::Storage
+        } else if usage.intersects(wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::MAP_WRITE) {
+            Self::Staging
+        } else {
+            Self::Other
+        }
+    }
+
+    /// 根据纹理用途推断类别
+    pub fn from_texture_usage(usage: wgpu::TextureUsages) -> Self {
+        if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+            Self::RenderTarget
+        } else {
+            Self::Texture
+        }
+    }
+}
+
+/// 一条资源登记信息
+#[derive(Clone, Debug)]
+pub struct ResourceInfo {
+    pub category: ResourceCategory,
+    pub label: String,
+    pub size: u64,
+    /// 创建资源的代码位置
+    pub creator: &'static Location<'static>,
+}
+
+struct Registry {
+    next_id: u64,
+    resources: BTreeMap<u64, ResourceInfo>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 1,
+    resources: BTreeMap::new(),
+});
+
+/// 资源的登记凭证，被 drop 时从登记表中注销
+pub struct TrackedResource {
+    id: u64,
+}
+
+impl TrackedResource {
+    #[track_caller]
+    pub fn new(category: ResourceCategory, label: Option<&str>, size: u64) -> Self {
+        let creator = Location::caller();
+        let mut registry = REGISTRY.lock();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.resources.insert(
+            id,
+            ResourceInfo {
+                category,
+                label: label.unwrap_or("unlabeled").to_string(),
+                size,
+                creator,
+            },
+        );
+        Self { id }
+    }
+
+    #[track_caller]
+    pub fn buffer(buffer: &wgpu::Buffer, label: Option<&str>) -> Self {
+        Self::new(
+            ResourceCategory::from_buffer_usage(buffer.usage()),
+            label,
+            buffer.size(),
+        )
+    }
+
+    #[track_caller]
+    pub fn texture(texture: &wgpu::Texture, label: Option<&str>) -> Self {
+        Self::new(
+            ResourceCategory::from_texture_usage(texture.usage()),
+            label,
+            texture_bytes(texture),
+        )
+    }
+}
+
+impl Drop for TrackedResource {
+    fn drop(&mut self) {
+        REGISTRY.lock().resources.remove(&self.id);
+    }
+}
+
+/// 估算纹理占用的字节数（含所有 mip 层级与多重采样）
+fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    // 深度模板组合格式没有统一的块大小，按 4 字节估算
+    let block_bytes = format.block_copy_size(None).unwrap_or(4) as u64;
+    let size = texture.size();
+    let is_3d = texture.dimension() == wgpu::TextureDimension::D3;
+
+    let mut bytes = 0;
+    for level in 0..texture.mip_level_count() {
+        let width = (size.width >> level).max(1).div_ceil(block_width) as u64;
+        let height = (size.height >> level).max(1).div_ceil(block_height) as u64;
+        let depth = if is_3d {
+            (size.depth_or_array_layers >> level).max(1)
+        } else {
+            size.depth_or_array_layers
+        } as u64;
+        bytes += width * height * depth * block_bytes;
+    }
+    bytes * texture.sample_count() as u64
+}
+
+/// 按类别汇总的显存报告
+pub struct MemoryReport {
+    /// 每个类别的（资源数, 字节数）
+    pub totals: BTreeMap<ResourceCategory, (usize, u64)>,
+    pub total_bytes: u64,
+    pub resources: Vec<ResourceInfo>,
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "GPU 资源: {} 个, 共 {:.2}MB",
+            self.resources.len(),
+            mb(self.total_bytes)
+        )?;
+        for (category, (count, bytes)) in self.totals.iter() {
+            writeln!(f, "  {category:?}: {count} 个, {:.2}MB", mb(*bytes))?;
+        }
+        Ok(())
+    }
+}
+
+fn mb(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0
+}
+
+pub fn memory_report() -> MemoryReport {
+    let registry = REGISTRY.lock();
+    let mut totals: BTreeMap<ResourceCategory, (usize, u64)> = BTreeMap::new();
+    let mut total_bytes = 0;
+    for info in registry.resources.values() {
+        let entry = totals.entry(info.category).or_default();
+        entry.0 += 1;
+        entry.1 += info.size;
+        total_bytes += info.size;
+    }
+
+    MemoryReport {
+        totals,
+        total_bytes,
+        resources: registry.resources.values().cloned().collect(),
+    }
+}
+
+pub fn log_memory_report() {
+    log::info!("{}", memory_report());
+}
+
+/// 输出所有仍未注销的资源，返回资源数
+///
+/// 应在 app 被销毁之后调用，此时还存活的资源即为泄漏
+pub fn report_leaks() -> usize {
+    let report = memory_report();
+    for info in report.resources.iter() {
+        log::warn!(
+            "GPU 资源泄漏: {:?} \"{}\" {} bytes, 创建于 {}",
+            info.category,
+            info.label,
+            info.size,
+            info.creator
+        );
+    }
+    report.resources.len()
+}
@@ -0,0 +1,300 @@
+use crate::{Error, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use wgpu::{AddressMode, CompareFunction, FilterMode, MipmapFilterMode, SamplerBorderColor};
+
+/// 采样器描述，可作为 [`SamplerCache`] 的键
+///
+/// ```ignore
+/// let desc = SamplerDesc::linear()
+///     .address_mode(wgpu::AddressMode::Repeat)
+///     .anisotropy(8);
+/// let sampler = cache.get(&desc)?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDesc {
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub address_mode_w: AddressMode,
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub mipmap_filter: MipmapFilterMode,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    pub anisotropy_clamp: u16,
+    pub compare: Option<CompareFunction>,
+    pub border_color: Option<SamplerBorderColor>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::nearest()
+    }
+}
+
+// lod 为 NaN 的描述无法通过验证，不会进入缓存
+impl Eq for SamplerDesc {}
+
+impl Hash for SamplerDesc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address_mode_u.hash(state);
+        self.address_mode_v.hash(state);
+        self.address_mode_w.hash(state);
+        self.mag_filter.hash(state);
+        self.min_filter.hash(state);
+        self.mipmap_filter.hash(state);
+        self.lod_min_clamp.to_bits().hash(state);
+        self.lod_max_clamp.to_bits().hash(state);
+        self.anisotropy_clamp.hash(state);
+        self.compare.hash(state);
+        self.border_color.hash(state);
+    }
+}
+
+#[allow(dead_code)]
+impl SamplerDesc {
+    /// 边缘截取，最近点采样
+    pub fn nearest() -> Self {
+        Self {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: MipmapFilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            anisotropy_clamp: 1,
+            compare: None,
+            border_color: None,
+        }
+    }
+
+    /// 边缘截取，mip 内与 mip 之间都做线性插值
+    pub fn linear() -> Self {
+        Self::nearest()
+            .filter(FilterMode::Linear)
+            .mipmap_filter(MipmapFilterMode::Linear)
+    }
+
+    /// 同时设置 u、v、w 三个方向
+    pub fn address_mode(self, mode: AddressMode) -> Self {
+        self.address_mode_uvw(mode, mode, mode)
+    }
+
+    pub fn address_mode_uvw(mut self, u: AddressMode, v: AddressMode, w: AddressMode) -> Self {
+        self.address_mode_u = u;
+        self.address_mode_v = v;
+        self.address_mode_w = w;
+        self
+    }
+
+    /// 同时设置放大与缩小过滤
+    pub fn filter(mut self, filter: FilterMode) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mag_filter(mut self, filter: FilterMode) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn min_filter(mut self, filter: FilterMode) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mipmap_filter(mut self, filter: MipmapFilterMode) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.lod_min_clamp = min;
+        self.lod_max_clamp = max;
+        self
+    }
+
+    /// 各向异性过滤的最大采样数，取值 1..=16，需要三种过滤都为线性
+    pub fn anisotropy(mut self, clamp: u16) -> Self {
+        self.anisotropy_clamp = clamp;
+        self
+    }
+
+    /// 比较采样器，只能用于深度纹理，
+    /// 绑定到颜色纹理上在部分平台（如 iOS）会直接崩溃
+    pub fn compare(mut self, compare: CompareFunction) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    /// 地址模式为 `ClampToBorder` 时使用的边框颜色
+    pub fn border_color(mut self, color: SamplerBorderColor) -> Self {
+        self.border_color = Some(color);
+        self
+    }
+
+    /// 检查各选项的组合以及设备 features 是否支持
+    pub fn validate(&self, features: wgpu::Features) -> Result<()> {
+        if self.lod_min_clamp.is_nan()
+            || self.lod_max_clamp.is_nan()
+            || self.lod_min_clamp < 0.0
+            || self.lod_max_clamp < self.lod_min_clamp
+        {
+            return Err(Error::Validation(format!(
+                "lod 范围 {}..{} 无效，需满足 0 <= min <= max",
+                self.lod_min_clamp, self.lod_max_clamp
+            )));
+        }
+        if !(1..=16).contains(&self.anisotropy_clamp) {
+            return Err(Error::Validation(format!(
+                "各向异性采样数 {} 超出了 1..=16",
+                self.anisotropy_clamp
+            )));
+        }
+        if self.anisotropy_clamp > 1
+            && (self.mag_filter != FilterMode::Linear
+                || self.min_filter != FilterMode::Linear
+                || self.mipmap_filter != MipmapFilterMode::Linear)
+        {
+            return Err(Error::Validation(format!(
+                "各向异性过滤要求 mag / min / mipmap 过滤都为 Linear，当前为 {:?} / {:?} / {:?}",
+                self.mag_filter, self.min_filter, self.mipmap_filter
+            )));
+        }
+
+        let modes = [
+            self.address_mode_u,
+            self.address_mode_v,
+            self.address_mode_w,
+        ];
+        if modes.contains(&AddressMode::ClampToBorder) {
+            let Some(color) = self.border_color else {
+                return Err(Error::Validation(
+                    "ClampToBorder 地址模式需要设置 border_color".into(),
+                ));
+            };
+            let feature = if color == SamplerBorderColor::Zero {
+                wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO
+            } else {
+                wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER
+            };
+            if !features.contains(feature) {
+                return Err(Error::Validation(format!(
+                    "边框颜色 {color:?} 需要设备支持 {feature:?}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_descriptor<'a>(&self, label: Option<&'a str>) -> wgpu::SamplerDescriptor<'a> {
+        wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            address_mode_w: self.address_mode_w,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            compare: self.compare,
+            anisotropy_clamp: self.anisotropy_clamp,
+            border_color: self.border_color,
+        }
+    }
+
+    /// 验证后创建采样器，不经过缓存
+    pub fn create(&self, device: &wgpu::Device) -> Result<wgpu::Sampler> {
+        self.validate(device.features())?;
+        Ok(device.create_sampler(&self.to_descriptor(None)))
+    }
+}
+
+/// 按设备缓存采样器，相同的 [`SamplerDesc`] 只创建一次
+pub struct SamplerCache {
+    device: wgpu::Device,
+    samplers: Mutex<HashMap<SamplerDesc, wgpu::Sampler>>,
+}
+
+#[allow(dead_code)]
+impl SamplerCache {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            device: device.clone(),
+            samplers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, desc: &SamplerDesc) -> Result<wgpu::Sampler> {
+        let mut samplers = self.samplers.lock();
+        if let Some(sampler) = samplers.get(desc) {
+            return Ok(sampler.clone());
+        }
+        let sampler = desc.create(&self.device)?;
+        samplers.insert(*desc, sampler.clone());
+        Ok(sampler)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samplers.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.samplers.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SamplerDesc;
+    use wgpu::{AddressMode, FilterMode, SamplerBorderColor};
+
+    #[test]
+    fn anisotropy_requires_linear_filters() {
+        let features = wgpu::Features::empty();
+        assert!(
+            SamplerDesc::linear()
+                .anisotropy(8)
+                .validate(features)
+                .is_ok()
+        );
+        assert!(
+            SamplerDesc::nearest()
+                .anisotropy(8)
+                .validate(features)
+                .is_err()
+        );
+        assert!(
+            SamplerDesc::linear()
+                .mag_filter(FilterMode::Nearest)
+                .anisotropy(4)
+                .validate(features)
+                .is_err()
+        );
+        assert!(
+            SamplerDesc::linear()
+                .anisotropy(32)
+                .validate(features)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn clamp_to_border_requires_color_and_feature() {
+        let desc = SamplerDesc::linear().address_mode(AddressMode::ClampToBorder);
+        let features = wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER;
+        assert!(desc.validate(features).is_err());
+        let desc = desc.border_color(SamplerBorderColor::OpaqueBlack);
+        assert!(desc.validate(features).is_ok());
+        assert!(desc.validate(wgpu::Features::empty()).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+// 视锥剔除：把可见实例的数据复制到输出缓冲区，并累加间接绘制参数中的实例数
+//
+// 实例数据的前 16 个 u32 须为列主序的模型矩阵，其余部分按原样复制
+
+struct CullParams {
+    planes: array<vec4f, 6>,
+    // xyz: 网格在模型空间中的包围球球心，w: 半径
+    sphere: vec4f,
+    instance_count: u32,
+    stride_words: u32,
+    _pad: vec2u,
+}
+
+struct DrawIndexedIndirectArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: CullParams;
+@group(0) @binding(1) var<storage, read> instances: array<u32>;
+@group(0) @binding(2) var<storage, read_write> visible: array<u32>;
+@group(0) @binding(3) var<storage, read_write> draw_args: DrawIndexedIndirectArgs;
+
+fn load_column(base: u32) -> vec4f {
+    return vec4f(
+        bitcast<f32>(instances[base]),
+        bitcast<f32>(instances[base + 1u]),
+        bitcast<f32>(instances[base + 2u]),
+        bitcast<f32>(instances[base + 3u]),
+    );
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) gid: vec3u) {
+    let index = gid.x;
+    if (index >= params.instance_count) {
+        return;
+    }
+    let base = index * params.stride_words;
+    let model = mat4x4f(
+        load_column(base),
+        load_column(base + 4u),
+        load_column(base + 8u),
+        load_column(base + 12u),
+    );
+
+    // 非均匀缩放时按最大的轴向缩放放大半径
+    let center = (model * vec4f(params.sphere.xyz, 1.0)).xyz;
+    let scale = max(length(model[0].xyz), max(length(model[1].xyz), length(model[2].xyz)));
+    let radius = params.sphere.w * scale;
+    for (var i = 0u; i < 6u; i++) {
+        let plane = params.planes[i];
+        if (dot(plane.xyz, center) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let slot = atomicAdd(&draw_args.instance_count, 1u);
+    let dst = slot * params.stride_words;
+    for (var w = 0u; w < params.stride_words; w++) {
+        visible[dst + w] = instances[base + w];
+    }
+}
@@ -0,0 +1,41 @@
+// 通过线性过滤采样上一级 mip 来生成下一级
+// 颜色纹理使用与纹理相同的 sRGB / 线性格式的视图，由硬件完成 sRGB 的解码与编码
+
+// 法线贴图：平均之后重新归一化
+override NORMAL_MAP: bool = false;
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // 覆盖整个视口的大三角形
+    let uv = vec2f(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var src_tex: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+
+fn renormalize(encoded: vec3f) -> vec3f {
+    let n = encoded * 2.0 - 1.0;
+    let len = length(n);
+    if len < 1e-5 {
+        return vec3f(0.5, 0.5, 1.0);
+    }
+    return n / len * 0.5 + 0.5;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(src_tex, src_sampler, in.uv);
+    if NORMAL_MAP {
+        return vec4f(renormalize(color.xyz), color.a);
+    }
+    return color;
+}
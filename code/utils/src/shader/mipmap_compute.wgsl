@@ -0,0 +1,36 @@
+// 对上一级 mip 的 2x2 像素取平均来生成下一级
+// STORAGE_FORMAT 在创建管线前会被替换为纹理的实际格式
+
+override NORMAL_MAP: bool = false;
+
+@group(0) @binding(0) var src_tex: texture_2d<f32>;
+@group(0) @binding(1) var dst_tex: texture_storage_2d<STORAGE_FORMAT, write>;
+
+fn renormalize(encoded: vec3f) -> vec3f {
+    let n = encoded * 2.0 - 1.0;
+    let len = length(n);
+    if len < 1e-5 {
+        return vec3f(0.5, 0.5, 1.0);
+    }
+    return n / len * 0.5 + 0.5;
+}
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let dst_size = textureDimensions(dst_tex);
+    if id.x >= dst_size.x || id.y >= dst_size.y {
+        return;
+    }
+    // 奇数尺寸时最后一列 / 行会被钳制到边缘
+    let max_coord = textureDimensions(src_tex) - 1u;
+    let base = id.xy * 2u;
+    let sum = textureLoad(src_tex, min(base, max_coord), 0)
+        + textureLoad(src_tex, min(base + vec2u(1u, 0u), max_coord), 0)
+        + textureLoad(src_tex, min(base + vec2u(0u, 1u), max_coord), 0)
+        + textureLoad(src_tex, min(base + vec2u(1u, 1u), max_coord), 0);
+    var color = sum * 0.25;
+    if NORMAL_MAP {
+        color = vec4f(renormalize(color.xyz), color.a);
+    }
+    textureStore(dst_tex, id.xy, color);
+}
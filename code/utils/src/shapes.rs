@@ -0,0 +1,615 @@
+//! 程序化生成的几何体
+//!
+//! 所有几何体以原点为中心、+Y 朝上，三角形从外侧看为逆时针（`FrontFace::Ccw`）。
+//! 纹理坐标的 v 轴向下，与 wgpu 的纹理坐标一致。
+//!
+//! ```ignore
+//! let mesh = UvSphere { radius: 0.5, ..Default::default() }.generate().with_tangents();
+//! let vertices = mesh.tangent_vertices();
+//! let wireframe = mesh.line_indices();
+//! ```
+
+use crate::vertex::{PosNormalUv, PosNormalUvTangent};
+use glam::{Vec2, Vec3};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{PI, TAU};
+
+/// 生成的网格，`indices` 为 triangle list
+#[derive(Clone, Debug, Default)]
+pub struct ShapeMesh {
+    pub vertices: Vec<PosNormalUv>,
+    pub indices: Vec<u32>,
+    /// 每个顶点的切线，w 分量为副切线的方向（±1）
+    pub tangents: Option<Vec<[f32; 4]>>,
+}
+
+impl ShapeMesh {
+    /// 由纹理坐标计算 MikkTSpace 切线
+    pub fn with_tangents(mut self) -> Self {
+        self.tangents = Some(compute_tangents(&self.vertices, &self.indices));
+        self
+    }
+
+    /// 带切线的顶点，尚未计算切线时会先计算
+    pub fn tangent_vertices(&self) -> Vec<PosNormalUvTangent> {
+        let computed;
+        let tangents = match &self.tangents {
+            Some(tangents) => tangents,
+            None => {
+                computed = compute_tangents(&self.vertices, &self.indices);
+                &computed
+            }
+        };
+        self.vertices
+            .iter()
+            .zip(tangents)
+            .map(|(v, t)| PosNormalUvTangent {
+                pos: v.pos,
+                normal: v.normal,
+                uv: v.uv,
+                tangent: *t,
+            })
+            .collect()
+    }
+
+    /// 线框的 line list 索引，三角形共享的边只出现一次
+    pub fn line_indices(&self) -> Vec<u32> {
+        let mut edges = HashSet::new();
+        let mut lines = Vec::with_capacity(self.indices.len() * 2);
+        for tri in self.indices.chunks_exact(3) {
+            for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+                if edges.insert((a.min(b), a.max(b))) {
+                    lines.extend_from_slice(&[a, b]);
+                }
+            }
+        }
+        lines
+    }
+
+    fn scale_uv(mut self, scale: Vec2) -> Self {
+        if scale != Vec2::ONE {
+            for v in &mut self.vertices {
+                v.uv = (Vec2::from(v.uv) * scale).into();
+            }
+        }
+        self
+    }
+
+    fn push(&mut self, pos: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.vertices.push(PosNormalUv {
+            pos: pos.into(),
+            normal: normal.into(),
+            uv: uv.into(),
+        });
+        self.vertices.len() as u32 - 1
+    }
+
+    /// 添加 (cols + 1) x (rows + 1) 个顶点组成的网格，`f(j, i)` 返回第 i 行第 j 列的顶点
+    ///
+    /// i 增大的方向叉乘 j 增大的方向须指向外侧，面积为 0 的三角形（如球的极点）会被跳过
+    fn push_grid(&mut self, cols: u32, rows: u32, f: impl Fn(u32, u32) -> (Vec3, Vec3, Vec2)) {
+        let base = self.vertices.len() as u32;
+        for i in 0..=rows {
+            for j in 0..=cols {
+                let (pos, normal, uv) = f(j, i);
+                self.push(pos, normal, uv);
+            }
+        }
+        for i in 0..rows {
+            for j in 0..cols {
+                let a = base + i * (cols + 1) + j;
+                let b = a + cols + 1;
+                self.push_triangle([a, b, a + 1]);
+                self.push_triangle([a + 1, b, b + 1]);
+            }
+        }
+    }
+
+    fn push_triangle(&mut self, tri: [u32; 3]) {
+        let [p0, p1, p2] = tri.map(|i| Vec3::from(self.vertices[i as usize].pos));
+        if (p1 - p0).cross(p2 - p0).length_squared() > 1e-12 {
+            self.indices.extend_from_slice(&tri);
+        }
+    }
+}
+
+/// 可配置的平面网格，位于 XZ 平面，法线为 +Y
+#[derive(Clone, Copy, Debug)]
+pub struct Grid {
+    pub width: f32,
+    pub depth: f32,
+    pub width_segments: u32,
+    pub depth_segments: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            depth: 1.0,
+            width_segments: 1,
+            depth_segments: 1,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Grid {
+    pub fn generate(&self) -> ShapeMesh {
+        let (cols, rows) = (self.width_segments.max(1), self.depth_segments.max(1));
+        let mut mesh = ShapeMesh::default();
+        // 从 -Z 一侧开始，i 增大的方向为 +Z
+        mesh.push_grid(cols, rows, |j, i| {
+            let uv = Vec2::new(j as f32 / cols as f32, i as f32 / rows as f32);
+            let pos = Vec3::new((uv.x - 0.5) * self.width, 0.0, (uv.y - 0.5) * self.depth);
+            (pos, Vec3::Y, uv)
+        });
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 长方体，每个面细分为 segments x segments 个格子，六个面各自使用完整的纹理
+#[derive(Clone, Copy, Debug)]
+pub struct Cuboid {
+    pub size: Vec3,
+    pub segments: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Cuboid {
+    fn default() -> Self {
+        Self {
+            size: Vec3::ONE,
+            segments: 1,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Cuboid {
+    pub fn generate(&self) -> ShapeMesh {
+        let segments = self.segments.max(1);
+        let half = self.size * 0.5;
+        let mut mesh = ShapeMesh::default();
+        // (法线, 从外侧看时纹理的右方向, 上方向)，right x up = normal
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+        for (normal, right, up) in faces {
+            mesh.push_grid(segments, segments, |j, i| {
+                let uv = Vec2::new(j as f32, i as f32) / segments as f32;
+                let pos = (normal + right * (uv.x * 2.0 - 1.0) - up * (uv.y * 2.0 - 1.0)) * half;
+                (pos, normal, uv)
+            });
+        }
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 经纬球，纹理按等距柱状投影映射
+#[derive(Clone, Copy, Debug)]
+pub struct UvSphere {
+    pub radius: f32,
+    /// 经线方向的分段数
+    pub sectors: u32,
+    /// 纬线方向的分段数
+    pub stacks: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for UvSphere {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            sectors: 32,
+            stacks: 16,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl UvSphere {
+    pub fn generate(&self) -> ShapeMesh {
+        let (sectors, stacks) = (self.sectors.max(3), self.stacks.max(2));
+        let mut mesh = ShapeMesh::default();
+        mesh.push_grid(sectors, stacks, |j, i| {
+            let uv = Vec2::new(j as f32 / sectors as f32, i as f32 / stacks as f32);
+            let normal = sphere_dir(uv.x * TAU, uv.y * PI);
+            (normal * self.radius, normal, uv)
+        });
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 由正二十面体细分得到的球，三角形大小比经纬球均匀
+#[derive(Clone, Copy, Debug)]
+pub struct Icosphere {
+    pub radius: f32,
+    /// 细分次数，每次细分三角形数量变为 4 倍
+    pub subdivisions: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Icosphere {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            subdivisions: 3,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Icosphere {
+    pub fn generate(&self) -> ShapeMesh {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut points: Vec<Vec3> = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+        .collect();
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..self.subdivisions.min(8) {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let p = (points[a as usize] + points[b as usize]).normalize();
+                    points.push(p);
+                    points.len() as u32 - 1
+                })
+            };
+            triangles = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let mut mesh = ShapeMesh::default();
+        for p in &points {
+            let uv = Vec2::new(p.x.atan2(p.z) / TAU + 0.5, p.y.clamp(-1.0, 1.0).acos() / PI);
+            mesh.push(*p * self.radius, *p, uv);
+        }
+        // 跨越经度接缝的三角形，把 u 较小的顶点复制一份并加 1，避免纹理在接缝处倒卷
+        let mut seam_copies = HashMap::new();
+        for tri in &mut triangles {
+            let us = tri.map(|i| mesh.vertices[i as usize].uv[0]);
+            let max_u = us[0].max(us[1]).max(us[2]);
+            for (k, index) in tri.iter_mut().enumerate() {
+                if max_u - us[k] > 0.5 {
+                    *index = *seam_copies.entry(*index).or_insert_with(|| {
+                        let mut v = mesh.vertices[*index as usize];
+                        v.uv[0] += 1.0;
+                        mesh.vertices.push(v);
+                        mesh.vertices.len() as u32 - 1
+                    });
+                }
+            }
+        }
+        mesh.indices = triangles.into_iter().flatten().collect();
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 圆柱，也可以是上下半径不同的圆台
+#[derive(Clone, Copy, Debug)]
+pub struct Cylinder {
+    pub radius_top: f32,
+    pub radius_bottom: f32,
+    pub height: f32,
+    pub radial_segments: u32,
+    pub height_segments: u32,
+    /// 是否生成上下两个底面
+    pub caps: bool,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            radius_top: 0.5,
+            radius_bottom: 0.5,
+            height: 1.0,
+            radial_segments: 32,
+            height_segments: 1,
+            caps: true,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn generate(&self) -> ShapeMesh {
+        let radial = self.radial_segments.max(3);
+        let rows = self.height_segments.max(1);
+        let half_height = self.height * 0.5;
+        let slope = (self.radius_bottom - self.radius_top) / self.height.max(f32::EPSILON);
+        let mut mesh = ShapeMesh::default();
+        mesh.push_grid(radial, rows, |j, i| {
+            let uv = Vec2::new(j as f32 / radial as f32, i as f32 / rows as f32);
+            let radius = self.radius_top + (self.radius_bottom - self.radius_top) * uv.y;
+            let dir = sphere_dir(uv.x * TAU, PI * 0.5);
+            let pos = dir * radius + Vec3::Y * (half_height - self.height * uv.y);
+            let normal = (dir + Vec3::Y * slope).normalize();
+            (pos, normal, uv)
+        });
+        if self.caps {
+            if self.radius_top > 0.0 {
+                push_disc(&mut mesh, radial, self.radius_top, half_height, true);
+            }
+            if self.radius_bottom > 0.0 {
+                push_disc(&mut mesh, radial, self.radius_bottom, -half_height, false);
+            }
+        }
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 圆锥，顶点朝 +Y
+#[derive(Clone, Copy, Debug)]
+pub struct Cone {
+    pub radius: f32,
+    pub height: f32,
+    pub radial_segments: u32,
+    pub height_segments: u32,
+    /// 是否生成底面
+    pub cap: bool,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            height: 1.0,
+            radial_segments: 32,
+            height_segments: 1,
+            cap: true,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Cone {
+    pub fn generate(&self) -> ShapeMesh {
+        Cylinder {
+            radius_top: 0.0,
+            radius_bottom: self.radius,
+            height: self.height,
+            radial_segments: self.radial_segments,
+            height_segments: self.height_segments,
+            caps: self.cap,
+            uv_scale: self.uv_scale,
+        }
+        .generate()
+    }
+}
+
+/// 圆环，环绕 Y 轴
+#[derive(Clone, Copy, Debug)]
+pub struct Torus {
+    /// 圆环中心线的半径
+    pub major_radius: f32,
+    /// 管的半径
+    pub minor_radius: f32,
+    pub major_segments: u32,
+    pub minor_segments: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self {
+            major_radius: 0.5,
+            minor_radius: 0.2,
+            major_segments: 48,
+            minor_segments: 24,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Torus {
+    pub fn generate(&self) -> ShapeMesh {
+        let (cols, rows) = (self.major_segments.max(3), self.minor_segments.max(3));
+        let mut mesh = ShapeMesh::default();
+        mesh.push_grid(cols, rows, |j, i| {
+            let uv = Vec2::new(j as f32 / cols as f32, i as f32 / rows as f32);
+            let radial = sphere_dir(uv.x * TAU, PI * 0.5);
+            // 从外侧赤道开始向下绕管一周
+            let (sin, cos) = (uv.y * TAU).sin_cos();
+            let normal = radial * cos - Vec3::Y * sin;
+            (
+                radial * self.major_radius + normal * self.minor_radius,
+                normal,
+                uv,
+            )
+        });
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// 胶囊体：圆柱加两个半球，`height` 为中间圆柱部分的高度
+#[derive(Clone, Copy, Debug)]
+pub struct Capsule {
+    pub radius: f32,
+    pub height: f32,
+    pub radial_segments: u32,
+    /// 每个半球在纬线方向的分段数
+    pub rings: u32,
+    pub height_segments: u32,
+    pub uv_scale: Vec2,
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self {
+            radius: 0.25,
+            height: 0.5,
+            radial_segments: 32,
+            rings: 8,
+            height_segments: 1,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Capsule {
+    pub fn generate(&self) -> ShapeMesh {
+        let radial = self.radial_segments.max(3);
+        let rings = self.rings.max(1);
+        let height_segments = self.height_segments.max(1);
+        let half_height = self.height * 0.5;
+
+        // 轮廓上从顶到底的点：(y, 半径, 法线的 y 分量, 法线的径向分量)
+        let mut profile = vec![];
+        for k in 0..=rings {
+            let phi = k as f32 / rings as f32 * PI * 0.5;
+            let (sin, cos) = phi.sin_cos();
+            profile.push((half_height + self.radius * cos, self.radius * sin, cos, sin));
+        }
+        for k in 1..height_segments {
+            let y = half_height - self.height * k as f32 / height_segments as f32;
+            profile.push((y, self.radius, 0.0, 1.0));
+        }
+        for k in 0..=rings {
+            let phi = PI * 0.5 + k as f32 / rings as f32 * PI * 0.5;
+            let (sin, cos) = phi.sin_cos();
+            profile.push((
+                -half_height + self.radius * cos,
+                self.radius * sin,
+                cos,
+                sin,
+            ));
+        }
+        let total_height = self.height + self.radius * 2.0;
+
+        let mut mesh = ShapeMesh::default();
+        mesh.push_grid(radial, profile.len() as u32 - 1, |j, i| {
+            let (y, radius, normal_y, normal_r) = profile[i as usize];
+            let u = j as f32 / radial as f32;
+            let dir = sphere_dir(u * TAU, PI * 0.5);
+            let uv = Vec2::new(u, (half_height + self.radius - y) / total_height);
+            (
+                dir * radius + Vec3::Y * y,
+                dir * normal_r + Vec3::Y * normal_y,
+                uv,
+            )
+        });
+        mesh.scale_uv(self.uv_scale)
+    }
+}
+
+/// theta 为绕 Y 轴的角度（0 指向 +Z），phi 为与 +Y 的夹角
+fn sphere_dir(theta: f32, phi: f32) -> Vec3 {
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    Vec3::new(sin_phi * sin_theta, cos_phi, sin_phi * cos_theta)
+}
+
+/// 水平的圆盘，纹理按俯视的平面投影映射
+fn push_disc(mesh: &mut ShapeMesh, radial: u32, radius: f32, y: f32, facing_up: bool) {
+    let (normal, sign) = if facing_up {
+        (Vec3::Y, 1.0)
+    } else {
+        (Vec3::NEG_Y, -1.0)
+    };
+    // i 为从圆心向外的环，底面反向绕行以保持朝外
+    mesh.push_grid(radial, 1, |j, i| {
+        let dir = sphere_dir(sign * j as f32 / radial as f32 * TAU, PI * 0.5);
+        let pos = dir * radius * i as f32 + Vec3::Y * y;
+        let uv = Vec2::new(
+            0.5 + dir.x * 0.5 * i as f32,
+            0.5 - sign * dir.z * 0.5 * i as f32,
+        );
+        (pos, normal, uv)
+    });
+}
+
+fn compute_tangents(vertices: &[PosNormalUv], indices: &[u32]) -> Vec<[f32; 4]> {
+    let positions: Vec<_> = vertices.iter().map(|v| v.pos).collect();
+    let normals: Vec<_> = vertices.iter().map(|v| v.normal).collect();
+    let uvs: Vec<_> = vertices.iter().map(|v| v.uv).collect();
+    crate::mesh::generate_tangents(&positions, &normals, &uvs, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个三角形的几何法线都应与顶点法线同向，即从外侧看为逆时针
+    fn assert_outward(mesh: &ShapeMesh) {
+        assert!(!mesh.indices.is_empty());
+        for tri in mesh.indices.chunks_exact(3) {
+            let v = [tri[0], tri[1], tri[2]].map(|i| mesh.vertices[i as usize]);
+            let [p0, p1, p2] = v.map(|v| Vec3::from(v.pos));
+            let face_normal = (p1 - p0).cross(p2 - p0);
+            let vertex_normal: Vec3 = v.iter().map(|v| Vec3::from(v.normal)).sum();
+            assert!(
+                face_normal.dot(vertex_normal) > 0.0,
+                "inward triangle {tri:?}: {p0} {p1} {p2}"
+            );
+        }
+    }
+
+    #[test]
+    fn all_shapes_wind_outward() {
+        assert_outward(&Grid::default().generate());
+        assert_outward(&Cuboid::default().generate());
+        assert_outward(&UvSphere::default().generate());
+        assert_outward(&Icosphere::default().generate());
+        assert_outward(&Cylinder::default().generate());
+        assert_outward(&Cone::default().generate());
+        assert_outward(&Torus::default().generate());
+        assert_outward(&Capsule::default().generate());
+    }
+
+    #[test]
+    fn wireframe_shares_edges() {
+        let mesh = Grid::default().generate();
+        // 两个三角形共 5 条边
+        assert_eq!(mesh.line_indices().len(), 10);
+    }
+}
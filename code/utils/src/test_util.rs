@@ -0,0 +1,10 @@
+//! 测试用的 GPU 设备
+
+/// 请求一个测试用的设备，没有可用的适配器时返回 `None`，调用方应跳过需要 GPU 的测试
+pub(crate) fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok()?;
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()
+}
@@ -0,0 +1,290 @@
+use crate::resource_tracker::TrackedResource;
+use crate::{BufferObj, Error, Result};
+use bytemuck::Pod;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+/// 带元素类型的缓冲区
+///
+/// 在 `BufferObj` 之上记录元素个数与容量，容量不足时会在 GPU 上分配新的缓冲区并复制已有数据。
+/// `min_binding_size` 总是单个元素的字节长度，所以作为 uniform 或存储缓冲区绑定到尺寸不匹配的
+/// 着色器变量时会在创建管线时报错，而不是等到绘制时才出错。作为 uniform 使用时，着色器中的变量
+/// 应声明为单个 `T`。
+///
+/// # NOTE:
+/// 重新分配后内部的 `wgpu::Buffer` 会被替换，引用了它的 bind group 需要重建；
+/// 写入的起始字节偏移与字节长度都需是 4 的倍数，否则返回 [`Error::Validation`]，
+/// 元素小于 4 字节（如 `u16`）时需成组写入
+pub struct TypedBuffer<T: Pod> {
+    pub obj: BufferObj,
+    len: u64,
+    capacity: u64,
+    usage: wgpu::BufferUsages,
+    label: Option<&'static str>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Deref for TypedBuffer<T> {
+    type Target = BufferObj;
+    fn deref(&self) -> &BufferObj {
+        &self.obj
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Pod> TypedBuffer<T> {
+    /// 创建可容纳 `capacity` 个元素的空缓冲区
+    #[track_caller]
+    pub fn new(
+        device: &wgpu::Device,
+        capacity: u64,
+        usage: wgpu::BufferUsages,
+        label: Option<&'static str>,
+    ) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC;
+        Self {
+            obj: Self::allocate(device, capacity, usage, label),
+            len: 0,
+            capacity,
+            usage,
+            label,
+            _marker: PhantomData,
+        }
+    }
+
+    #[track_caller]
+    pub fn from_slice(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[T],
+        usage: wgpu::BufferUsages,
+        label: Option<&'static str>,
+    ) -> Result<Self> {
+        let mut buf = Self::new(device, data.len() as u64, usage, label);
+        buf.extend(device, queue, data)?;
+        Ok(buf)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// 单个元素的字节长度，可用作 bind group layout 的 `min_binding_size`
+    pub fn binding_size() -> wgpu::BufferSize {
+        wgpu::BufferSize::new(Self::stride()).expect("zero-sized element type")
+    }
+
+    /// 已写入元素的字节长度
+    pub fn byte_len(&self) -> wgpu::BufferAddress {
+        self.len * Self::stride()
+    }
+
+    /// 覆盖写入 `[start, start + data.len())` 范围内的元素，范围不能超出当前元素个数
+    pub fn write_range(&self, queue: &wgpu::Queue, start: u64, data: &[T]) -> Result<()> {
+        assert!(
+            start + data.len() as u64 <= self.len,
+            "write range {}..{} out of bounds (len: {})",
+            start,
+            start + data.len() as u64,
+            self.len
+        );
+        Self::check_write(start, data.len() as u64)?;
+        Self::write_bytes(queue, &self.obj.buffer, start, data);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn push(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, item: T) -> Result<()> {
+        self.extend(device, queue, core::slice::from_ref(&item))
+    }
+
+    /// 在末尾追加元素，容量不足时自动扩容
+    #[track_caller]
+    pub fn extend(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[T]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let start = self.len;
+        Self::check_write(start, data.len() as u64)?;
+        self.reserve(device, queue, data.len() as u64);
+        Self::write_bytes(queue, &self.obj.buffer, start, data);
+        self.set_len(start + data.len() as u64);
+        Ok(())
+    }
+
+    /// 调整元素个数，新增的元素在 GPU 上为零值
+    ///
+    /// 缩小时不会释放容量
+    #[track_caller]
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        new_len: u64,
+    ) -> Result<()> {
+        if new_len > self.len {
+            let start = self.len;
+            Self::check_write(start, new_len - start)?;
+            self.reserve(device, queue, new_len - start);
+            let zeros = vec![T::zeroed(); (new_len - start) as usize];
+            Self::write_bytes(queue, &self.obj.buffer, start, &zeros);
+        }
+        self.set_len(new_len);
+        Ok(())
+    }
+
+    /// 确保还能再容纳 `additional` 个元素
+    #[track_caller]
+    pub fn reserve(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, additional: u64) {
+        let required = self.len + additional;
+        if required <= self.capacity {
+            return;
+        }
+        let new_capacity = required.max(self.capacity * 2);
+        let new_obj = Self::allocate(device, new_capacity, self.usage, self.label);
+        if self.len > 0 {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("TypedBuffer resize"),
+            });
+            let copy_size = self
+                .byte_len()
+                .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+                .min(self.obj.size);
+            encoder.copy_buffer_to_buffer(&self.obj.buffer, 0, &new_obj.buffer, 0, copy_size);
+            queue.submit(Some(encoder.finish()));
+        }
+        self.obj = new_obj;
+        self.capacity = new_capacity;
+        self.obj.used_count = self.len;
+    }
+
+    fn set_len(&mut self, len: u64) {
+        self.len = len;
+        self.obj.used_count = len;
+    }
+
+    fn stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<T>() as wgpu::BufferAddress
+    }
+
+    /// `queue.write_buffer` 要求偏移与长度都按 `COPY_BUFFER_ALIGNMENT` 对齐
+    fn check_write(start: u64, count: u64) -> Result<()> {
+        let offset = start * Self::stride();
+        let size = count * Self::stride();
+        if !offset.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+            || !size.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+        {
+            return Err(Error::Validation(format!(
+                "写入的字节偏移 {offset} 与长度 {size} 需是 {} 的倍数",
+                wgpu::COPY_BUFFER_ALIGNMENT
+            )));
+        }
+        Ok(())
+    }
+
+    fn write_bytes(queue: &wgpu::Queue, buffer: &wgpu::Buffer, start: u64, data: &[T]) {
+        queue.write_buffer(buffer, start * Self::stride(), bytemuck::cast_slice(data));
+    }
+
+    #[track_caller]
+    fn allocate(
+        device: &wgpu::Device,
+        capacity: u64,
+        usage: wgpu::BufferUsages,
+        label: Option<&'static str>,
+    ) -> BufferObj {
+        // 容量为 0 时也分配一个元素的空间，避免创建空缓冲区
+        let size = (capacity.max(1) * Self::stride()).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            size,
+            usage,
+            label,
+            mapped_at_creation: false,
+        });
+        BufferObj {
+            tracked: TrackedResource::buffer(&buffer, label),
+            buffer,
+            size,
+            min_binding_size: Some(Self::binding_size()),
+            has_dynamic_offset: false,
+            read_only: false,
+            used_count: 0,
+            uniform_binding_size: Some(Self::binding_size()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{BindGroupData, ComputeNode};
+
+    fn build_with_uniform(device: &wgpu::Device, uniform: &BufferObj, wgsl_type: &str) -> bool {
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "@group(0) @binding(0) var<uniform> params: {wgsl_type};
+                    @compute @workgroup_size(1)
+                    fn cs_main() {{ _ = params; }}"
+                )
+                .into(),
+            ),
+        });
+        let bg_data = BindGroupData {
+            workgroup_count: (1, 1, 1),
+            uniforms: vec![uniform],
+            ..Default::default()
+        };
+        ComputeNode::new(device, &bg_data, &shader_module).is_ok()
+    }
+
+    #[test]
+    fn uniform_binding_size_follows_element_type() {
+        let Some((device, queue)) = crate::test_util::device() else {
+            return;
+        };
+        let typed = TypedBuffer::<[f32; 4]>::from_slice(
+            &device,
+            &queue,
+            &[[0.0; 4]],
+            wgpu::BufferUsages::UNIFORM,
+            None,
+        )
+        .unwrap();
+        assert!(build_with_uniform(&device, &typed, "vec4f"));
+        assert!(!build_with_uniform(&device, &typed, "mat4x4f"));
+    }
+
+    #[test]
+    fn unaligned_writes_are_rejected_without_changing_len() {
+        let Some((device, queue)) = crate::test_util::device() else {
+            return;
+        };
+        let mut buf = TypedBuffer::<u16>::new(&device, 4, wgpu::BufferUsages::STORAGE, None);
+        assert!(matches!(
+            buf.push(&device, &queue, 1),
+            Err(Error::Validation(_))
+        ));
+        assert_eq!(buf.len(), 0);
+
+        buf.extend(&device, &queue, &[1, 2]).unwrap();
+        assert!(buf.write_range(&queue, 1, &[3]).is_err());
+        assert!(buf.resize(&device, &queue, 3).is_err());
+        assert_eq!(buf.len(), 2);
+
+        // 成组写入，扩容后已有数据保留
+        buf.extend(&device, &queue, &[3, 4, 5, 6]).unwrap();
+        buf.write_range(&queue, 4, &[7, 8]).unwrap();
+        let data = pollster::block_on(buf.read::<u16>(&device, &queue)).unwrap();
+        assert_eq!(data[..6], [1, 2, 3, 4, 7, 8]);
+    }
+}
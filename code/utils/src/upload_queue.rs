@@ -0,0 +1,420 @@
+//! 并行解码图片，并在主线程按每帧的字节预算分批上传纹理
+//!
+//! ```ignore
+//! let mut uploads = UploadQueue::new(&app.device, TextureLoadOptions::default(), 8 << 20);
+//! uploads.load(&["a.png", "b.jpg"]).await;
+//! // 每帧调用
+//! let progress = uploads.poll(&app.device, &app.queue);
+//! if progress.is_done() {
+//!     let a = uploads.take("a.png").unwrap();
+//! }
+//! ```
+//!
+//! 桌面端在 rayon 线程池中解码；web 端没有工作线程，在调用处按顺序解码。
+
+use crate::load_texture::{self, AnyTexture, TextureLoadOptions};
+use crate::mipmap::{MipFilter, MipmapGenerator, mip_level_count, required_usage};
+use crate::{Error, Result, ValidationScope};
+use std::collections::{HashMap, VecDeque};
+use wgpu::{Extent3d, TextureFormat};
+
+/// 解码后等待上传的像素数据
+pub struct DecodedImage {
+    pub name: String,
+    pub texels: Vec<u8>,
+    pub extent: Extent3d,
+    pub format: TextureFormat,
+}
+
+impl DecodedImage {
+    pub fn decode(
+        name: &str,
+        bytes: &[u8],
+        options: &TextureLoadOptions,
+        features: wgpu::Features,
+    ) -> Result<Self> {
+        if crate::compressed_texture::is_container(bytes) {
+            return Err(Error::UnsupportedFormat(format!(
+                "{name}: KTX2 / DDS 容器请使用 load_texture::from_bytes 加载"
+            )));
+        }
+        let img = load_texture::decode_image(bytes, name)?;
+        if img.width() == 0 || img.height() == 0 {
+            return Err(Error::Decode(format!("{name}: 图片的宽或高为 0")));
+        }
+        let (texels, extent, mut format) = load_texture::load_from_img(img, options, features)?;
+        if options.mipmaps == Some(MipFilter::NormalMap) {
+            format = format.remove_srgb_suffix();
+        }
+        Ok(Self {
+            name: name.to_string(),
+            texels,
+            extent,
+            format,
+        })
+    }
+
+    fn bytes_per_row(&self) -> u32 {
+        // 解码结果都是非压缩格式
+        self.format.block_copy_size(None).unwrap_or(4) * self.extent.width
+    }
+}
+
+/// 并行解码一批图片，结果与输入的顺序一致
+pub fn decode_all(
+    items: &[(String, Vec<u8>)],
+    options: &TextureLoadOptions,
+    features: wgpu::Features,
+) -> Vec<Result<DecodedImage>> {
+    let decode =
+        |(name, bytes): &(String, Vec<u8>)| DecodedImage::decode(name, bytes, options, features);
+    std::cfg_select! {
+        target_arch = "wasm32" => {
+            items.iter().map(decode).collect()
+        }
+        _ => {
+            use rayon::prelude::*;
+            items.par_iter().map(decode).collect()
+        }
+    }
+}
+
+/// 上传进度
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// 提交解码的图片数
+    pub requested: usize,
+    /// 已上传完成的纹理数
+    pub uploaded: usize,
+    /// 解码失败的图片数
+    pub failed: usize,
+    /// 已解码、尚未上传完的字节数
+    pub bytes_pending: u64,
+    /// 累计上传的字节数
+    pub bytes_uploaded: u64,
+}
+
+impl UploadProgress {
+    /// 已处理（上传完成或失败）的图片比例
+    pub fn fraction(&self) -> f32 {
+        if self.requested == 0 {
+            1.0
+        } else {
+            (self.uploaded + self.failed) as f32 / self.requested as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.uploaded + self.failed == self.requested
+    }
+}
+
+// 正在分批上传的纹理
+struct InFlight {
+    image: DecodedImage,
+    texture: wgpu::Texture,
+    next_row: u32,
+}
+
+/// 主线程上的纹理上传队列
+pub struct UploadQueue {
+    sender: flume::Sender<(String, Result<DecodedImage>)>,
+    receiver: flume::Receiver<(String, Result<DecodedImage>)>,
+    options: TextureLoadOptions,
+    features: wgpu::Features,
+    usage: wgpu::TextureUsages,
+    bytes_per_frame: u64,
+    pending: VecDeque<DecodedImage>,
+    current: Option<InFlight>,
+    mipmap_generator: Option<MipmapGenerator>,
+    finished: HashMap<String, AnyTexture>,
+    errors: Vec<Error>,
+    progress: UploadProgress,
+}
+
+#[allow(dead_code)]
+impl UploadQueue {
+    /// `bytes_per_frame` 为每次 [`poll`](Self::poll) 最多上传的字节数，
+    /// 单行像素超过预算时每帧至少上传一行
+    pub fn new(device: &wgpu::Device, options: TextureLoadOptions, bytes_per_frame: u64) -> Self {
+        let (sender, receiver) = flume::unbounded();
+        Self {
+            sender,
+            receiver,
+            options,
+            features: device.features(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            bytes_per_frame: bytes_per_frame.max(1),
+            pending: VecDeque::new(),
+            current: None,
+            mipmap_generator: None,
+            finished: HashMap::new(),
+            errors: vec![],
+            progress: UploadProgress::default(),
+        }
+    }
+
+    /// 纹理的用途，默认为 `TEXTURE_BINDING`，`COPY_DST` 总是会被加上
+    pub fn with_usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// 提交一张图片文件数据进行解码，`name` 用于之后取出纹理
+    pub fn decode(&mut self, name: &str, bytes: Vec<u8>) {
+        self.progress.requested += 1;
+        let name = name.to_string();
+        let options = self.options;
+        let features = self.features;
+        let sender = self.sender.clone();
+        let task = move || {
+            let result = DecodedImage::decode(&name, &bytes, &options, features);
+            let _ = sender.send((name, result));
+        };
+        std::cfg_select! {
+            target_arch = "wasm32" => task(),
+            _ => rayon::spawn(task),
+        }
+    }
+
+    /// 通过 [`assets`](crate::assets) 读取文件后提交解码，以路径作为纹理名
+    ///
+    /// 读取失败的文件记为失败，不会中断其余文件的加载
+    pub async fn load(&mut self, paths: &[&str]) {
+        for path in paths {
+            match crate::assets::load(path).await {
+                Ok(bytes) => self.decode(path, bytes),
+                Err(e) => {
+                    self.progress.requested += 1;
+                    self.fail(path, e);
+                }
+            }
+        }
+    }
+
+    /// 每帧调用一次：接收已解码的图片，并在字节预算内上传
+    pub fn poll(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> UploadProgress {
+        while let Ok((name, result)) = self.receiver.try_recv() {
+            match result {
+                Ok(image) => {
+                    self.progress.bytes_pending += image.texels.len() as u64;
+                    self.pending.push_back(image);
+                }
+                Err(e) => self.fail(&name, e),
+            }
+        }
+
+        let mut budget = self.bytes_per_frame;
+        let mut uploaded_any = false;
+        while budget > 0 {
+            if self.current.is_none() {
+                let Some(image) = self.pending.pop_front() else {
+                    break;
+                };
+                let (name, bytes) = (image.name.clone(), image.texels.len() as u64);
+                match self.begin(device, image) {
+                    Ok(in_flight) => self.current = Some(in_flight),
+                    Err(e) => {
+                        self.progress.bytes_pending -= bytes;
+                        self.fail(&name, e);
+                        continue;
+                    }
+                }
+            }
+            let in_flight = self.current.as_mut().unwrap();
+            let bytes_per_row = in_flight.image.bytes_per_row();
+            let remaining_rows = in_flight.image.extent.height - in_flight.next_row;
+            let rows = rows_within_budget(budget, bytes_per_row, remaining_rows, uploaded_any);
+            if rows == 0 {
+                break;
+            }
+            write_rows(queue, in_flight, rows);
+            uploaded_any = true;
+            let bytes = rows as u64 * bytes_per_row as u64;
+            budget = budget.saturating_sub(bytes);
+            self.progress.bytes_uploaded += bytes;
+            self.progress.bytes_pending -= bytes;
+
+            if in_flight.next_row == in_flight.image.extent.height {
+                let in_flight = self.current.take().unwrap();
+                self.finish(device, queue, in_flight);
+            }
+        }
+        self.progress
+    }
+
+    pub fn progress(&self) -> UploadProgress {
+        self.progress
+    }
+
+    /// 取出上传完成的纹理
+    pub fn take(&mut self, name: &str) -> Option<AnyTexture> {
+        self.finished.remove(name)
+    }
+
+    /// 取出所有上传完成的纹理
+    pub fn take_all(&mut self) -> HashMap<String, AnyTexture> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// 解码或读取失败的错误
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    fn fail(&mut self, name: &str, e: Error) {
+        log::error!("{name}: {e}");
+        self.progress.failed += 1;
+        self.errors.push(e);
+    }
+
+    /// 创建纹理，尺寸超出设备限制等验证错误会返回 `Err`
+    fn begin(&self, device: &wgpu::Device, image: DecodedImage) -> Result<InFlight> {
+        let mip_usage = self
+            .options
+            .mipmaps
+            .and_then(|_| required_usage(device, image.format));
+        let mip_level_count = if mip_usage.is_some() {
+            mip_level_count(image.extent.width, image.extent.height)
+        } else {
+            1
+        };
+        let scope = ValidationScope::push(device);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&image.name),
+            size: image.extent,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: image.format,
+            usage: self.usage
+                | wgpu::TextureUsages::COPY_DST
+                | mip_usage.unwrap_or(wgpu::TextureUsages::empty()),
+            view_formats: &[],
+        });
+        scope.pop()?;
+        Ok(InFlight {
+            image,
+            texture,
+            next_row: 0,
+        })
+    }
+
+    fn finish(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, in_flight: InFlight) {
+        let InFlight { image, texture, .. } = in_flight;
+        if let Some(filter) = self.options.mipmaps
+            && texture.mip_level_count() > 1
+        {
+            self.mipmap_generator
+                .get_or_insert_with(|| MipmapGenerator::new(device))
+                .generate_and_submit(device, queue, &texture, filter);
+        }
+        let tex_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let any_tex = AnyTexture::from_parts(
+            texture,
+            tex_view,
+            wgpu::TextureViewDimension::D2,
+            Some(&image.name),
+        );
+        self.finished.insert(image.name, any_tex);
+        self.progress.uploaded += 1;
+    }
+}
+
+/// 本次能在预算内上传的行数，返回 0 表示本帧不再上传
+///
+/// 单行超过预算时，如果本帧还没有上传过任何数据，仍上传一行以保证进度
+fn rows_within_budget(
+    budget: u64,
+    bytes_per_row: u32,
+    remaining_rows: u32,
+    uploaded_any: bool,
+) -> u32 {
+    let rows = (budget / bytes_per_row as u64).min(remaining_rows as u64) as u32;
+    if rows == 0 && !uploaded_any {
+        remaining_rows.min(1)
+    } else {
+        rows
+    }
+}
+
+fn write_rows(queue: &wgpu::Queue, in_flight: &mut InFlight, rows: u32) {
+    let image = &in_flight.image;
+    let bytes_per_row = image.bytes_per_row();
+    let start = (in_flight.next_row * bytes_per_row) as usize;
+    let end = start + (rows * bytes_per_row) as usize;
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &in_flight.texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: in_flight.next_row,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        &image.texels[start..end],
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: Some(rows),
+        },
+        Extent3d {
+            width: image.extent.width,
+            height: rows,
+            depth_or_array_layers: 1,
+        },
+    );
+    in_flight.next_row += rows;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_split_by_budget() {
+        // 每行 100 字节，剩余 10 行
+        assert_eq!(rows_within_budget(350, 100, 10, false), 3);
+        assert_eq!(rows_within_budget(5000, 100, 10, true), 10);
+        // 预算不足一行：本帧第一次上传时仍上传一行，否则等下一帧
+        assert_eq!(rows_within_budget(50, 100, 10, false), 1);
+        assert_eq!(rows_within_budget(50, 100, 10, true), 0);
+        assert_eq!(rows_within_budget(50, 100, 0, false), 0);
+    }
+
+    #[test]
+    fn bytes_per_row_follows_format() {
+        let image = |format, width| DecodedImage {
+            name: String::new(),
+            texels: vec![],
+            extent: Extent3d {
+                width,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+            format,
+        };
+        assert_eq!(image(TextureFormat::Rgba8UnormSrgb, 5).bytes_per_row(), 20);
+        assert_eq!(image(TextureFormat::R16Unorm, 5).bytes_per_row(), 10);
+        assert_eq!(image(TextureFormat::Rgba32Float, 2).bytes_per_row(), 32);
+    }
+
+    #[test]
+    fn progress_counts_failures_as_processed() {
+        let mut progress = UploadProgress::default();
+        assert!(progress.is_done());
+        assert_eq!(progress.fraction(), 1.0);
+
+        progress.requested = 4;
+        progress.uploaded = 1;
+        assert!(!progress.is_done());
+        assert_eq!(progress.fraction(), 0.25);
+
+        progress.uploaded = 3;
+        progress.failed = 1;
+        assert!(progress.is_done());
+        assert_eq!(progress.fraction(), 1.0);
+    }
+}
@@ -1,36 +1,152 @@
 #![allow(dead_code)]
 use bytemuck::{Pod, Zeroable};
+
+/// `#[derive(Vertex)]` 按字段类型与 `repr(C)` 布局生成顶点属性，见 [`utils_derive::Vertex`]
+pub use utils_derive::Vertex;
+
+#[doc(hidden)]
+pub use wgpu as __wgpu;
+
 pub trait Vertex {
     fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute>;
+
+    /// 以 `vertex_attributes` 的结果构造逐顶点的缓冲区布局
+    ///
+    /// ```ignore
+    /// let attributes = PosTex::vertex_attributes(0);
+    /// let layout = PosTex::buffer_layout(&attributes);
+    /// ```
+    fn buffer_layout(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout<'_>
+    where
+        Self: Sized,
+    {
+        wgpu::VertexBufferLayout {
+            array_stride: core::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes,
+        }
+    }
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
-pub struct VertexEmpty {}
-impl Vertex for VertexEmpty {
-    fn vertex_attributes(_offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![]
+/// 顶点字段类型对应的 `VertexFormat`
+///
+/// 矩阵等占用多个 shader location 的类型，每个 location 使用一次 `FORMAT`
+pub trait VertexFormatOf {
+    const FORMAT: wgpu::VertexFormat;
+    const LOCATIONS: u32 = 1;
+}
+
+macro_rules! impl_vertex_format {
+    ($($ty:ty => $format:ident),* $(,)?) => {
+        $(impl VertexFormatOf for $ty {
+            const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::$format;
+        })*
+    };
+}
+
+impl_vertex_format! {
+    f32 => Float32,
+    [f32; 1] => Float32,
+    [f32; 2] => Float32x2,
+    [f32; 3] => Float32x3,
+    [f32; 4] => Float32x4,
+    u32 => Uint32,
+    [u32; 1] => Uint32,
+    [u32; 2] => Uint32x2,
+    [u32; 3] => Uint32x3,
+    [u32; 4] => Uint32x4,
+    i32 => Sint32,
+    [i32; 1] => Sint32,
+    [i32; 2] => Sint32x2,
+    [i32; 3] => Sint32x3,
+    [i32; 4] => Sint32x4,
+    [u16; 2] => Uint16x2,
+    [u16; 4] => Uint16x4,
+    [i16; 2] => Sint16x2,
+    [i16; 4] => Sint16x4,
+    [u8; 2] => Uint8x2,
+    [u8; 4] => Uint8x4,
+    [i8; 2] => Sint8x2,
+    [i8; 4] => Sint8x4,
+    glam::Vec2 => Float32x2,
+    glam::Vec3 => Float32x3,
+    glam::Vec4 => Float32x4,
+    glam::UVec2 => Uint32x2,
+    glam::UVec3 => Uint32x3,
+    glam::UVec4 => Uint32x4,
+    glam::IVec2 => Sint32x2,
+    glam::IVec3 => Sint32x3,
+    glam::IVec4 => Sint32x4,
+}
+
+impl VertexFormatOf for glam::Mat4 {
+    const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::Float32x4;
+    const LOCATIONS: u32 = 4;
+}
+
+impl VertexFormatOf for [[f32; 4]; 4] {
+    const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::Float32x4;
+    const LOCATIONS: u32 = 4;
+}
+
+impl VertexFormatOf for glam::Mat3 {
+    const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::Float32x3;
+    const LOCATIONS: u32 = 3;
+}
+
+impl VertexFormatOf for [[f32; 3]; 3] {
+    const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::Float32x3;
+    const LOCATIONS: u32 = 3;
+}
+
+// 以下两个函数供 `#[derive(Vertex)]` 生成的代码调用
+#[doc(hidden)]
+pub fn __push_attributes<T: VertexFormatOf>(
+    attributes: &mut Vec<wgpu::VertexAttribute>,
+    location: &mut u32,
+    offset: wgpu::BufferAddress,
+) {
+    for i in 0..T::LOCATIONS {
+        attributes.push(wgpu::VertexAttribute {
+            format: T::FORMAT,
+            offset: offset + i as u64 * T::FORMAT.size(),
+            shader_location: *location,
+        });
+        *location += 1;
     }
 }
 
+#[doc(hidden)]
+pub fn __push_format_attribute<T>(
+    attributes: &mut Vec<wgpu::VertexAttribute>,
+    location: &mut u32,
+    offset: wgpu::BufferAddress,
+    format: wgpu::VertexFormat,
+) {
+    assert!(
+        format.size() <= core::mem::size_of::<T>() as u64,
+        "vertex format {format:?} is larger than the field"
+    );
+    attributes.push(wgpu::VertexAttribute {
+        format,
+        offset,
+        shader_location: *location,
+    });
+    *location += 1;
+}
+
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
+pub struct VertexEmpty {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
 pub struct PosOnly {
     pub pos: [f32; 3],
 }
 
-impl Vertex for PosOnly {
-    fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![wgpu::VertexAttribute {
-            shader_location: offset,
-            format: wgpu::VertexFormat::Float32x3,
-            offset: 0,
-        }]
-    }
-}
-
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
 pub struct PosTex {
     pub pos: [f32; 3],
     pub tex_coord: [f32; 2],
@@ -42,79 +158,23 @@ impl PosTex {
     }
 }
 
-impl Vertex for PosTex {
-    fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![
-            wgpu::VertexAttribute {
-                shader_location: offset,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 0,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 1,
-                format: wgpu::VertexFormat::Float32x2,
-                offset: 4 * 3,
-            },
-        ]
-    }
-}
-
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
 pub struct PosColor {
     pub pos: [f32; 3],
     pub color: [f32; 4],
 }
 
-impl Vertex for PosColor {
-    fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![
-            wgpu::VertexAttribute {
-                shader_location: offset,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 0,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 1,
-                format: wgpu::VertexFormat::Float32x4,
-                offset: 4 * 3,
-            },
-        ]
-    }
-}
-
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
 pub struct PosNormalUv {
     pub pos: [f32; 3],
     pub normal: [f32; 3],
     pub uv: [f32; 2],
 }
 
-impl Vertex for PosNormalUv {
-    fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![
-            wgpu::VertexAttribute {
-                shader_location: offset,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 0,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 1,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 4 * 3,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 2,
-                format: wgpu::VertexFormat::Float32x2,
-                offset: 4 * 6,
-            },
-        ]
-    }
-}
-
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
 pub struct PosNormalUvIndex {
     pub pos: [f32; 3],
     pub normal: [f32; 3],
@@ -122,29 +182,60 @@ pub struct PosNormalUvIndex {
     pub index: u32,
 }
 
-impl Vertex for PosNormalUvIndex {
-    fn vertex_attributes(offset: u32) -> Vec<wgpu::VertexAttribute> {
-        vec![
-            wgpu::VertexAttribute {
-                shader_location: offset,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 0,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 1,
-                format: wgpu::VertexFormat::Float32x3,
-                offset: 4 * 3,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 2,
-                format: wgpu::VertexFormat::Float32x2,
-                offset: 4 * 6,
-            },
-            wgpu::VertexAttribute {
-                shader_location: offset + 3,
-                format: wgpu::VertexFormat::Uint32,
-                offset: 4 * 8,
-            },
-        ]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Vertex)]
+pub struct PosNormalUvTangent {
+    pub pos: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    /// w 分量为副切线的方向（±1）
+    pub tangent: [f32; 4],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derived_offsets_follow_repr_c_layout() {
+        let attributes = PosNormalUvIndex::vertex_attributes(2);
+        let expected = [
+            (2, wgpu::VertexFormat::Float32x3, 0),
+            (3, wgpu::VertexFormat::Float32x3, 4 * 3),
+            (4, wgpu::VertexFormat::Float32x2, 4 * 6),
+            (5, wgpu::VertexFormat::Uint32, 4 * 8),
+        ];
+        assert_eq!(attributes.len(), expected.len());
+        for (attr, (location, format, offset)) in attributes.iter().zip(expected) {
+            assert_eq!(
+                (attr.shader_location, attr.format, attr.offset),
+                (location, format, offset)
+            );
+        }
+        let layout = PosNormalUvIndex::buffer_layout(&attributes);
+        assert_eq!(layout.array_stride, 4 * 9);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Vertex)]
+    struct Instance {
+        model: glam::Mat4,
+        #[skip]
+        _id: u32,
+        #[location = 7]
+        #[format = "Unorm8x4"]
+        color: [u8; 4],
+        scale: f32,
+    }
+
+    #[test]
+    fn location_skip_and_format_attributes() {
+        let attributes = Instance::vertex_attributes(1);
+        let locations: Vec<_> = attributes.iter().map(|a| a.shader_location).collect();
+        assert_eq!(locations, [1, 2, 3, 4, 8, 9]);
+        assert_eq!(attributes[3].offset, 48);
+        assert_eq!(attributes[4].format, wgpu::VertexFormat::Unorm8x4);
+        assert_eq!(attributes[4].offset, 68);
+        assert_eq!(attributes[5].offset, 72);
     }
 }